@@ -0,0 +1,112 @@
+//! Derive macro for the `markdown-gen` crate, enabled by its `derive` feature
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+/// Derives `ToMarkdown`, which writes fields of a struct as a table or a definition list
+///
+/// Container attributes:
+/// * `#[markdown(style = "table")]` - Field/value table, the default
+/// * `#[markdown(style = "list")]` - Definition list
+///
+/// Field attributes:
+/// * `#[markdown(rename = "Header")]` - Name written instead of the field name
+/// * `#[markdown(code)]` - Value is written as inline code
+/// * `#[markdown(skip)]` - Field is not written
+///
+/// Values are written using their `Display` implementation.
+#[proc_macro_derive(ToMarkdown, attributes(markdown))]
+pub fn derive_to_markdown(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let mut list = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("markdown")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("style") {
+                let style: LitStr = meta.value()?.parse()?;
+                list = match style.value().as_str() {
+                    "table" => false,
+                    "list" => true,
+                    _ => return Err(meta.error("expected \"table\" or \"list\"")),
+                };
+                Ok(())
+            } else {
+                Err(meta.error("unsupported markdown attribute"))
+            }
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "ToMarkdown can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "ToMarkdown can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut calls = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let mut name = ident.to_string();
+        let mut code = false;
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("markdown")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let rename: LitStr = meta.value()?.parse()?;
+                    name = rename.value();
+                } else if meta.path.is_ident("code") {
+                    code = true;
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else {
+                    return Err(meta.error("unsupported markdown attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        if skip {
+            continue;
+        }
+        let method = if code {
+            quote!(code_field)
+        } else {
+            quote!(field)
+        };
+        calls.push(quote! {
+            .#method(#name, ::markdown_gen::__private::ToString::to_string(&self.#ident))
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let constructor = if list {
+        quote!(definition_list)
+    } else {
+        quote!(table)
+    };
+    Ok(quote! {
+        impl #impl_generics ::markdown_gen::markdown::ToMarkdown for #name #ty_generics #where_clause {
+            fn to_markdown(&self) -> ::markdown_gen::markdown::FieldList {
+                ::markdown_gen::markdown::FieldList::#constructor() #(#calls)*
+            }
+        }
+    })
+}
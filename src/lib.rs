@@ -1 +1,20 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// Tests run on a host with `std`, also when the crate is built without it
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+// Allows the derive macro to refer to this crate by name inside its own tests
+extern crate self as markdown_gen;
+
 pub mod markdown;
+#[cfg(feature = "serde")]
+pub mod ser;
+
+// Paths used by code generated by the derive macro, which must work without `std`
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::string::ToString;
+}
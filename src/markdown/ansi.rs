@@ -0,0 +1,236 @@
+//! Conversion of ANSI-colored terminal output, e.g. of a failed CI job
+//!
+//! Available with the `ansi` feature.
+
+use super::io::{Error, Write};
+use super::{
+    display, escape_html, skip_escape_sequence, BlockElement, CodeBlock, Html, MarkdownWritable,
+    RenderContext,
+};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Terminal output with ANSI escape sequences
+///
+/// Written as a plain code block without the escape sequences by default,
+/// or as an HTML `<pre>` block with colors preserved by [html](#method.html).
+pub struct AnsiOutput<'a> {
+    text: Cow<'a, str>,
+    html: bool,
+}
+
+impl<'a> AnsiOutput<'a> {
+    /// Creates terminal output from captured `text`
+    pub fn new<T: Into<Cow<'a, str>>>(text: T) -> Self {
+        Self {
+            text: text.into(),
+            html: false,
+        }
+    }
+
+    /// Writes the output as HTML with colored `<span>` elements
+    ///
+    /// GitHub strips inline styles, so the colors are shown only by renderers allowing them.
+    pub fn html(mut self) -> Self {
+        self.html = true;
+        self
+    }
+
+    /// Returns the output without escape sequences
+    pub fn plain(&self) -> String {
+        parse(&self.text)
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect()
+    }
+
+    /// Returns the output as a `<pre>` element with colored spans
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<pre>");
+        for (style, text) in parse(&self.text) {
+            let css = style.css();
+            if css.is_empty() {
+                html.push_str(&escape_html(&text));
+            } else {
+                html.push_str(&format!(
+                    "<span style=\"{}\">{}</span>",
+                    css,
+                    escape_html(&text)
+                ));
+            }
+        }
+        html.push_str("</pre>");
+        html
+    }
+}
+
+/// Text attributes set by SGR sequences, e.g. `ESC [ 1 ; 31 m`
+#[derive(Clone, Copy, Default, PartialEq)]
+struct Style {
+    foreground: Option<(u8, u8, u8)>,
+    background: Option<(u8, u8, u8)>,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+}
+
+impl Style {
+    /// Applies SGR parameters separated by `;`
+    fn apply(&mut self, parameters: &str) {
+        let mut codes = parameters
+            .split(';')
+            .map(|code| code.parse::<u8>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                22 => self.bold = false,
+                23 => self.italic = false,
+                24 => self.underline = false,
+                30..=37 => self.foreground = Some(palette(code - 30)),
+                38 => self.foreground = extended_color(&mut codes),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(palette(code - 40)),
+                48 => self.background = extended_color(&mut codes),
+                49 => self.background = None,
+                90..=97 => self.foreground = Some(palette(code - 90 + 8)),
+                100..=107 => self.background = Some(palette(code - 100 + 8)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the inline CSS of the style, empty for the default style
+    fn css(&self) -> String {
+        let mut css = Vec::new();
+        if let Some((r, g, b)) = self.foreground {
+            css.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if let Some((r, g, b)) = self.background {
+            css.push(format!("background-color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if self.bold {
+            css.push("font-weight:bold".into());
+        }
+        if self.italic {
+            css.push("font-style:italic".into());
+        }
+        if self.underline {
+            css.push("text-decoration:underline".into());
+        }
+        css.join(";")
+    }
+}
+
+/// Reads a 256-color `5;n` or true color `2;r;g;b` argument of SGR 38 or 48
+fn extended_color<I: Iterator<Item = u8>>(codes: &mut I) -> Option<(u8, u8, u8)> {
+    match codes.next()? {
+        5 => Some(palette(codes.next()?)),
+        2 => Some((codes.next()?, codes.next()?, codes.next()?)),
+        _ => None,
+    }
+}
+
+/// Color of the xterm 256-color palette
+fn palette(index: u8) -> (u8, u8, u8) {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    match index {
+        0..=15 => BASIC[index as usize],
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+/// Splits `text` into runs of text with the same style,
+/// escape sequences other than colors and control characters except line breaks and tabs are dropped
+fn parse(text: &str) -> Vec<(Style, String)> {
+    let mut runs: Vec<(Style, String)> = Vec::new();
+    let mut style = Style::default();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' if chars.peek() == Some(&'[') => {
+                chars.next();
+                let mut parameters = String::new();
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        if c == 'm' {
+                            style.apply(&parameters);
+                        }
+                        break;
+                    }
+                    parameters.push(c);
+                }
+            }
+            '\u{1b}' => skip_escape_sequence(&mut chars),
+            c if c.is_control() && c != '\n' && c != '\t' => {}
+            c => match runs.last_mut() {
+                Some((last, text)) if *last == style => text.push(c),
+                _ => runs.push((style, c.into())),
+            },
+        }
+    }
+    runs
+}
+
+impl MarkdownWritable for &'_ AnsiOutput<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        if self.html {
+            Html::new(self.to_html()).write_to(writer, context)
+        } else {
+            CodeBlock::new(self.plain()).write_to(writer, context)
+        }
+    }
+
+    fn count_max_streak(&self, _char: u8, _carry: usize) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+impl MarkdownWritable for AnsiOutput<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+impl fmt::Display for AnsiOutput<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        display(&self, f)
+    }
+}
+
+impl BlockElement for AnsiOutput<'_> {}
+impl BlockElement for &'_ AnsiOutput<'_> {}
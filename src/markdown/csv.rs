@@ -0,0 +1,27 @@
+//! Conversion of CSV data to tables
+//!
+//! Available with the `csv` feature.
+
+use super::Table;
+use ::csv::{Error, ReaderBuilder};
+use std::io::Read;
+
+impl Table<'static> {
+    /// Creates a table from CSV data, the first record is used as the header
+    ///
+    /// Records may have different numbers of fields, missing cells are left empty.
+    /// Cells are written as text, so they are escaped and line breaks are kept as `<br>`.
+    /// Available with the `csv` feature.
+    ///
+    /// # Returns
+    /// Table or `csv::Error` if the data cannot be read or is not valid CSV
+    pub fn from_csv_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+        let header: Vec<String> = reader.headers()?.iter().map(String::from).collect();
+        let mut table = Table::new().header(header);
+        for record in reader.records() {
+            table = table.row(record?.iter().map(String::from).collect::<Vec<_>>());
+        }
+        Ok(table)
+    }
+}
@@ -0,0 +1,210 @@
+//! Human readable formatting of byte sizes, durations and large numbers
+//!
+//! Available with the `humanize` feature.
+
+use super::{InlineElement, MarkdownWritable, RenderContext, RichText};
+use std::fmt;
+use std::io::{Error, Write};
+use std::time::Duration;
+
+/// Byte size formatted with SI units, e.g. `1.4 GB`
+#[derive(Clone, Copy)]
+pub struct Bytes {
+    value: u64,
+    code: bool,
+}
+
+impl Bytes {
+    /// Creates a byte size of `value` bytes
+    pub fn new(value: u64) -> Self {
+        Self { value, code: false }
+    }
+
+    /// Writes the size as inline code instead of text
+    pub fn code(mut self) -> Self {
+        self.code = true;
+        self
+    }
+}
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.value < 1000 {
+            return write!(f, "{} B", self.value);
+        }
+        let (value, unit) = scale(
+            self.value as f64,
+            &["B", "kB", "MB", "GB", "TB", "PB", "EB"],
+        );
+        write!(f, "{} {}", value, unit)
+    }
+}
+
+impl MarkdownWritable for &'_ Bytes {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        write_formatted(&self.to_string(), self.code, writer, context)
+    }
+
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
+    }
+}
+
+impl MarkdownWritable for Bytes {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+impl InlineElement for Bytes {}
+impl InlineElement for &'_ Bytes {}
+
+/// Duration formatted with up to two most significant units, e.g. `3m 12s`
+#[derive(Clone, Copy)]
+pub struct HumanDuration {
+    value: Duration,
+    code: bool,
+}
+
+impl HumanDuration {
+    /// Creates a formatted duration
+    pub fn new(value: Duration) -> Self {
+        Self { value, code: false }
+    }
+
+    /// Writes the duration as inline code instead of text
+    pub fn code(mut self) -> Self {
+        self.code = true;
+        self
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.value.as_secs();
+        if seconds == 0 {
+            return write!(f, "{}ms", self.value.subsec_millis());
+        }
+        let units = [
+            (seconds / 86400, "d"),
+            (seconds / 3600 % 24, "h"),
+            (seconds / 60 % 60, "m"),
+            (seconds % 60, "s"),
+        ];
+        let first = units.iter().position(|(value, _)| *value > 0).unwrap_or(3);
+        write!(f, "{}{}", units[first].0, units[first].1)?;
+        if let Some((value, unit)) = units.get(first + 1) {
+            if *value > 0 {
+                write!(f, " {}{}", value, unit)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MarkdownWritable for &'_ HumanDuration {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        write_formatted(&self.to_string(), self.code, writer, context)
+    }
+
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
+    }
+}
+
+impl MarkdownWritable for HumanDuration {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+impl InlineElement for HumanDuration {}
+impl InlineElement for &'_ HumanDuration {}
+
+/// Large number formatted with a suffix, e.g. `1.2M`
+#[derive(Clone, Copy)]
+pub struct Count {
+    value: u64,
+    code: bool,
+}
+
+impl Count {
+    /// Creates a formatted number
+    pub fn new(value: u64) -> Self {
+        Self { value, code: false }
+    }
+
+    /// Writes the number as inline code instead of text
+    pub fn code(mut self) -> Self {
+        self.code = true;
+        self
+    }
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.value < 1000 {
+            return write!(f, "{}", self.value);
+        }
+        let (value, unit) = scale(self.value as f64, &["", "k", "M", "B", "T", "Q"]);
+        write!(f, "{}{}", value, unit)
+    }
+}
+
+impl MarkdownWritable for &'_ Count {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        write_formatted(&self.to_string(), self.code, writer, context)
+    }
+
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
+    }
+}
+
+impl MarkdownWritable for Count {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+impl InlineElement for Count {}
+impl InlineElement for &'_ Count {}
+
+/// Divides `value` by 1000 until it fits the unit
+///
+/// # Returns
+/// `(value, unit)` with value rounded to one decimal place, trailing `.0` is omitted
+fn scale(mut value: f64, units: &[&'static str]) -> (String, &'static str) {
+    let mut unit = 0;
+    while unit + 1 < units.len() && (value * 10.0).round() / 10.0 >= 1000.0 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    let rounded = format!("{:.1}", value);
+    let rounded = rounded.strip_suffix(".0").unwrap_or(&rounded).to_string();
+    (rounded, units[unit])
+}
+
+/// Writes formatted `text` as text or inline code
+fn write_formatted(
+    text: &str,
+    code: bool,
+    writer: &mut dyn Write,
+    context: &RenderContext<'_>,
+) -> Result<(), Error> {
+    let mut text = RichText::new(text);
+    text.code = code;
+    text.write_to(writer, context)
+}
@@ -0,0 +1,134 @@
+//! Writer and error types used when writing elements
+//!
+//! [MarkdownWritable](../trait.MarkdownWritable.html) elements are written to a `Write`
+//! and fail with an `Error`.
+//!
+//! With the `std` feature these are the types of `std::io`.
+//! Without it, a minimal replacement is provided, implemented for `Vec<u8>`
+//! and [FmtWriter](../struct.FmtWriter.html), which writes to an `alloc::string::String`.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::no_std::{Error, ErrorKind, Write};
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use alloc::vec::Vec;
+    use core::fmt;
+
+    /// Category of an [Error](struct.Error.html)
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        /// Data written is not valid, e.g. not valid UTF-8
+        InvalidData,
+        /// Element cannot be written, e.g. it is empty in strict mode
+        InvalidInput,
+        /// Writer accepted no more data
+        WriteZero,
+        /// Any other error
+        Other,
+    }
+
+    /// Error returned by a [Write](trait.Write.html)
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: &'static str,
+    }
+
+    impl Error {
+        /// Creates an error of `kind` with a `message`
+        pub fn new(kind: ErrorKind, message: &'static str) -> Self {
+            Self { kind, message }
+        }
+
+        /// Creates an error of kind [Other](enum.ErrorKind.html#variant.Other)
+        pub fn other(message: &'static str) -> Self {
+            Self::new(ErrorKind::Other, message)
+        }
+
+        /// Returns the category of the error
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    /// Byte-oriented sink, a subset of `std::io::Write`
+    pub trait Write {
+        /// Writes a part of `buf`, returning the number of bytes written
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error>;
+
+        /// Flushes buffered data
+        fn flush(&mut self) -> Result<(), Error>;
+
+        /// Writes the whole `buf`
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<(), Error> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+
+        /// Writes formatted text, used by `write!`
+        fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), Error> {
+            struct Adapter<'w, W: ?Sized> {
+                writer: &'w mut W,
+                error: Option<Error>,
+            }
+
+            impl<W: Write + ?Sized> fmt::Write for Adapter<'_, W> {
+                fn write_str(&mut self, s: &str) -> fmt::Result {
+                    self.writer.write_all(s.as_bytes()).map_err(|error| {
+                        self.error = Some(error);
+                        fmt::Error
+                    })
+                }
+            }
+
+            let mut adapter = Adapter {
+                writer: self,
+                error: None,
+            };
+            fmt::write(&mut adapter, args)
+                .map_err(|_| adapter.error.unwrap_or(Error::other("formatter error")))
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+            (**self).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            (**self).flush()
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Export of elements as Jupyter notebook cells
+//!
+//! Available with the `jupyter` feature.
+
+use super::{CodeBlock, Escaping, MarkdownWritable, Options, RenderContext};
+use serde_json::{json, Value};
+use std::io;
+use std::io::Write;
+
+enum Cell<'a> {
+    Markdown(Box<dyn 'a + MarkdownWritable>),
+    Code(CodeBlock<'a>),
+    Break,
+}
+
+/// Jupyter notebook (nbformat 4) built from markdown elements
+///
+/// Consecutive elements are joined into a single markdown cell,
+/// a new cell is started by [cell_break](#method.cell_break)
+/// and optionally before every heading.
+pub struct Notebook<'a> {
+    cells: Vec<Cell<'a>>,
+    split_at_headings: bool,
+    code_cells: bool,
+    language: Option<String>,
+}
+
+impl<'a> Notebook<'a> {
+    /// Creates an empty notebook
+    pub fn new() -> Self {
+        Self {
+            cells: Vec::new(),
+            split_at_headings: false,
+            code_cells: false,
+            language: None,
+        }
+    }
+
+    /// Starts a new markdown cell before every element containing a heading
+    pub fn split_at_headings(mut self, split: bool) -> Self {
+        self.split_at_headings = split;
+        self
+    }
+
+    /// Writes code blocks added by [code](#method.code) as code cells instead of markdown
+    pub fn code_cells(mut self, code_cells: bool) -> Self {
+        self.code_cells = code_cells;
+        self
+    }
+
+    /// Sets the language of the notebook kernel, e.g. `python`
+    pub fn language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    /// Appends an element to the current markdown cell
+    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
+        self.cells.push(Cell::Markdown(Box::new(element)));
+        self
+    }
+
+    /// Appends a code block, which becomes a code cell if [code_cells](#method.code_cells) is set
+    pub fn code(mut self, block: CodeBlock<'a>) -> Self {
+        self.cells.push(Cell::Code(block));
+        self
+    }
+
+    /// Ends the current markdown cell
+    pub fn cell_break(mut self) -> Self {
+        self.cells.push(Cell::Break);
+        self
+    }
+
+    /// Converts the notebook to its JSON representation
+    ///
+    /// # Returns
+    /// JSON of the notebook or `std::io::Error` if an element failed to render
+    pub fn to_json(&self) -> Result<Value, io::Error> {
+        let options = Options::new();
+        let mut cells = Vec::new();
+        let mut markdown = Vec::new();
+        for cell in &self.cells {
+            match cell {
+                Cell::Markdown(element) => {
+                    if self.split_at_headings && !element.headings(&options).is_empty() {
+                        push_markdown_cell(&mut cells, &mut markdown);
+                    }
+                    write_element(&mut markdown, element.as_ref(), &options)?;
+                }
+                Cell::Code(block) if self.code_cells => {
+                    push_markdown_cell(&mut cells, &mut markdown);
+                    cells.push(json!({
+                        "cell_type": "code",
+                        "execution_count": null,
+                        "metadata": {},
+                        "outputs": [],
+                        "source": source_lines(&block.code),
+                    }));
+                }
+                Cell::Code(block) => write_element(&mut markdown, block, &options)?,
+                Cell::Break => push_markdown_cell(&mut cells, &mut markdown),
+            }
+        }
+        push_markdown_cell(&mut cells, &mut markdown);
+
+        let mut metadata = json!({});
+        if let Some(language) = &self.language {
+            metadata["language_info"] = json!({ "name": language });
+        }
+        Ok(json!({
+            "cells": cells,
+            "metadata": metadata,
+            "nbformat": 4,
+            "nbformat_minor": 4,
+        }))
+    }
+
+    /// Writes the notebook as JSON to `writer`
+    ///
+    /// # Returns
+    /// `writer` or `std::io::Error` if an element failed to render or an error occurred during writing
+    pub fn render<W: Write>(&self, mut writer: W) -> Result<W, io::Error> {
+        serde_json::to_writer_pretty(&mut writer, &self.to_json()?)?;
+        writer.write_all(b"\n")?;
+        Ok(writer)
+    }
+}
+
+impl Default for Notebook<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn write_element(
+    markdown: &mut Vec<u8>,
+    element: &dyn MarkdownWritable,
+    options: &Options,
+) -> Result<(), io::Error> {
+    element.write_to(
+        markdown,
+        &RenderContext::new(false, Escaping::Normal, None, options),
+    )
+}
+
+/// Moves collected markdown into a new cell, empty markdown is skipped
+fn push_markdown_cell(cells: &mut Vec<Value>, markdown: &mut Vec<u8>) {
+    let text = String::from_utf8_lossy(markdown).into_owned();
+    markdown.clear();
+    let text = text.trim_matches('\n');
+    if text.is_empty() {
+        return;
+    }
+    cells.push(json!({
+        "cell_type": "markdown",
+        "metadata": {},
+        "source": source_lines(text),
+    }));
+}
+
+/// Splits `text` into lines keeping the line breaks, as stored in notebooks
+fn source_lines(text: &str) -> Vec<&str> {
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    text.split_inclusive('\n').collect()
+}
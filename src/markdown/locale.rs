@@ -0,0 +1,303 @@
+//! Locale-aware formatting of numbers, e.g. `1,234.56` or `1.234,56`,
+//! percentages and amounts of money
+//!
+//! Available with the `locale` feature. The numbers are right-aligned in
+//! [Table](../struct.Table.html) columns without explicit alignment.
+
+use super::io::{Error, Write};
+use super::{Alignment, InlineElement, MarkdownWritable, RenderContext, RichText};
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+
+/// Digit grouping and decimal separators of a locale
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Locale {
+    grouping: Option<char>,
+    decimal: char,
+}
+
+impl Locale {
+    /// English formatting, `1,234.56`
+    pub const EN: Locale = Locale::new(Some(','), '.');
+    /// German formatting, `1.234,56`
+    pub const DE: Locale = Locale::new(Some('.'), ',');
+    /// French formatting, `1 234,56` grouped with a narrow no-break space
+    pub const FR: Locale = Locale::new(Some('\u{202f}'), ',');
+    /// Swiss formatting, `1'234.56`
+    pub const CH: Locale = Locale::new(Some('\''), '.');
+
+    /// Creates a locale with custom separators
+    ///
+    /// # Arguments
+    /// * `grouping` - Separator of thousands, `None` disables grouping
+    /// * `decimal` - Separator of the fractional part
+    pub const fn new(grouping: Option<char>, decimal: char) -> Self {
+        Self { grouping, decimal }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::EN
+    }
+}
+
+/// Number formatted according to a [Locale](struct.Locale.html)
+#[derive(Clone, Copy)]
+pub struct Number {
+    value: f64,
+    precision: usize,
+    locale: Locale,
+    code: bool,
+}
+
+impl Number {
+    /// Creates a number written with no decimal places in the English locale
+    pub fn new<T: Into<f64>>(value: T) -> Self {
+        Self {
+            value: value.into(),
+            precision: 0,
+            locale: Locale::EN,
+            code: false,
+        }
+    }
+
+    /// Sets number of decimal places
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Sets the locale used for separators
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Writes the number as inline code instead of text
+    pub fn code(mut self) -> Self {
+        self.code = true;
+        self
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let formatted = format!("{:.*}", self.precision, self.value);
+        let (sign, digits) = match formatted.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", formatted.as_str()),
+        };
+        let (integer, fraction) = match digits.find('.') {
+            Some(at) => (&digits[..at], Some(&digits[at + 1..])),
+            None => (digits, None),
+        };
+
+        f.write_str(sign)?;
+        for (i, digit) in integer.chars().enumerate() {
+            if let Some(grouping) = self.locale.grouping {
+                if i > 0 && (integer.len() - i) % 3 == 0 {
+                    write!(f, "{}", grouping)?;
+                }
+            }
+            write!(f, "{}", digit)?;
+        }
+        if let Some(fraction) = fraction {
+            write!(f, "{}{}", self.locale.decimal, fraction)?;
+        }
+        Ok(())
+    }
+}
+
+impl MarkdownWritable for &'_ Number {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let mut text = RichText::new(self.to_string());
+        text.code = self.code;
+        text.write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
+    }
+
+    fn preferred_alignment(&self) -> Option<Alignment> {
+        Some(Alignment::Right)
+    }
+}
+
+impl MarkdownWritable for Number {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn preferred_alignment(&self) -> Option<Alignment> {
+        (&self).preferred_alignment()
+    }
+}
+
+impl InlineElement for Number {}
+impl InlineElement for &'_ Number {}
+
+/// Ratio formatted as a percentage, e.g. `12.5%` for `0.125`
+#[derive(Clone, Copy)]
+pub struct Percent {
+    number: Number,
+}
+
+impl Percent {
+    /// Creates a percentage of `ratio` written with no decimal places in the English locale
+    pub fn new<T: Into<f64>>(ratio: T) -> Self {
+        Self {
+            number: Number::new(ratio.into() * 100.0),
+        }
+    }
+
+    /// Sets number of decimal places
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.number = self.number.precision(precision);
+        self
+    }
+
+    /// Sets the locale used for separators
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.number = self.number.locale(locale);
+        self
+    }
+
+    /// Writes the percentage as inline code instead of text
+    pub fn code(mut self) -> Self {
+        self.number = self.number.code();
+        self
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.number)
+    }
+}
+
+/// Placement of the symbol of a [Currency](struct.Currency.html) amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SymbolPlacement {
+    /// Symbol before the amount, e.g. `$1,234.50` or `-$5.00`
+    #[default]
+    Before,
+    /// Symbol after the amount separated by a no-break space, e.g. `1.234,50 €`
+    After,
+}
+
+/// Amount of money formatted with a currency symbol, e.g. `$1,234.50`
+#[derive(Clone)]
+pub struct Currency {
+    number: Number,
+    symbol: String,
+    placement: SymbolPlacement,
+}
+
+impl Currency {
+    /// Creates an amount written with two decimal places in the English locale
+    ///
+    /// # Arguments
+    /// * `amount` - Amount of money
+    /// * `symbol` - Symbol or code of the currency, e.g. `$` or `CHF`
+    pub fn new<T: Into<f64>, S: Into<String>>(amount: T, symbol: S) -> Self {
+        Self {
+            number: Number::new(amount).precision(2),
+            symbol: symbol.into(),
+            placement: SymbolPlacement::Before,
+        }
+    }
+
+    /// Sets number of decimal places
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.number = self.number.precision(precision);
+        self
+    }
+
+    /// Sets the locale used for separators
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.number = self.number.locale(locale);
+        self
+    }
+
+    /// Sets the placement of the symbol
+    pub fn placement(mut self, placement: SymbolPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Writes the amount as inline code instead of text
+    pub fn code(mut self) -> Self {
+        self.number = self.number.code();
+        self
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number = self.number.to_string();
+        match self.placement {
+            SymbolPlacement::Before => match number.strip_prefix('-') {
+                Some(number) => write!(f, "-{}{}", self.symbol, number),
+                None => write!(f, "{}{}", self.symbol, number),
+            },
+            SymbolPlacement::After => write!(f, "{}\u{a0}{}", number, self.symbol),
+        }
+    }
+}
+
+/// Implements [MarkdownWritable](../trait.MarkdownWritable.html) for a formatted number
+/// written as text or inline code and right-aligned in tables
+macro_rules! formatted_number {
+    ($type:ty) => {
+        impl MarkdownWritable for &'_ $type {
+            fn write_to(
+                &self,
+                writer: &mut dyn Write,
+                context: &RenderContext<'_>,
+            ) -> Result<(), Error> {
+                let mut text = RichText::new(self.to_string());
+                text.code = self.number.code;
+                text.write_to(writer, context)
+            }
+
+            fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+                (carry, 0)
+            }
+
+            fn preferred_alignment(&self) -> Option<Alignment> {
+                Some(Alignment::Right)
+            }
+        }
+
+        impl MarkdownWritable for $type {
+            fn write_to(
+                &self,
+                writer: &mut dyn Write,
+                context: &RenderContext<'_>,
+            ) -> Result<(), Error> {
+                (&self).write_to(writer, context)
+            }
+
+            fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+                (&self).count_max_streak(char, carry)
+            }
+
+            fn preferred_alignment(&self) -> Option<Alignment> {
+                (&self).preferred_alignment()
+            }
+        }
+
+        impl InlineElement for $type {}
+        impl InlineElement for &'_ $type {}
+    };
+}
+
+formatted_number!(Percent);
+formatted_number!(Currency);
@@ -1,9 +1,38 @@
-use std::io;
-use std::io::{Error, Write};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt;
+use core::iter::FromIterator;
+use io::{Error, Write};
+use sync::Mutex;
 use Escaping::{InlineCode, Normal};
 
+#[cfg(feature = "ansi")]
+pub mod ansi;
+#[cfg(feature = "csv")]
+mod csv;
+#[cfg(feature = "derive")]
+pub use markdown_gen_derive::ToMarkdown;
+#[cfg(feature = "humanize")]
+pub mod humanize;
+pub mod io;
+#[cfg(feature = "jupyter")]
+pub mod jupyter;
+#[cfg(feature = "locale")]
+pub mod locale;
+#[cfg(feature = "pandoc")]
+pub mod pandoc;
+mod sync;
 #[cfg(test)]
 mod tests;
+#[cfg(feature = "tokio")]
+pub mod tokio;
 
 /// Specifies string escaping mode
 #[derive(Clone, Copy)]
@@ -14,713 +43,9086 @@ pub enum Escaping {
     InlineCode,
 }
 
-/// Struct for generating Markdown
-pub struct Markdown<W: Write> {
-    writer: W,
+/// Kind of text, which determines the characters escaped by [needs_escape](fn.needs_escape.html)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EscapeContext {
+    /// Ordinary text written in `flavor`
+    Text(Flavor),
+    /// Link or image address written in `flavor`
+    ///
+    /// Except on Telegram, addresses are not backslash-escaped. Spaces, control characters,
+    /// `<`, `>` and unbalanced parentheses are percent-encoded instead.
+    Address(Flavor),
+    /// Inline code written in `flavor`, which is only escaped on Telegram
+    InlineCode(Flavor),
+    /// Quoted string, e.g. an image title or an attribute value
+    Quoted,
 }
 
-impl<W: Write> Markdown<W> {
-    /// Creates a new [Markdown](struct.Markdown.html) struct
-    ///
-    /// # Arguments
+impl EscapeContext {
+    /// ASCII characters escaped with a backslash, see [write_escaped](fn.write_escaped.html)
+    pub fn escaped(self) -> &'static [u8] {
+        match self {
+            EscapeContext::Text(flavor) => flavor.escape(),
+            EscapeContext::Address(flavor) => flavor.address_escape(),
+            EscapeContext::InlineCode(Flavor::Telegram) => b"\\`",
+            EscapeContext::InlineCode(_) => b"",
+            EscapeContext::Quoted => b"\\\"",
+        }
+    }
+}
+
+/// Returns whether `c` is escaped with a backslash when written in `context`
+///
+/// This is the escaping policy used by all elements, inline code is instead
+/// surrounded by enough backticks except on Telegram.
+/// Inside [Table](struct.Table.html) cells `|` is escaped as well.
+/// With [smart escaping](struct.Markdown.html#method.with_smart_escaping)
+/// text is escaped only where needed and this is an upper bound.
+pub fn needs_escape(c: char, context: EscapeContext) -> bool {
+    c.is_ascii() && context.escaped().contains(&(c as u8))
+}
+
+/// Callback transforming text before it is escaped and written
+pub type Transform = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Callback receiving non-fatal issues reported during writing
+pub type WarningHandler = Arc<dyn Fn(&Warning) + Send + Sync>;
+
+/// Kind of a [Warning](struct.Warning.html)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WarningKind {
+    /// Text was changed to be written, e.g. control characters were removed
+    LossyEscaping,
+    /// An element was written in a simpler form supported by the flavor, e.g. a heading as bold text
+    Degraded,
+    /// Part of an element was left out, e.g. table rows over the limit
+    Truncated,
+    /// An empty element was written leniently
+    Empty,
+}
+
+/// Non-fatal issue encountered during writing,
+/// see [Markdown::with_warning_handler](struct.Markdown.html#method.with_warning_handler)
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Warning {
+    /// Kind of the issue
+    pub kind: WarningKind,
+    /// Description of the issue, e.g. `heading level 4 written as bold text`
+    pub message: String,
+    /// Titles of the headings enclosing the element, see [HeadingPath](struct.HeadingPath.html)
+    pub heading_path: Vec<String>,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if !self.heading_path.is_empty() {
+            write!(f, " in {}", self.heading_path.join(" > "))?;
+        }
+        Ok(())
+    }
+}
+
+/// `(label, address)` of reference links shared by clones of [Options](struct.Options.html)
+type LinkDefinitions = Arc<Mutex<Vec<(String, String)>>>;
+
+/// Counters of numbered headings shared by clones of [Options](struct.Options.html)
+type HeadingNumbers = Arc<Mutex<HeadingNumbering>>;
+
+/// `(level, text, id)` of headings as they were written, including their numbers,
+/// collected for the [Toc](struct.Toc.html) of [Markdown](struct.Markdown.html)
+type WrittenHeadings = Arc<Mutex<Vec<(usize, String, Option<String>)>>>;
+
+/// Output of an element rendered for a [Fanout](struct.Fanout.html) writer and headings it contains
+type Rendered = (Vec<u8>, Vec<(usize, String, Option<String>)>);
+
+/// Titles of the headings enclosing the element being written, e.g. `Install > Linux > Arch`
+///
+/// The path is updated as headings are written and shared by clones, so a clone obtained by
+/// [Markdown::heading_path](struct.Markdown.html#method.heading_path) can be moved into
+/// a link rewriter or a warning handler.
+#[derive(Clone, Default)]
+pub struct HeadingPath(Arc<Mutex<Vec<(usize, String)>>>);
+
+impl HeadingPath {
+    /// Returns the titles from the top level heading to the innermost one
+    pub fn titles(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .iter()
+            .map(|(_, title)| title.clone())
+            .collect()
+    }
+
+    /// Returns `true` if no heading was written yet
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().is_empty()
+    }
+
+    /// Leaves headings of `level` and lower, then enters a heading titled `title`
+    fn enter(&self, level: usize, title: String) {
+        let mut path = self.0.lock();
+        path.retain(|(l, _)| *l < level);
+        path.push((level, title));
+    }
+}
+
+impl fmt::Debug for HeadingPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HeadingPath").field(&self.titles()).finish()
+    }
+}
+
+impl fmt::Display for HeadingPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.titles().join(" > "))
+    }
+}
+
+/// State of automatic heading numbering,
+/// see [Markdown::with_heading_numbers](struct.Markdown.html#method.with_heading_numbers)
+#[derive(Default)]
+struct HeadingNumbering {
+    counters: [usize; 6],
+    /// Highest level numbered so far, `0` if no heading was numbered
+    top: usize,
+}
+
+impl HeadingNumbering {
+    /// Advances the counter of `level` and returns the number, e.g. `1.` or `1.2`
+    fn next(&mut self, level: usize) -> String {
+        self.counters[level - 1] += 1;
+        for counter in &mut self.counters[level..] {
+            *counter = 0;
+        }
+        if self.top == 0 || level < self.top {
+            self.top = level;
+        }
+        let parts: Vec<String> = self.counters[self.top - 1..level]
+            .iter()
+            .map(usize::to_string)
+            .collect();
+        if parts.len() == 1 {
+            format!("{}.", parts[0])
+        } else {
+            parts.join(".")
+        }
+    }
+}
+
+/// Kind of text a [Transform](type.Transform.html) is applied to
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TextRole {
+    /// Any text, unless a more specific transform is set
+    #[default]
+    Text,
+    /// Text inside a [Heading](struct.Heading.html)
+    Heading,
+    /// Text inside a header row of a [Table](struct.Table.html)
+    TableHeader,
+}
+
+/// Target platform, which determines escaping and syntax of written elements
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Flavor {
+    /// CommonMark
+    #[default]
+    CommonMark,
+    /// GitHub Flavored Markdown, `~` is escaped as well
+    Gfm,
+    /// GitHub comments, GFM without footnotes,
+    /// which are written as superscript links to a list at the end of the document instead
+    GfmComment,
+    /// Telegram MarkdownV2, headings are written as bold paragraphs
+    /// and emphasis uses `*bold*` and `_italic_`
     ///
-    /// * `writer` - Destination for Markdown data
-    pub fn new(writer: W) -> Self {
-        Self { writer }
+    /// Lists are written as `•` or numbered lines and tables as plain text in a code block.
+    Telegram,
+    /// Discord, headings deeper than level 3 are written as bold paragraphs
+    Discord,
+}
+
+impl Flavor {
+    /// Returns the deepest supported heading level,
+    /// deeper headings are written as bold paragraphs
+    pub fn max_heading_level(self) -> usize {
+        match self {
+            Flavor::Telegram => 0,
+            Flavor::Discord => 3,
+            _ => 6,
+        }
     }
 
-    /// Returns the underlying `writer` and consumes the object
-    pub fn into_inner(self) -> W {
-        self.writer
+    /// Returns `true` if attributes of headings, `# Title {#id .class}`, are supported
+    fn heading_attributes(self) -> bool {
+        self == Flavor::CommonMark
     }
 
-    /// Writes a [MarkdownWritable](trait.MarkdownWritable.html) to the document
+    /// Returns `true` if footnotes are written as `[^label]` references and definitions
     ///
-    /// # Returns
-    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
-    pub fn write<T: MarkdownWritable>(&mut self, element: T) -> Result<(), io::Error> {
-        element.write_to(&mut self.writer, false, Normal, None)?;
-        Ok(())
+    /// GitHub comments link HTML superscripts instead, Telegram and Discord do not render HTML,
+    /// so references are superscript digits, e.g. `¹`, followed by a numbered list of footnotes.
+    pub fn native_footnotes(self) -> bool {
+        matches!(self, Flavor::CommonMark | Flavor::Gfm)
     }
-}
 
-/// Trait for objects writable to Markdown documents
-pub trait MarkdownWritable {
-    /// Writes `self` as markdown to `writer`
+    /// Returns the forms italic and bold text are written in by default,
+    /// see [Markdown::with_emphasis_mapping](struct.Markdown.html#method.with_emphasis_mapping)
     ///
-    /// # Arguments
-    /// * `writer` - Destination writer
-    /// * `inner` - `true` if element is inside another element, `false` otherwise
-    /// * `escape` - Mode used for escaping string
-    /// * `line_prefix` - Prefix written before each line
+    /// All flavors support both forms, so the text keeps its emphasis.
+    pub fn emphasis_mapping(self) -> (Emphasis, Emphasis) {
+        match self {
+            Flavor::CommonMark
+            | Flavor::Gfm
+            | Flavor::GfmComment
+            | Flavor::Telegram
+            | Flavor::Discord => (Emphasis::Italic, Emphasis::Bold),
+        }
+    }
+
+    /// Delimiter of bold text
+    fn strong(self, style: EmphasisStyle) -> &'static [u8] {
+        match (self, style) {
+            (Flavor::Telegram, _) => b"*",
+            // `__text__` is underlined on Discord
+            (Flavor::Discord, _) | (_, EmphasisStyle::Asterisk) => b"**",
+            (_, EmphasisStyle::Underscore) => b"__",
+        }
+    }
+
+    /// Delimiter of italic text
+    fn emphasis(self, style: EmphasisStyle) -> &'static [u8] {
+        match (self, style) {
+            (Flavor::Telegram, _) | (_, EmphasisStyle::Underscore) => b"_",
+            (_, EmphasisStyle::Asterisk) => b"*",
+        }
+    }
+
+    /// Characters escaped in text
+    fn escape(self) -> &'static [u8] {
+        match self {
+            Flavor::CommonMark => NORMAL_ESCAPE,
+            Flavor::Gfm | Flavor::GfmComment => b"\\`*_{}[]()#+-.!~",
+            Flavor::Telegram => b"\\_*[]()~`>#+-=|{}.!",
+            Flavor::Discord => b"\\`*_~|>#-[]()",
+        }
+    }
+
+    /// Characters escaped with a backslash in link and image addresses,
+    /// other flavors percent-encode addresses instead, see [write_address](fn.write_address.html)
+    fn address_escape(self) -> &'static [u8] {
+        match self {
+            Flavor::Telegram => b"\\)",
+            _ => b"",
+        }
+    }
+}
+
+/// Options affecting how elements are written
+#[derive(Clone, Default)]
+pub struct Options {
+    transforms: Vec<(TextRole, Transform)>,
+    role: TextRole,
+    flavor: Flavor,
+    link_rewriter: Option<Transform>,
+    warning_handler: Option<WarningHandler>,
+    link_definitions: Option<LinkDefinitions>,
+    heading_numbers: Option<HeadingNumbers>,
+    heading_path: HeadingPath,
+    written_headings: WrittenHeadings,
+    smart_escaping: bool,
+    line_width: Option<usize>,
+    long_links: LongLinks,
+    emphasis: EmphasisStyle,
+    line_break: LineBreakStyle,
+    italic_as: Option<Emphasis>,
+    bold_as: Option<Emphasis>,
+    strict: bool,
+    whitespace: WhitespacePolicy,
+    tabs: TabPolicy,
+    bullet: Bullet,
+    list_indent: Option<usize>,
+    control: ControlChars,
+    key_order: KeyOrder,
+    null_placeholder: String,
+    heading_offset: usize,
+}
+
+/// Handling of control characters in text and code,
+/// see [Markdown::with_control_chars](struct.Markdown.html#method.with_control_chars)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ControlChars {
+    /// Control characters are written as they are
+    #[default]
+    Keep,
+    /// ANSI escape sequences and control characters are removed
+    Strip,
+    /// Control characters are replaced by visible symbols, e.g. `␛` for escape
+    Escape,
+}
+
+/// Order of keys of elements built from maps,
+/// see [Markdown::with_key_order](struct.Markdown.html#method.with_key_order)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum KeyOrder {
+    /// Keys are written in the order they were added
+    #[default]
+    Insertion,
+    /// Keys are written sorted, so the output does not depend on the iteration order of a map
+    Sorted,
+}
+
+/// Marker of bulleted list items,
+/// see [Markdown::with_list_style](struct.Markdown.html#method.with_list_style)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Bullet {
+    /// `* item`
+    #[default]
+    Asterisk,
+    /// `- item`
+    Dash,
+    /// `+ item`
+    Plus,
+}
+
+/// Handling of tabs in text and inline code,
+/// see [Markdown::with_tab_policy](struct.Markdown.html#method.with_tab_policy)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TabPolicy {
+    /// Tabs are written as they are
+    #[default]
+    Preserve,
+    /// Tabs are replaced by spaces up to the next multiple of the tab width
+    Expand(usize),
+    /// Writing text with a tab fails with `ErrorKind::InvalidInput`
+    Reject,
+}
+
+/// Handling of spaces which markdown would drop or collapse,
+/// see [Markdown::with_whitespace_policy](struct.Markdown.html#method.with_whitespace_policy)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum WhitespacePolicy {
+    /// Spaces are written as they are
+    #[default]
+    AsIs,
+    /// Spaces which would be lost are written as `&nbsp;`
+    Preserve,
+    /// Writing text with spaces which would be lost fails with `ErrorKind::InvalidInput`
+    Reject,
+}
+
+/// Delimiters of italic and bold text,
+/// see [Markdown::with_emphasis_style](struct.Markdown.html#method.with_emphasis_style)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum EmphasisStyle {
+    /// `*italic*` and `**bold**`
+    #[default]
+    Asterisk,
+    /// `_italic_` and `__bold__`
+    Underscore,
+}
+
+/// Form emphasized text is written in, see [Markdown::with_emphasis_mapping](struct.Markdown.html#method.with_emphasis_mapping)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Emphasis {
+    /// *Italic* text
+    Italic,
+    /// **Bold** text
+    Bold,
+    /// Text without emphasis
+    Plain,
+}
+
+/// Form of a [LineBreak](struct.LineBreak.html),
+/// see [Markdown::with_line_break_style](struct.Markdown.html#method.with_line_break_style)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LineBreakStyle {
+    /// Backslash at the end of the line
+    #[default]
+    Backslash,
+    /// Two spaces at the end of the line, invisible and often removed by editors
+    Spaces,
+}
+
+/// Placement of links longer than the line width when paragraphs are wrapped,
+/// see [Markdown::with_line_width](struct.Markdown.html#method.with_line_width)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LongLinks {
+    /// Inline links are never broken, a link which does not fit is moved to its own line
+    #[default]
+    OwnLine,
+    /// Links longer than the line width are written as [reference links](struct.Link.html#method.reference),
+    /// other flavors fall back to [OwnLine](#variant.OwnLine)
+    Reference,
+}
+
+impl Options {
+    /// Creates default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the target platform
+    pub fn set_flavor(&mut self, flavor: Flavor) {
+        self.flavor = flavor;
+    }
+
+    /// Returns the target platform
+    pub fn flavor(&self) -> Flavor {
+        self.flavor
+    }
+
+    /// Escapes characters in text only where they would be parsed as markdown,
+    /// see [Markdown::with_smart_escaping](struct.Markdown.html#method.with_smart_escaping)
+    pub fn set_smart_escaping(&mut self, smart: bool) {
+        self.smart_escaping = smart;
+    }
+
+    /// Returns whether smart escaping is enabled
+    pub fn smart_escaping(&self) -> bool {
+        self.smart_escaping
+    }
+
+    /// Sets the width paragraphs are wrapped to, `None` disables wrapping
+    pub fn set_line_width(&mut self, width: Option<usize>, long_links: LongLinks) {
+        self.line_width = width;
+        self.long_links = long_links;
+    }
+
+    /// Returns the width paragraphs are wrapped to
+    pub fn line_width(&self) -> Option<usize> {
+        self.line_width
+    }
+
+    /// Sets the delimiters of italic and bold text
+    pub fn set_emphasis_style(&mut self, style: EmphasisStyle) {
+        self.emphasis = style;
+    }
+
+    /// Returns the delimiters of italic and bold text
+    pub fn emphasis_style(&self) -> EmphasisStyle {
+        self.emphasis
+    }
+
+    /// Sets the form of hard line breaks
+    pub fn set_line_break_style(&mut self, style: LineBreakStyle) {
+        self.line_break = style;
+    }
+
+    /// Returns the form of hard line breaks
+    pub fn line_break_style(&self) -> LineBreakStyle {
+        self.line_break
+    }
+
+    /// Sets the forms italic and bold text are written in, for targets supporting only one of them,
+    /// overriding the [mapping of the flavor](enum.Flavor.html#method.emphasis_mapping)
+    pub fn set_emphasis_mapping(&mut self, italic: Emphasis, bold: Emphasis) {
+        self.italic_as = Some(italic);
+        self.bold_as = Some(bold);
+    }
+
+    /// Returns the forms italic and bold text are written in
+    pub fn emphasis_mapping(&self) -> (Emphasis, Emphasis) {
+        let (italic, bold) = self.flavor.emphasis_mapping();
+        (
+            self.italic_as.unwrap_or(italic),
+            self.bold_as.unwrap_or(bold),
+        )
+    }
+
+    /// Maps `bold` and `italic` text to the forms supported by the target
     ///
     /// # Returns
-    /// `()` or `std::io::Error` if an error occurred during writing
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), io::Error>;
+    /// Whether the text is written as `(bold, italic)`
+    fn map_emphasis(&self, bold: bool, italic: bool) -> (bool, bool) {
+        let (italic_as, bold_as) = self.emphasis_mapping();
+        let mut mapped = (false, false);
+        for (set, form) in &[(bold, bold_as), (italic, italic_as)] {
+            match form {
+                Emphasis::Bold if *set => mapped.0 = true,
+                Emphasis::Italic if *set => mapped.1 = true,
+                _ => {}
+            }
+        }
+        mapped
+    }
 
-    /// Counts length of longest streak of `char` in `self`
+    /// Delimiter of bold paragraphs replacing headings the flavor does not support
+    fn strong_delimiter(&self) -> &'static [u8] {
+        match self.map_emphasis(true, false) {
+            (true, _) => self.flavor.strong(self.emphasis),
+            (_, true) => self.flavor.emphasis(self.emphasis),
+            _ => b"",
+        }
+    }
+
+    /// Makes empty elements an error instead of writing them leniently,
+    /// see [Markdown::with_strict](struct.Markdown.html#method.with_strict)
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Returns whether empty elements are an error
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Sets the handling of spaces which markdown would drop or collapse
+    pub fn set_whitespace_policy(&mut self, policy: WhitespacePolicy) {
+        self.whitespace = policy;
+    }
+
+    /// Returns the handling of spaces which markdown would drop or collapse
+    pub fn whitespace_policy(&self) -> WhitespacePolicy {
+        self.whitespace
+    }
+
+    /// Sets the handling of tabs in text and inline code
+    pub fn set_tab_policy(&mut self, policy: TabPolicy) {
+        self.tabs = policy;
+    }
+
+    /// Returns the handling of tabs in text and inline code
+    pub fn tab_policy(&self) -> TabPolicy {
+        self.tabs
+    }
+
+    /// Sets the marker of bulleted list items and the indentation of nested lists,
+    /// which is clamped to 2-4 spaces; top level items then start at the beginning of the line
+    pub fn set_list_style(&mut self, bullet: Bullet, indent: usize) {
+        self.bullet = bullet;
+        self.list_indent = Some(indent.clamp(2, 4));
+    }
+
+    /// Returns the marker of bulleted list items and the indentation of nested lists
+    pub fn list_style(&self) -> (Bullet, usize) {
+        (self.bullet, self.list_indent.unwrap_or(3))
+    }
+
+    /// Sets the handling of control characters in text and code
+    pub fn set_control_chars(&mut self, control: ControlChars) {
+        self.control = control;
+    }
+
+    /// Returns the handling of control characters in text and code
+    pub fn control_chars(&self) -> ControlChars {
+        self.control
+    }
+
+    /// Sets the order of keys of elements built from maps
+    pub fn set_key_order(&mut self, order: KeyOrder) {
+        self.key_order = order;
+    }
+
+    /// Returns the order of keys of elements built from maps
+    pub fn key_order(&self) -> KeyOrder {
+        self.key_order
+    }
+
+    /// Sets the text written for `None` values, e.g. `—` or `n/a`
+    pub fn set_null_placeholder<T: Into<String>>(&mut self, placeholder: T) {
+        self.null_placeholder = placeholder.into();
+    }
+
+    /// Returns the text written for `None` values, empty by default
+    pub fn null_placeholder(&self) -> &str {
+        &self.null_placeholder
+    }
+
+    /// Sets the number of levels all headings are shifted down by, e.g. 1 to write `#` as `##`
     ///
-    /// # Arguments
-    /// * `char` - Character to search for
-    /// * `carry` - Length to add to possible occurrence at the beginning
+    /// Levels beyond 6 are clamped.
+    pub fn set_heading_offset(&mut self, offset: usize) {
+        self.heading_offset = offset;
+    }
+
+    /// Returns the number of levels all headings are shifted down by, 0 by default
+    pub fn heading_offset(&self) -> usize {
+        self.heading_offset
+    }
+
+    /// Applies the control character policy to `text`, line breaks and tabs are kept
+    fn sanitize<'t>(&self, text: Cow<'t, str>) -> Cow<'t, str> {
+        let is_control = |c: char| c.is_control() && c != '\n' && c != '\t';
+        if self.control == ControlChars::Keep || !text.contains(is_control) {
+            return text;
+        }
+        let mut sanitized = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if !is_control(c) {
+                sanitized.push(c);
+            } else if self.control == ControlChars::Escape {
+                // Control Pictures block, U+2400 to U+241F and U+2421 for delete
+                let picture = match c as u32 {
+                    code @ 0..=0x1f => char::from_u32(0x2400 + code),
+                    0x7f => Some('\u{2421}'),
+                    _ => None,
+                };
+                sanitized.push(picture.unwrap_or('\u{fffd}'));
+            } else if c == '\u{1b}' {
+                skip_escape_sequence(&mut chars);
+            }
+        }
+        let action = match self.control {
+            ControlChars::Escape => "replaced",
+            _ => "removed",
+        };
+        self.warn(
+            WarningKind::LossyEscaping,
+            format!("control characters {}", action),
+        );
+        Cow::Owned(sanitized)
+    }
+
+    /// Applies the tab policy to `text`
     ///
-    /// # Returns
-    /// `(count, carry)`
-    /// * `count` - Length of longest streak
-    /// * `carry` - Length of streak at the end
-    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize);
-}
+    /// `line` is the text already written on the current line, if given,
+    /// expanded tabs indenting a line are dropped, as the spaces would start an indented code block.
+    fn tabs<'t>(&self, text: Cow<'t, str>, line: Option<&[u8]>) -> Result<Cow<'t, str>, Error> {
+        if self.tabs == TabPolicy::Preserve || !text.contains('\t') {
+            return Ok(text);
+        }
+        let width = match self.tabs {
+            TabPolicy::Expand(width) => width.max(1),
+            _ => {
+                return Err(Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "text contains a tab",
+                ))
+            }
+        };
+        let mut expanded = String::with_capacity(text.len());
+        // Columns are counted from the start of the line, continuation bytes are skipped
+        let mut column = line.map_or(0, |line| line.iter().filter(|&&b| b & 0xc0 != 0x80).count());
+        let mut indent = line.is_some_and(|line| line.iter().all(u8::is_ascii_whitespace));
+        let mut dropped = false;
+        for c in text.chars() {
+            match c {
+                '\t' if indent => dropped = true,
+                '\t' => {
+                    let spaces = width - column % width;
+                    expanded.extend(core::iter::repeat(' ').take(spaces));
+                    column += spaces;
+                }
+                '\n' => {
+                    expanded.push(c);
+                    column = 0;
+                    indent = line.is_some();
+                }
+                _ => {
+                    expanded.push(c);
+                    column += 1;
+                    indent &= c == ' ';
+                }
+            }
+        }
+        if dropped {
+            self.warn(WarningKind::LossyEscaping, "tabs indenting a line removed");
+        }
+        Ok(Cow::Owned(expanded))
+    }
 
-/// Trait for objects convertible to a Markdown element
-pub trait AsMarkdown<'a> {
-    /// Converts `self` to [Paragraph](struct.Paragraph.html)
-    fn paragraph(self) -> Paragraph<'a>;
-    /// Converts `self` to [Heading](struct.Heading.html)
+    /// Applies the whitespace policy to `text`,
+    /// spaces at its start and end are kept only if `edges` is set
+    fn whitespace<'t>(&self, text: Cow<'t, str>, edges: bool) -> Result<Cow<'t, str>, Error> {
+        if self.whitespace == WhitespacePolicy::AsIs {
+            return Ok(text);
+        }
+        let preserved = preserve_whitespace(&text, edges, self.flavor);
+        if preserved == text {
+            Ok(text)
+        } else if self.whitespace == WhitespacePolicy::Reject {
+            Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "text contains whitespace which would be lost",
+            ))
+        } else {
+            Ok(Cow::Owned(preserved))
+        }
+    }
+
+    /// Handles an empty `element`, returns an error in strict mode
+    fn empty(&self, element: &'static str) -> Result<(), Error> {
+        if self.strict {
+            Err(Error::new(io::ErrorKind::InvalidInput, element))
+        } else {
+            self.warn(WarningKind::Empty, element);
+            Ok(())
+        }
+    }
+
+    /// Returns titles of the headings enclosing the element being written
+    pub fn heading_path(&self) -> &HeadingPath {
+        &self.heading_path
+    }
+
+    /// Sets a callback receiving non-fatal issues reported during writing
+    pub fn set_warning_handler(&mut self, handler: WarningHandler) {
+        self.warning_handler = Some(handler);
+    }
+
+    /// Reports a non-fatal issue to the warning handler, nothing is done without one
+    pub fn warn<T: Into<String>>(&self, kind: WarningKind, message: T) {
+        if let Some(handler) = &self.warning_handler {
+            handler(&Warning {
+                kind,
+                message: message.into(),
+                heading_path: self.heading_path.titles(),
+            });
+        }
+    }
+
+    /// Sets a callback rewriting every link and image address before it is written
+    pub fn set_link_rewriter(&mut self, rewriter: Transform) {
+        self.link_rewriter = Some(rewriter);
+    }
+
+    /// Applies the link rewriter to `address`
+    pub fn rewrite_link<'t>(&self, address: &'t str) -> Cow<'t, str> {
+        match &self.link_rewriter {
+            Some(rewriter) => Cow::Owned(rewriter(address)),
+            None => Cow::Borrowed(address),
+        }
+    }
+
+    /// Records a definition of a reference link, returning its label
     ///
-    /// # Arguments
-    /// * `level` - Heading level (1-6)
-    fn heading(self, level: usize) -> Heading<'a>;
-    /// Converts `self` to [Link](struct.Link.html)
+    /// A label already defined with another address gets a numeric suffix.
+    /// Returns `None` if definitions are not collected or the flavor has no reference links.
+    fn define_link(&self, label: &str, address: &str) -> Option<String> {
+        if !matches!(
+            self.flavor,
+            Flavor::CommonMark | Flavor::Gfm | Flavor::GfmComment
+        ) {
+            return None;
+        }
+        let mut definitions = self.link_definitions.as_ref()?.lock();
+        let mut candidate = label.to_string();
+        let mut suffix = 1;
+        loop {
+            match definitions.iter().find(|(l, _)| *l == candidate) {
+                Some((_, a)) if a == address => return Some(candidate),
+                Some(_) => {
+                    suffix += 1;
+                    candidate = format!("{}-{}", label, suffix);
+                }
+                None => break,
+            }
+        }
+        definitions.push((candidate.clone(), address.to_string()));
+        Some(candidate)
+    }
+
+    /// Sets a transform applied to text with `role`
+    pub fn set_transform(&mut self, role: TextRole, transform: Transform) {
+        self.transforms.retain(|(r, _)| *r != role);
+        self.transforms.push((role, transform));
+    }
+
+    /// Returns options for writing text with `role`
     ///
-    /// # Arguments
-    /// * `address` - Address which will the link lead to
-    fn link_to(self, address: &'a str) -> Link<'a>;
+    /// If no transform is set for `role`, the current transform stays in effect.
+    pub fn with_role(&self, role: TextRole) -> Cow<'_, Options> {
+        if self.role == role || !self.transforms.iter().any(|(r, _)| *r == role) {
+            return Cow::Borrowed(self);
+        }
+        let mut options = self.clone();
+        options.role = role;
+        Cow::Owned(options)
+    }
+
+    /// Applies the transform in effect to `text`
+    pub fn transform<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        let transform = self
+            .transforms
+            .iter()
+            .find(|(r, _)| *r == self.role)
+            .or_else(|| self.transforms.iter().find(|(r, _)| *r == TextRole::Text));
+        match transform {
+            Some((_, transform)) => Cow::Owned(transform(text)),
+            None => Cow::Borrowed(text),
+        }
+    }
+}
+
+/// Converts `text` to title case
+///
+/// Every word is capitalized except for articles, short conjunctions and prepositions,
+/// which are not the first or the last word.
+pub fn title_case(text: &str) -> String {
+    HeadingCase::new(CaseStyle::Title).fix(text)
+}
+
+/// Converts heading `text` to an anchor the same way as GitHub does
+///
+/// Text is lowercased, spaces are replaced by `-` and other punctuation is removed.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            slug.extend(c.to_lowercase());
+        } else if c == ' ' {
+            slug.push('-');
+        }
+    }
+    slug
+}
+
+/// Capitalization style of headings
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CaseStyle {
+    /// Only the first word is capitalized, e.g. `Writing to a file`
+    Sentence,
+    /// Every word except for small words is capitalized, e.g. `Writing to a File`
+    Title,
+}
+
+/// Checks and fixes capitalization of headings
+///
+/// Words written in capitals, like `API`, are kept as they are in both styles.
+/// Use [fix](#method.fix) as a [Heading](enum.TextRole.html#variant.Heading) transform
+/// to correct headings while writing.
+#[derive(Clone, Debug)]
+pub struct HeadingCase {
+    style: CaseStyle,
+    small_words: Vec<String>,
+}
+
+impl HeadingCase {
+    /// Creates a checker of `style` with the default list of small words
+    pub fn new(style: CaseStyle) -> Self {
+        Self {
+            style,
+            small_words: [
+                "a", "an", "and", "as", "at", "but", "by", "for", "in", "nor", "of", "on", "or",
+                "the", "to", "up", "via",
+            ]
+            .iter()
+            .map(|w| w.to_string())
+            .collect(),
+        }
+    }
+
+    /// Replaces the list of words kept lowercase in title case
+    pub fn small_words<I, T>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        self.small_words = words
+            .into_iter()
+            .map(|w| w.as_ref().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Returns whether `text` follows the style
+    pub fn check(&self, text: &str) -> bool {
+        self.fix(text) == text
+    }
+
+    /// Converts `text` to the style
+    pub fn fix(&self, text: &str) -> String {
+        let words: Vec<&str> = text.split(' ').collect();
+        let last = words.len() - 1;
+        let mut result = String::with_capacity(text.len());
+        for (i, word) in words.iter().enumerate() {
+            if i > 0 {
+                result.push(' ');
+            }
+            let acronym = word.chars().filter(|c| c.is_alphabetic()).count() > 1
+                && !word.chars().any(char::is_lowercase);
+            let lowercase = match self.style {
+                CaseStyle::Sentence => i != 0,
+                CaseStyle::Title => {
+                    i != 0 && i != last && self.small_words.contains(&word.to_lowercase())
+                }
+            };
+            if acronym {
+                result.push_str(word);
+            } else if lowercase {
+                result.push_str(&word.to_lowercase());
+            } else {
+                let mut chars = word.chars();
+                if let Some(first) = chars.next() {
+                    result.extend(first.to_uppercase());
+                    match self.style {
+                        CaseStyle::Sentence => result.push_str(&chars.as_str().to_lowercase()),
+                        CaseStyle::Title => result.push_str(chars.as_str()),
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Struct for generating Markdown
+pub struct Markdown<W: Write> {
+    writer: W,
+    options: Options,
+    footnotes: Vec<(usize, Vec<u8>)>,
+    footnote_count: usize,
+    headings: Vec<(usize, String, Option<String>)>,
+    position: usize,
+    source_map: Option<Vec<SourceMapEntry>>,
+    link_definitions_written: usize,
+    section_depth: usize,
+    /// Buffer reused for rendering elements, see [with_buffered_writes](#method.with_buffered_writes)
+    buffer: Option<Vec<u8>>,
+}
+
+/// Part of the output written by a single call, see [Markdown::with_source_map](struct.Markdown.html#method.with_source_map)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    /// Label passed to [write_labeled](struct.Markdown.html#method.write_labeled)
+    pub label: Option<String>,
+    /// Byte range occupied in the output
+    pub range: core::ops::Range<usize>,
+}
+
+impl<W: Write> Markdown<W> {
+    /// Creates a new [Markdown](struct.Markdown.html) struct
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination for Markdown data
+    pub fn new(writer: W) -> Self {
+        let mut options = Options::new();
+        options.link_definitions = Some(Arc::new(Mutex::new(Vec::new())));
+        Self {
+            writer,
+            options,
+            footnotes: Vec::new(),
+            footnote_count: 0,
+            headings: Vec::new(),
+            position: 0,
+            source_map: None,
+            link_definitions_written: 0,
+            section_depth: 0,
+            buffer: None,
+        }
+    }
+
+    /// Sets a transform applied to text with `role` when it is written
+    ///
+    /// # Arguments
+    /// * `role` - Kind of text the transform is applied to
+    /// * `transform` - Function transforming the text, e.g. [title_case](fn.title_case.html) or `str::to_uppercase`
+    pub fn with_transform<F: 'static + Fn(&str) -> String + Send + Sync>(
+        mut self,
+        role: TextRole,
+        transform: F,
+    ) -> Self {
+        self.options.set_transform(role, Arc::new(transform));
+        self
+    }
+
+    /// Sets the target platform, see [Flavor](enum.Flavor.html)
+    pub fn with_flavor(mut self, flavor: Flavor) -> Self {
+        self.options.set_flavor(flavor);
+        self
+    }
+
+    /// Escapes characters in text only where they would be parsed as markdown
+    ///
+    /// E.g. `#` is escaped only at the start of a line, `.` only after a leading number
+    /// and `_` only when it is not inside a word, so `v1.2-rc` stays as it is.
+    /// Telegram requires all special characters to be escaped and is not affected.
+    pub fn with_smart_escaping(mut self, smart: bool) -> Self {
+        self.options.set_smart_escaping(smart);
+        self
+    }
+
+    /// Wraps paragraphs to lines of at most `width` characters,
+    /// not counting prefixes of enclosing blockquotes and lists
+    ///
+    /// Lines are broken only at spaces, so longer words and inline links exceed the width.
+    /// Link syntax cannot be broken, `long_links` determines how links are placed.
+    pub fn with_line_width(mut self, width: usize, long_links: LongLinks) -> Self {
+        self.options.set_line_width(Some(width), long_links);
+        self
+    }
+
+    /// Sets the delimiters of italic and bold text, see [EmphasisStyle](enum.EmphasisStyle.html)
+    ///
+    /// Telegram syntax is fixed and Discord underlines `__text__`, so bold text keeps
+    /// asterisks there. Underscores do not emphasize text inside a word, e.g. in `un_believ_able`.
+    pub fn with_emphasis_style(mut self, style: EmphasisStyle) -> Self {
+        self.options.set_emphasis_style(style);
+        self
+    }
+
+    /// Sets the form of hard line breaks, see [LineBreakStyle](enum.LineBreakStyle.html)
+    ///
+    /// Telegram and Discord keep line breaks of text, so a plain line break is written there.
+    pub fn with_line_break_style(mut self, style: LineBreakStyle) -> Self {
+        self.options.set_line_break_style(style);
+        self
+    }
+
+    /// Sets the forms italic and bold text are written in, e.g. `(Emphasis::Bold, Emphasis::Bold)`
+    /// for a chat platform supporting only bold text
+    ///
+    /// Text both italic and bold is written in both mapped forms.
+    /// Also applies to headings written as bold paragraphs.
+    /// Without it, the [mapping of the flavor](enum.Flavor.html#method.emphasis_mapping) is used.
+    pub fn with_emphasis_mapping(mut self, italic: Emphasis, bold: Emphasis) -> Self {
+        self.options.set_emphasis_mapping(italic, bold);
+        self
+    }
+
+    /// Makes writing empty elements fail with `ErrorKind::InvalidInput`
+    ///
+    /// Otherwise empty text, bold or code is skipped, a link without text shows its address
+    /// and an empty list item is written as a bare marker.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.options.set_strict(strict);
+        self
+    }
+
+    /// Sets the handling of spaces which markdown would drop or collapse
+    ///
+    /// These are spaces at the start and end of lines, paragraphs and bold or italic text
+    /// and all but the first space of a run. Inline code keeps its spaces in any case.
+    pub fn with_whitespace_policy(mut self, policy: WhitespacePolicy) -> Self {
+        self.options.set_whitespace_policy(policy);
+        self
+    }
+
+    /// Sets the handling of tabs in text and inline code, see [TabPolicy](enum.TabPolicy.html)
+    ///
+    /// A tab at the start of a line can turn text into a code block or a nested list
+    /// and renderers disagree on the width of tabs. Columns are counted from the start
+    /// of every piece of text, so expanded tabs align only within it.
+    pub fn with_tab_policy(mut self, policy: TabPolicy) -> Self {
+        self.options.set_tab_policy(policy);
+        self
+    }
+
+    /// Sets the marker of bulleted list items and the indentation of nested lists,
+    /// e.g. to satisfy markdownlint rules MD004 and MD007
+    ///
+    /// Top level items start at the beginning of the line.
+    ///
+    /// # Arguments
+    /// * `bullet` - Marker of bulleted list items, `*` by default
+    /// * `indent` - Spaces nested items and item content are indented by, 2-4 and 3 by default.
+    ///   Content of numbered items is indented by at least 3 spaces, so it is aligned with the text after `1. `.
+    pub fn with_list_style(mut self, bullet: Bullet, indent: usize) -> Self {
+        self.options.set_list_style(bullet, indent);
+        self
+    }
+
+    /// Sets the handling of control characters, e.g. ANSI colors in captured terminal output,
+    /// see [ControlChars](enum.ControlChars.html)
+    ///
+    /// Applies to text, inline code and code blocks. Line breaks and tabs are not affected.
+    pub fn with_control_chars(mut self, control: ControlChars) -> Self {
+        self.options.set_control_chars(control);
+        self
+    }
+
+    /// Sets the order of keys of [FrontMatter](struct.FrontMatter.html),
+    /// [FieldList](struct.FieldList.html) and maps written by the serde serializer
+    ///
+    /// [Sorted](enum.KeyOrder.html#variant.Sorted) makes output built from a `HashMap` deterministic.
+    pub fn with_key_order(mut self, order: KeyOrder) -> Self {
+        self.options.set_key_order(order);
+        self
+    }
+
+    /// Sets the text written for `None` values, e.g. table cells built from `Option`
+    /// or null values written by the serde serializer
+    ///
+    /// `None` is written as nothing by default.
+    pub fn with_null_placeholder<T: Into<String>>(mut self, placeholder: T) -> Self {
+        self.options.set_null_placeholder(placeholder);
+        self
+    }
+
+    /// Sets a callback rewriting every link and image address before it is written,
+    /// e.g. to prepend a host or to change `.md` extensions to `.html`
+    pub fn with_link_rewriter<F: 'static + Fn(&str) -> String + Send + Sync>(
+        mut self,
+        rewriter: F,
+    ) -> Self {
+        self.options.set_link_rewriter(Arc::new(rewriter));
+        self
+    }
+
+    /// Sets a callback receiving non-fatal issues instead of ignoring them,
+    /// e.g. empty elements, headings degraded to bold text or table rows over the limit
+    ///
+    /// Warnings can be logged along with a document ID captured by the callback
+    /// or collected into a shared `Vec`.
+    pub fn with_warning_handler<F: 'static + Fn(&Warning) + Send + Sync>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.options.set_warning_handler(Arc::new(handler));
+        self
+    }
+
+    /// Numbers headings automatically, e.g. `1.`, `1.1` and `1.1.1`
+    ///
+    /// Numbering starts at the highest level written, headings marked
+    /// [unnumbered](struct.Heading.html#method.unnumbered) are skipped.
+    /// Numbers are part of the heading text, so they appear in the [Toc](struct.Toc.html)
+    /// and in anchors generated by renderers. Numbered headings get an explicit
+    /// [Heading::anchor](struct.Heading.html#method.anchor) without the number,
+    /// the `{#id}` attribute or an `<a id="anchor"></a>` depending on the flavor,
+    /// so links to headings do not change when headings are inserted.
+    pub fn with_heading_numbers(mut self) -> Self {
+        self.options.heading_numbers = Some(Arc::default());
+        self
+    }
+
+    /// Restarts numbering of headings at `1.`, e.g. before appendices
+    pub fn reset_heading_numbers(&mut self) {
+        if let Some(numbers) = &self.options.heading_numbers {
+            *numbers.lock() = HeadingNumbering::default();
+        }
+    }
+
+    /// Returns titles of the headings written so far enclosing the next element,
+    /// e.g. `Install > Linux > Arch`
+    ///
+    /// The returned path follows later headings, so it can be moved into
+    /// a [link rewriter](#method.with_link_rewriter) or a [warning handler](#method.with_warning_handler)
+    /// to report where an issue occurred.
+    pub fn heading_path(&self) -> HeadingPath {
+        self.options.heading_path.clone()
+    }
+
+    /// Returns the underlying `writer` and consumes the object
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes a [MarkdownWritable](trait.MarkdownWritable.html) to the document
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during rendering the element
+    /// or writing to the underlying writer
+    pub fn write<T: MarkdownWritable>(&mut self, element: T) -> Result<(), io::Error> {
+        self.write_element(None, &element)
+    }
+
+    /// Writes a [MarkdownWritable](trait.MarkdownWritable.html) and records `label` in the source map
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn write_labeled<T: MarkdownWritable>(
+        &mut self,
+        label: &str,
+        element: T,
+    ) -> Result<(), io::Error> {
+        self.write_element(Some(label), &element)
+    }
+
+    /// Writes `title` as a heading and calls `body` to write content of the section
+    ///
+    /// Headings of sections are one level below the enclosing section, starting at level 1,
+    /// so fragments written by `body` don't depend on where they are placed.
+    /// Levels beyond 6 are clamped.
+    ///
+    /// # Returns
+    /// `()` or the first `std::io::Error` returned by writing the heading or by `body`
+    pub fn section<T, F>(&mut self, title: T, body: F) -> Result<(), io::Error>
+    where
+        T: InlineElement,
+        F: FnOnce(&mut Self) -> Result<(), io::Error>,
+    {
+        self.begin_section(title)?;
+        let result = body(self);
+        self.end_section();
+        result
+    }
+
+    /// Writes `title` as a heading and enters the section until [end_section](#method.end_section) is called,
+    /// see [section](#method.section)
+    pub fn begin_section<T: InlineElement>(&mut self, title: T) -> Result<(), io::Error> {
+        self.write(Heading::clamped(self.section_level()).append(title))?;
+        self.section_depth += 1;
+        Ok(())
+    }
+
+    /// Leaves the innermost section started by [begin_section](#method.begin_section)
+    pub fn end_section(&mut self) {
+        self.section_depth = self.section_depth.saturating_sub(1);
+    }
+
+    /// Returns the level of a heading of a section started now, e.g. 2 inside of a top level section
+    ///
+    /// May exceed 6 in deeply nested sections.
+    pub fn section_level(&self) -> usize {
+        self.section_depth + 1
+    }
+
+    /// Writes `element` with all headings shifted down by `level_offset` levels,
+    /// e.g. a [Document](struct.Document.html) generated independently to be merged into a report
+    ///
+    /// Levels beyond 6 are clamped. Inside of a [section](#method.section), an offset of
+    /// `section_level() - 1` places top level headings of `element` at the level of subsections.
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn write_nested<T: MarkdownWritable>(
+        &mut self,
+        element: T,
+        level_offset: usize,
+    ) -> Result<(), io::Error> {
+        let offset = self.options.heading_offset;
+        self.options.heading_offset += level_offset;
+        let result = self.write_element(None, &element);
+        self.options.heading_offset = offset;
+        result
+    }
+
+    /// Starts a table written row by row, e.g. for datasets too large to be kept in memory
+    ///
+    /// Nothing is written until the first row is added or the table is finished.
+    ///
+    /// # Returns
+    /// [TableWriter](struct.TableWriter.html) or `std::io::Error` if the header has no cells
+    /// or an error occurred during rendering the header
+    pub fn begin_table<I, T>(&mut self, header: I) -> Result<TableWriter<'_, W>, io::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: InlineElement,
+    {
+        let header: Vec<Box<dyn MarkdownWritable>> = header
+            .into_iter()
+            .map(|cell| Box::new(cell) as Box<dyn MarkdownWritable>)
+            .collect();
+        if header.is_empty() {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "table without columns",
+            ));
+        }
+        let header_options = self.options.with_role(TextRole::TableHeader);
+        let header = render_table_row(
+            &header,
+            header.len(),
+            &RenderContext::new(false, Normal, None, &header_options),
+        )?;
+        Ok(TableWriter {
+            markdown: self,
+            columns: header.len(),
+            header: Some(header),
+            alignment: Vec::new(),
+            written: 0,
+        })
+    }
+
+    /// Renders every element into a buffer and writes it only if rendering succeeds
+    ///
+    /// By default elements are streamed to the writer, so an element failing to render,
+    /// e.g. an empty element in [strict](#method.with_strict) mode, leaves its output written so far.
+    /// The buffer is kept and reused by all calls.
+    pub fn with_buffered_writes(mut self) -> Self {
+        self.buffer = Some(Vec::new());
+        self
+    }
+
+    /// Enables recording of the byte range written by every call
+    pub fn with_source_map(mut self) -> Self {
+        self.source_map = Some(Vec::new());
+        self
+    }
+
+    /// Returns ranges recorded since [with_source_map](#method.with_source_map) was called,
+    /// ordered by position in the output
+    pub fn source_map(&self) -> &[SourceMapEntry] {
+        self.source_map.as_deref().unwrap_or(&[])
+    }
+
+    fn write_element(
+        &mut self,
+        label: Option<&str>,
+        element: &dyn MarkdownWritable,
+    ) -> Result<(), io::Error> {
+        self.options.written_headings.lock().clear();
+        let context = RenderContext::new(false, Normal, None, &self.options);
+        let written = match &mut self.buffer {
+            Some(buffer) => {
+                buffer.clear();
+                element.write_to(buffer, &context)?;
+                self.writer.write_all(buffer)?;
+                buffer.len()
+            }
+            None => {
+                let mut counter = CountingWriter::new(&mut self.writer);
+                let result = element.write_to(&mut counter, &context);
+                if result.is_err() {
+                    // Keeps positions of following elements in sync with the output
+                    self.position += counter.count;
+                }
+                result?;
+                counter.count
+            }
+        };
+        self.record(label, written);
+        let headings = core::mem::take(&mut *self.options.written_headings.lock());
+        self.headings.extend(headings);
+        Ok(())
+    }
+
+    /// Renders `element` without writing it, see [Fanout](struct.Fanout.html)
+    fn render_element(&self, element: &dyn MarkdownWritable) -> Result<Rendered, io::Error> {
+        self.options.written_headings.lock().clear();
+        let mut output = Vec::new();
+        element.write_to(
+            &mut output,
+            &RenderContext::new(false, Normal, None, &self.options),
+        )?;
+        let headings = core::mem::take(&mut *self.options.written_headings.lock());
+        Ok((output, headings))
+    }
+
+    /// Writes an element rendered by [render_element](#method.render_element)
+    fn write_rendered(&mut self, (output, headings): Rendered) -> Result<(), io::Error> {
+        self.writer.write_all(&output)?;
+        self.record(None, output.len());
+        self.headings.extend(headings);
+        Ok(())
+    }
+
+    fn record(&mut self, label: Option<&str>, written: usize) {
+        let start = self.position;
+        self.position += written;
+        if let Some(source_map) = &mut self.source_map {
+            source_map.push(SourceMapEntry {
+                label: label.map(str::to_string),
+                range: start..self.position,
+            });
+        }
+    }
+
+    /// Writes a [Toc](struct.Toc.html) of all headings written so far
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn write_toc(&mut self) -> Result<(), io::Error> {
+        let toc = Toc {
+            entries: self.headings.clone(),
+        };
+        self.write(toc)
+    }
+
+    /// Adds a footnote, labels are assigned automatically in ascending order
+    ///
+    /// The definition is written by [write_footnotes](struct.Markdown.html#method.write_footnotes).
+    ///
+    /// # Arguments
+    /// * `body` - Contents of the footnote
+    ///
+    /// # Returns
+    /// [FootnoteRef](struct.FootnoteRef.html) to be written where the footnote is referenced
+    /// or `std::io::Error` if an error occurred during writing the body
+    pub fn footnote<T: MarkdownWritable>(&mut self, body: T) -> Result<FootnoteRef, io::Error> {
+        self.add_footnote(&body)
+    }
+
+    fn add_footnote(&mut self, body: &dyn MarkdownWritable) -> Result<FootnoteRef, io::Error> {
+        let buffer = self.render_footnote(body)?;
+        Ok(self.push_footnote(buffer))
+    }
+
+    fn render_footnote(&self, body: &dyn MarkdownWritable) -> Result<Vec<u8>, io::Error> {
+        let mut buffer = Vec::new();
+        body.write_to(
+            &mut buffer,
+            &RenderContext {
+                nesting: Some(Nesting::Footnote),
+                ..RenderContext::new(true, Normal, Some(b"    "), &self.options)
+            },
+        )?;
+        Ok(buffer)
+    }
+
+    fn push_footnote(&mut self, buffer: Vec<u8>) -> FootnoteRef {
+        self.footnote_count += 1;
+        self.footnotes.push((self.footnote_count, buffer));
+        FootnoteRef {
+            label: self.footnote_count,
+        }
+    }
+
+    /// Writes definitions of footnotes added since the last call, usually at the end of the document
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn write_footnotes(&mut self) -> Result<(), io::Error> {
+        if self.footnotes.is_empty() {
+            return Ok(());
+        }
+        let mut counter = CountingWriter::new(&mut self.writer);
+        write_footnote_definitions(&mut counter, &self.footnotes, self.options.flavor)?;
+        self.footnotes.clear();
+        let written = counter.count;
+        self.record(None, written);
+        Ok(())
+    }
+
+    /// Writes definitions `[label]: address` of [reference links](struct.Link.html#method.reference)
+    /// written since the last call, usually at the end of the document
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn write_link_definitions(&mut self) -> Result<(), io::Error> {
+        let definitions = match &self.options.link_definitions {
+            Some(definitions) => definitions.lock(),
+            None => return Ok(()),
+        };
+        if definitions.len() == self.link_definitions_written {
+            return Ok(());
+        }
+        let mut counter = CountingWriter::new(&mut self.writer);
+        for (label, address) in &definitions[self.link_definitions_written..] {
+            write!(counter, "[{}]: ", label)?;
+            write_address(&mut counter, address, self.options.flavor, None)?;
+            counter.write_all(b"\n")?;
+        }
+        counter.write_all(b"\n")?;
+        let written = counter.count;
+        self.link_definitions_written = definitions.len();
+        drop(definitions);
+        self.record(None, written);
+        Ok(())
+    }
+}
+
+/// Adapter writing to a `std::fmt::Write`, e.g. a `String` or a `Formatter`
+///
+/// Allows [Markdown](struct.Markdown.html) to write text without a `Vec<u8>` round-trip,
+/// e.g. `Markdown::new(FmtWriter::new(String::new()))` or inside a `Display` implementation.
+pub struct FmtWriter<F: fmt::Write> {
+    writer: F,
+    pending: Vec<u8>,
+}
+
+impl<F: fmt::Write> FmtWriter<F> {
+    /// Creates a new [FmtWriter](struct.FmtWriter.html) writing to `writer`
+    pub fn new(writer: F) -> Self {
+        Self {
+            writer,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns the underlying writer
+    pub fn into_inner(self) -> F {
+        self.writer
+    }
+}
+
+impl<F: fmt::Write> Write for FmtWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        // Characters split between calls are held back until they are complete
+        self.pending.extend_from_slice(buf);
+        let valid = match core::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(_) => {
+                self.pending.truncate(self.pending.len() - buf.len());
+                return Err(Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                ));
+            }
+        };
+        let text = core::str::from_utf8(&self.pending[..valid]).unwrap();
+        self.writer
+            .write_str(text)
+            .map_err(|_| Error::other("formatter error"))?;
+        self.pending.drain(..valid);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Container an element is written inside of, see [RenderContext::nesting](struct.RenderContext.html#method.nesting)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Nesting {
+    /// Item of a [List](struct.List.html)
+    List,
+    /// Block quote, e.g. a [Blockquote](struct.Blockquote.html) or an [Alert](struct.Alert.html)
+    Quote,
+    /// Cell of a [Table](struct.Table.html)
+    Table,
+    /// Body of a footnote
+    Footnote,
+    /// Body of an [Admonition](struct.Admonition.html)
+    Admonition,
+    /// Content of a [Collapsible](struct.Collapsible.html) section, e.g. an answer of a [Faq](struct.Faq.html)
+    Collapsible,
+    /// Fenced [Div](struct.Div.html) or a container [Directive](struct.Directive.html)
+    Div,
+}
+
+/// Position of an element being written and options of the document
+#[derive(Clone, Copy)]
+pub struct RenderContext<'a> {
+    /// `true` if the element is written inside of another element, e.g. text in a paragraph,
+    /// `false` if it is a block on its own, which ends with a blank line
+    pub inner: bool,
+    /// Escaping of text
+    pub escape: Escaping,
+    /// Prefix written after every line break, e.g. `> ` inside of a block quote
+    pub line_prefix: Option<&'a [u8]>,
+    /// Options affecting how elements are written
+    pub options: &'a Options,
+    parent: Option<&'a RenderContext<'a>>,
+    nesting: Option<Nesting>,
+    /// Text already written on the current line after the line prefix, used by smart escaping
+    line: &'a [u8],
+}
+
+impl<'a> RenderContext<'a> {
+    /// Creates a context of an element which is not nested in any container
+    pub fn new(
+        inner: bool,
+        escape: Escaping,
+        line_prefix: Option<&'a [u8]>,
+        options: &'a Options,
+    ) -> Self {
+        Self {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            parent: None,
+            nesting: None,
+            line: &[],
+        }
+    }
+
+    /// Creates a context of an element written by the current element
+    pub fn child<'b>(
+        &'b self,
+        inner: bool,
+        escape: Escaping,
+        line_prefix: Option<&'b [u8]>,
+        options: &'b Options,
+    ) -> RenderContext<'b> {
+        RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            parent: Some(self),
+            nesting: None,
+            line: &[],
+        }
+    }
+
+    /// Creates a context of an element written inside of `container` by the current element
+    pub fn nested<'b>(
+        &'b self,
+        container: Nesting,
+        inner: bool,
+        escape: Escaping,
+        line_prefix: Option<&'b [u8]>,
+        options: &'b Options,
+    ) -> RenderContext<'b> {
+        RenderContext {
+            nesting: Some(container),
+            ..self.child(inner, escape, line_prefix, options)
+        }
+    }
+
+    /// Returns containers the element is written inside of, starting at the innermost one
+    pub fn nesting(&self) -> impl Iterator<Item = Nesting> + '_ {
+        let mut context = Some(self);
+        core::iter::from_fn(move || {
+            while let Some(current) = context {
+                context = current.parent;
+                if current.nesting.is_some() {
+                    return current.nesting;
+                }
+            }
+            None
+        })
+    }
+
+    /// Returns the number of `container`s the element is written inside of,
+    /// e.g. 2 in a list nested in another list
+    pub fn depth(&self, container: Nesting) -> usize {
+        self.nesting()
+            .filter(|&nesting| nesting == container)
+            .count()
+    }
+
+    /// Writes inline `element` inside of the current element, e.g. text escaped according to the flavor
+    pub fn write_inner<T: InlineElement>(
+        &self,
+        writer: &mut dyn Write,
+        element: T,
+    ) -> Result<(), Error> {
+        element.write_to(
+            writer,
+            &self.child(true, self.escape, self.line_prefix, self.options),
+        )
+    }
+
+    /// Writes `data` as it is, followed by the line prefix after every line break
+    pub fn write_raw(&self, writer: &mut dyn Write, data: &[u8]) -> Result<(), Error> {
+        write_line_prefixed(writer, data, self.line_prefix)
+    }
+
+    /// Ends a block with a blank line, nothing is written inside of another element
+    pub fn end_block(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        if !self.inner {
+            write_line_prefixed(writer, b"\n\n", self.line_prefix)?;
+        }
+        Ok(())
+    }
+}
+
+/// Trait for objects writable to Markdown documents
+pub trait MarkdownWritable {
+    /// Writes `self` as markdown to `writer`
+    ///
+    /// # Arguments
+    /// * `writer` - Destination writer
+    /// * `context` - Position of the element, escaping and options, see [RenderContext](struct.RenderContext.html)
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing
+    fn write_to(
+        &self,
+        writer: &mut dyn Write,
+        context: &RenderContext<'_>,
+    ) -> Result<(), io::Error>;
+
+    /// Counts length of longest streak of `char` in `self`
+    ///
+    /// # Arguments
+    /// * `char` - Character to search for
+    /// * `carry` - Length to add to possible occurrence at the beginning
+    ///
+    /// # Returns
+    /// `(count, carry)`
+    /// * `count` - Length of longest streak
+    /// * `carry` - Length of streak at the end
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize);
+
+    /// Collects headings contained in `self`, used for generating a table of contents
+    ///
+    /// # Arguments
+    /// * `options` - Options affecting how the heading text is written
+    ///
+    /// # Returns
+    /// `(level, text, id)` of each heading, where `text` is written as markdown
+    /// and `id` is the [explicit identifier](struct.Heading.html#method.id), if set
+    fn headings(&self, _options: &Options) -> Vec<(usize, String, Option<String>)> {
+        Vec::new()
+    }
+
+    /// Returns metadata attached to `self`, which backends may use and markdown output may ignore
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        None
+    }
+
+    /// Returns the alignment `self` prefers in a table column, e.g. right for numbers
+    ///
+    /// Columns of a [Table](struct.Table.html) without explicit alignment are aligned
+    /// as preferred by their cells if all cells with a preference agree.
+    fn preferred_alignment(&self) -> Option<Alignment> {
+        None
+    }
+
+    /// Converts `self` to inline elements of the Pandoc AST
+    ///
+    /// Elements without a Pandoc counterpart are written as raw markdown.
+    /// Available with the `pandoc` feature.
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        pandoc::raw(self, true, options)
+    }
+
+    /// Converts `self` to block elements of the Pandoc AST
+    ///
+    /// Elements without a Pandoc counterpart are written as raw markdown.
+    /// Available with the `pandoc` feature.
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        pandoc::raw(self, false, options)
+    }
+}
+
+/// Marker of elements which can be written inside of text, e.g. appended to a
+/// [Paragraph](struct.Paragraph.html) or a [Heading](struct.Heading.html)
+///
+/// Containers of inline content, including cells of pipe tables, accept only inline elements,
+/// so nesting e.g. a heading inside of a paragraph does not compile. Lists are blocks,
+/// a nested list is added as an item and the text before it is set by [List::title](struct.List.html#method.title).
+pub trait InlineElement: MarkdownWritable {}
+
+/// Marker of elements which occupy whole lines, e.g. paragraphs, lists and tables
+///
+/// Block elements can't be nested inside of inline elements. Elements which can be written
+/// both ways, like [Raw](struct.Raw.html), implement both traits.
+pub trait BlockElement: MarkdownWritable {}
+
+/// Implements a marker trait for elements and references to them
+macro_rules! impl_marker {
+    ($marker:ident: $($type:ty),+ $(,)?) => {
+        $(
+            impl $marker for $type {}
+            impl $marker for &'_ $type {}
+        )+
+    };
+}
+
+impl InlineElement for &str {}
+impl InlineElement for String {}
+impl InlineElement for Cow<'_, str> {}
+impl<T: InlineElement> InlineElement for Option<T> {}
+impl<T: BlockElement> BlockElement for Option<T> {}
+impl<T: InlineElement> InlineElement for WithAttributes<'_, T> {}
+impl<T: InlineElement> InlineElement for &'_ WithAttributes<'_, T> {}
+impl<T: BlockElement> BlockElement for WithAttributes<'_, T> {}
+impl<T: BlockElement> BlockElement for &'_ WithAttributes<'_, T> {}
+
+impl_marker!(
+    InlineElement: LineBreak,
+    Link<'_>,
+    RichText<'_>,
+    Change<'_>,
+    Image<'_>,
+    ExampleRef<'_>,
+    Span<'_>,
+    TextDirective<'_>,
+    FootnoteRef,
+    Bar,
+    Citation<'_>,
+    IndexMarker,
+    MathInline<'_>,
+    Raw<'_>,
+);
+
+impl_marker!(
+    BlockElement: Document<'_>,
+    Paragraph<'_>,
+    Heading<'_>,
+    List<'_>,
+    Quote<'_>,
+    Table<'_>,
+    ExampleList<'_>,
+    Div<'_>,
+    Directive<'_>,
+    SummaryTable<'_>,
+    Embed<'_>,
+    LogseqBlock<'_>,
+    Toc,
+    Statistics,
+    DocumentDiff,
+    CodeBlock<'_>,
+    Blockquote<'_>,
+    Alert<'_>,
+    Admonition<'_>,
+    Collapsible<'_>,
+    Faq<'_>,
+    Html<'_>,
+    Footer<'_>,
+    FrontMatter,
+    FieldList,
+    Index<'_>,
+    MathBlock<'_>,
+    Raw<'_>,
+);
+
+/// Writes `element` to `f` as a block with default options, without surrounding blank lines
+pub(crate) fn display(element: &dyn MarkdownWritable, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut buffer = Vec::new();
+    element
+        .write_to(
+            &mut buffer,
+            &RenderContext::new(false, Normal, None, &Options::new()),
+        )
+        .map_err(|_| fmt::Error)?;
+    f.write_str(String::from_utf8_lossy(&buffer).trim_matches('\n'))
+}
+
+/// Implements `Display` writing elements as markdown with default options
+macro_rules! impl_display {
+    ($($type:ty),+ $(,)?) => {
+        $(
+            impl fmt::Display for $type {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    display(&self, f)
+                }
+            }
+        )+
+    };
+}
+
+impl<T: MarkdownWritable> fmt::Display for WithAttributes<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        display(&self, f)
+    }
+}
+
+impl_display!(
+    Document<'_>,
+    LineBreak,
+    Paragraph<'_>,
+    Heading<'_>,
+    Link<'_>,
+    RichText<'_>,
+    Change<'_>,
+    Image<'_>,
+    List<'_>,
+    Quote<'_>,
+    Table<'_>,
+    ExampleList<'_>,
+    ExampleRef<'_>,
+    Div<'_>,
+    Span<'_>,
+    Directive<'_>,
+    TextDirective<'_>,
+    FootnoteRef,
+    SummaryTable<'_>,
+    Bar,
+    Embed<'_>,
+    LogseqBlock<'_>,
+    Toc,
+    Statistics,
+    DocumentDiff,
+    CodeBlock<'_>,
+    Blockquote<'_>,
+    Citation<'_>,
+    Alert<'_>,
+    Admonition<'_>,
+    Collapsible<'_>,
+    Faq<'_>,
+    Html<'_>,
+    Footer<'_>,
+    FrontMatter,
+    FieldList,
+    Index<'_>,
+    IndexMarker,
+    MathInline<'_>,
+    MathBlock<'_>,
+    Raw<'_>,
+);
+
+/// Element defined outside of this crate, e.g. a Hugo shortcode
+///
+/// Custom elements are [MarkdownWritable](trait.MarkdownWritable.html) and can implement
+/// [InlineElement](trait.InlineElement.html) or [BlockElement](trait.BlockElement.html)
+/// to be accepted by containers.
+pub trait CustomElement {
+    /// Writes the element to `writer`
+    ///
+    /// Text should be written by [RenderContext::write_inner](struct.RenderContext.html#method.write_inner)
+    /// to be escaped, markup by [RenderContext::write_raw](struct.RenderContext.html#method.write_raw).
+    fn write(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error>;
+}
+
+impl<T: CustomElement + ?Sized> CustomElement for &T {
+    fn write(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (**self).write(writer, context)
+    }
+}
+
+impl<T: CustomElement> MarkdownWritable for T {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        self.write(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        let options = Options::new();
+        let mut written = Vec::new();
+        // Errors are returned when the element is written to the document
+        let _ = self.write(
+            &mut written,
+            &RenderContext::new(true, Normal, None, &options),
+        );
+        String::from_utf8_lossy(&written)
+            .as_ref()
+            .count_max_streak(char, carry)
+    }
+}
+
+/// Trait for objects convertible to a Markdown element
+pub trait AsMarkdown<'a> {
+    /// Converts `self` to [Paragraph](struct.Paragraph.html)
+    fn paragraph(self) -> Paragraph<'a>;
+    /// Converts `self` to [Heading](struct.Heading.html)
+    ///
+    /// # Arguments
+    /// * `level` - Heading level (1-6)
+    fn heading(self, level: usize) -> Heading<'a>;
+    /// Converts `self` to [Heading](struct.Heading.html), levels outside 1-6 are clamped
+    ///
+    /// Useful when the level comes from data, e.g. the depth of a tree.
+    fn heading_clamped(self, level: usize) -> Heading<'a>
+    where
+        Self: Sized,
+    {
+        self.heading(level.clamp(1, 6))
+    }
+    /// Converts `self` to [Link](struct.Link.html)
+    ///
+    /// # Arguments
+    /// * `address` - Address which will the link lead to
+    fn link_to<A: Into<Cow<'a, str>>>(self, address: A) -> Link<'a>;
+
+    /// Converts `self` to **bold** [RichText](struct.RichText.html)
+    fn bold(self) -> RichText<'a>;
+
+    /// Converts `self` to *italic* [RichText](struct.RichText.html)
+    fn italic(self) -> RichText<'a>;
+
+    /// Converts `self` to `code` [RichText](struct.RichText.html)
+    fn code(self) -> RichText<'a>;
+
+    /// Converts `self` to [Quote](struct.Quote.html)
+    fn quote(self) -> Quote<'a>;
+
+    /// Converts `self` to [Image](struct.Image.html) with `self` as the alternative text
+    ///
+    /// # Arguments
+    /// * `address` - Address of the image
+    fn image_to<A: Into<Cow<'a, str>>>(self, address: A) -> Image<'a>;
+}
+
+//region Fanout
+/// Writes every element to several [Markdown](struct.Markdown.html) writers,
+/// e.g. a GFM file and a Telegram message
+///
+/// Every writer renders the elements with its own options, so an element is rendered
+/// once per writer. All writers render an element before it is written to any of them,
+/// so an element failing to render is written to none of them.
+/// The writers are borrowed and may write to different types, e.g. a file and a `Vec<u8>`.
+///
+/// All methods fail with `ErrorKind::InvalidInput` if no writer was added.
+pub struct Fanout<'a> {
+    sinks: Vec<&'a mut dyn Sink>,
+}
+
+/// [Markdown](struct.Markdown.html) writer with the type of its underlying writer erased
+trait Sink {
+    fn render_element(&self, element: &dyn MarkdownWritable) -> Result<Rendered, io::Error>;
+    fn write_rendered(&mut self, rendered: Rendered) -> Result<(), io::Error>;
+    fn toc(&self) -> Toc;
+    fn footnote_count(&self) -> usize;
+    fn render_footnote(&self, body: &dyn MarkdownWritable) -> Result<Vec<u8>, io::Error>;
+    fn push_footnote(&mut self, buffer: Vec<u8>) -> FootnoteRef;
+    fn write_footnotes(&mut self) -> Result<(), io::Error>;
+    fn write_link_definitions(&mut self) -> Result<(), io::Error>;
+}
+
+impl<W: Write> Sink for Markdown<W> {
+    fn render_element(&self, element: &dyn MarkdownWritable) -> Result<Rendered, io::Error> {
+        Markdown::render_element(self, element)
+    }
+
+    fn write_rendered(&mut self, rendered: Rendered) -> Result<(), io::Error> {
+        Markdown::write_rendered(self, rendered)
+    }
+
+    fn toc(&self) -> Toc {
+        Toc {
+            entries: self.headings.clone(),
+        }
+    }
+
+    fn footnote_count(&self) -> usize {
+        self.footnote_count
+    }
+
+    fn render_footnote(&self, body: &dyn MarkdownWritable) -> Result<Vec<u8>, io::Error> {
+        Markdown::render_footnote(self, body)
+    }
+
+    fn push_footnote(&mut self, buffer: Vec<u8>) -> FootnoteRef {
+        Markdown::push_footnote(self, buffer)
+    }
+
+    fn write_footnotes(&mut self) -> Result<(), io::Error> {
+        Markdown::write_footnotes(self)
+    }
+
+    fn write_link_definitions(&mut self) -> Result<(), io::Error> {
+        Markdown::write_link_definitions(self)
+    }
+}
+
+impl<'a> Fanout<'a> {
+    /// Creates a fan-out without writers
+    pub fn new() -> Self {
+        Self { sinks: Vec::new() }
+    }
+
+    /// Adds a writer with its own options, e.g. `Markdown::new(file).with_flavor(Flavor::Gfm)`
+    pub fn sink<W: Write>(mut self, markdown: &'a mut Markdown<W>) -> Self {
+        self.sinks.push(markdown);
+        self
+    }
+
+    /// Writes a [MarkdownWritable](trait.MarkdownWritable.html) to all writers
+    ///
+    /// # Returns
+    /// `()` or the first `std::io::Error` returned by a writer
+    pub fn write<T: MarkdownWritable>(&mut self, element: T) -> Result<(), io::Error> {
+        let rendered = self
+            .sinks()?
+            .iter()
+            .map(|sink| sink.render_element(&element))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.write_rendered(rendered)
+    }
+
+    /// Writes a [Toc](struct.Toc.html) of headings written so far to all writers
+    pub fn write_toc(&mut self) -> Result<(), io::Error> {
+        let rendered = self
+            .sinks()?
+            .iter()
+            .map(|sink| sink.render_element(&sink.toc()))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.write_rendered(rendered)
+    }
+
+    /// Adds a footnote to all writers, see [Markdown::footnote](struct.Markdown.html#method.footnote)
+    ///
+    /// # Returns
+    /// [FootnoteRef](struct.FootnoteRef.html) valid in all writers, or `std::io::Error` if the body
+    /// failed to render or writers would assign different labels, e.g. as footnotes were added
+    /// to some of them directly, which fails with `ErrorKind::InvalidData`
+    pub fn footnote<T: MarkdownWritable>(&mut self, body: T) -> Result<FootnoteRef, io::Error> {
+        let sinks = self.sinks()?;
+        let count = sinks[0].footnote_count();
+        if sinks.iter().any(|sink| sink.footnote_count() != count) {
+            return Err(Error::new(
+                io::ErrorKind::InvalidData,
+                "footnote labels differ between writers",
+            ));
+        }
+        let buffers = sinks
+            .iter()
+            .map(|sink| sink.render_footnote(&body))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut footnote = FootnoteRef { label: count + 1 };
+        for (sink, buffer) in self.sinks.iter_mut().zip(buffers) {
+            footnote = sink.push_footnote(buffer);
+        }
+        Ok(footnote)
+    }
+
+    /// Writes definitions of footnotes added since the last call to all writers
+    pub fn write_footnotes(&mut self) -> Result<(), io::Error> {
+        self.sinks()?;
+        self.sinks
+            .iter_mut()
+            .try_for_each(|sink| sink.write_footnotes())
+    }
+
+    /// Writes definitions of reference links written since the last call to all writers
+    pub fn write_link_definitions(&mut self) -> Result<(), io::Error> {
+        self.sinks()?;
+        self.sinks
+            .iter_mut()
+            .try_for_each(|sink| sink.write_link_definitions())
+    }
+
+    /// Returns the writers, or an error if there are none
+    fn sinks(&self) -> Result<&[&'a mut dyn Sink], io::Error> {
+        if self.sinks.is_empty() {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "fan-out without writers",
+            ));
+        }
+        Ok(&self.sinks)
+    }
+
+    /// Writes output rendered by every writer to it
+    fn write_rendered(&mut self, rendered: Vec<Rendered>) -> Result<(), io::Error> {
+        for (sink, rendered) in self.sinks.iter_mut().zip(rendered) {
+            sink.write_rendered(rendered)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Fanout<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+//endregion
+
+//region MarkdownPool
+/// Function configuring writers handed out by a [MarkdownPool](struct.MarkdownPool.html)
+type Configure = Box<dyn Fn(Markdown<Vec<u8>>) -> Markdown<Vec<u8>>>;
+
+/// Pool of [Markdown](struct.Markdown.html) writers reusing their buffers,
+/// avoiding allocations when many documents are rendered one after another
+///
+/// The pool is not shared between threads, a `thread_local!` pool can be kept by every worker.
+pub struct MarkdownPool {
+    buffers: RefCell<Vec<Vec<u8>>>,
+    capacity: usize,
+    max_idle: usize,
+    configure: Configure,
+}
+
+impl MarkdownPool {
+    /// Creates an empty pool handing out writers with default options
+    ///
+    /// # Arguments
+    /// * `capacity` - Number of bytes allocated for a new buffer
+    /// * `max_idle` - Maximum number of buffers kept for reuse, others are dropped
+    pub fn new(capacity: usize, max_idle: usize) -> Self {
+        Self {
+            buffers: RefCell::new(Vec::new()),
+            capacity,
+            max_idle,
+            configure: Box::new(|md| md),
+        }
+    }
+
+    /// Sets a function applied to every writer handed out,
+    /// e.g. `|md| md.with_flavor(Flavor::Gfm)`
+    pub fn with_options<F>(mut self, configure: F) -> Self
+    where
+        F: 'static + Fn(Markdown<Vec<u8>>) -> Markdown<Vec<u8>>,
+    {
+        self.configure = Box::new(configure);
+        self
+    }
+
+    /// Returns a writer to an empty buffer, reusing a recycled buffer if there is one
+    pub fn take(&self) -> Markdown<Vec<u8>> {
+        let buffer = self
+            .buffers
+            .borrow_mut()
+            .pop()
+            .unwrap_or_else(|| Vec::with_capacity(self.capacity));
+        (self.configure)(Markdown::new(buffer))
+    }
+
+    /// Returns `buffer` obtained by [Markdown::into_inner](struct.Markdown.html#method.into_inner)
+    /// to the pool, it is cleared and handed out by a later [take](#method.take)
+    pub fn recycle(&self, mut buffer: Vec<u8>) {
+        let mut buffers = self.buffers.borrow_mut();
+        if buffers.len() < self.max_idle {
+            buffer.clear();
+            buffers.push(buffer);
+        }
+    }
+
+    /// Returns the number of buffers waiting for reuse
+    pub fn idle(&self) -> usize {
+        self.buffers.borrow().len()
+    }
+}
+//endregion
+
+//region Document
+/// Tree of elements which is kept in memory and written on demand
+///
+/// Unlike [Markdown](struct.Markdown.html), which writes every element immediately,
+/// a document can be modified after elements are added.
+pub struct Document<'a> {
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+    footnotes: Vec<Box<dyn 'a + MarkdownWritable>>,
+    heading_numbers: bool,
+}
+
+impl<'a> Document<'a> {
+    /// Creates an empty document
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            footnotes: Vec::new(),
+            heading_numbers: false,
+        }
+    }
+
+    /// Sets whether headings are numbered when the document is [rendered](#method.render),
+    /// see [Markdown::with_heading_numbers](struct.Markdown.html#method.with_heading_numbers)
+    pub fn set_heading_numbers(&mut self, numbered: bool) {
+        self.heading_numbers = numbered;
+    }
+
+    /// Appends a block element to the end of the document
+    pub fn push<T: 'a + MarkdownWritable>(&mut self, element: T) {
+        self.children.push(Box::new(element));
+    }
+
+    /// Inserts a block element at `index`, shifting all elements after it
+    ///
+    /// # Panics
+    /// Panics if `index` is greater than the number of elements
+    pub fn insert<T: 'a + MarkdownWritable>(&mut self, index: usize, element: T) {
+        self.children.insert(index, Box::new(element));
+    }
+
+    /// Removes and returns the element at `index`
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds
+    pub fn remove(&mut self, index: usize) -> Box<dyn 'a + MarkdownWritable> {
+        self.children.remove(index)
+    }
+
+    /// Returns the number of elements in the document
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Returns whether the document has no elements
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Returns an iterator over the elements of the document
+    pub fn iter(&self) -> impl Iterator<Item = &(dyn 'a + MarkdownWritable)> {
+        self.children.iter().map(|child| child.as_ref())
+    }
+
+    /// Creates a [Toc](struct.Toc.html) of headings in the document
+    ///
+    /// The TOC can be inserted back into the document, e.g. at index `0`.
+    /// Transforms are not applied to the heading text.
+    ///
+    /// # Returns
+    /// [Toc](struct.Toc.html) or `std::io::Error` if an element failed to render,
+    /// which is rendered only to number the headings
+    pub fn toc(&self) -> Result<Toc, io::Error> {
+        if self.heading_numbers {
+            let mut md = Markdown::new(Vec::new()).with_heading_numbers();
+            md.write(self)?;
+            return Ok(Toc {
+                entries: md.headings,
+            });
+        }
+        Ok(Toc {
+            entries: self.headings(&Options::new()),
+        })
+    }
+
+    /// Counts sections, words, tables and links of the rendered document
+    ///
+    /// The [Statistics](struct.Statistics.html) can be inserted back into the document,
+    /// e.g. at index `0`. Figures don't include the inserted statistics themselves.
+    ///
+    /// # Returns
+    /// [Statistics](struct.Statistics.html) or `std::io::Error` if an element failed to render
+    pub fn statistics(&self) -> Result<Statistics, io::Error> {
+        let md = self.render_markdown(Vec::new())?;
+        let sections = md.headings.len();
+        Ok(Statistics::count(
+            &String::from_utf8_lossy(&md.into_inner()),
+            sections,
+        ))
+    }
+
+    /// Writes the document to `writer`
+    ///
+    /// # Returns
+    /// `writer` or `std::io::Error` if an error occurred during writing
+    pub fn render<W: Write>(&self, writer: W) -> Result<W, io::Error> {
+        Ok(self.render_markdown(writer)?.into_inner())
+    }
+
+    /// Writes the document with definitions of footnotes and reference links to `writer`
+    fn render_markdown<W: Write>(&self, writer: W) -> Result<Markdown<W>, io::Error> {
+        let mut md = Markdown::new(writer);
+        if self.heading_numbers {
+            md = md.with_heading_numbers();
+        }
+        md.write(self)?;
+        let footnotes = self.footnote_definitions(&md.options, |_| true)?;
+        write_footnote_definitions(&mut md.writer, &footnotes, md.options.flavor)?;
+        md.write_link_definitions()?;
+        Ok(md)
+    }
+
+    /// Adds a footnote, labels are assigned automatically in ascending order
+    ///
+    /// Definitions are written at the end of the rendered document,
+    /// or at the end of every part referencing them when [split](#method.split).
+    ///
+    /// # Returns
+    /// [FootnoteRef](struct.FootnoteRef.html) to be written where the footnote is referenced
+    pub fn footnote<T: 'a + MarkdownWritable>(&mut self, body: T) -> FootnoteRef {
+        self.footnotes.push(Box::new(body));
+        FootnoteRef {
+            label: self.footnotes.len(),
+        }
+    }
+
+    /// Writes bodies of footnotes whose label satisfies `filter`
+    fn footnote_definitions<F: Fn(usize) -> bool>(
+        &self,
+        options: &Options,
+        filter: F,
+    ) -> Result<Vec<(usize, Vec<u8>)>, Error> {
+        let mut definitions = Vec::new();
+        for (i, body) in self.footnotes.iter().enumerate() {
+            if filter(i + 1) {
+                let mut buffer = Vec::new();
+                body.write_to(
+                    &mut buffer,
+                    &RenderContext {
+                        nesting: Some(Nesting::Footnote),
+                        ..RenderContext::new(true, Normal, Some(b"    "), options)
+                    },
+                )?;
+                definitions.push((i + 1, buffer));
+            }
+        }
+        Ok(definitions)
+    }
+
+    /// Renders the document split into parts, e.g. to write every part to its own file
+    ///
+    /// A new part starts at every element containing a heading.
+    /// Each part is complete on its own, definitions of footnotes and reference links
+    /// are written at the end of every part using them, so a definition used by several parts
+    /// is duplicated. Footnote labels are kept the same in all parts.
+    ///
+    /// # Returns
+    /// Content of every part or `std::io::Error` if an element failed to render
+    pub fn split(&self) -> Result<Vec<String>, io::Error> {
+        self.parts()
+            .into_iter()
+            .map(|part| self.render_part(&part, None))
+            .collect()
+    }
+
+    /// Renders the document split into files like [split](#method.split),
+    /// rewriting links to headings so they point to the file containing the heading
+    ///
+    /// Links with an address `#anchor` of a heading in another part are rewritten to
+    /// `file#anchor`, see [AnchorNamespace](struct.AnchorNamespace.html).
+    ///
+    /// # Arguments
+    /// * `file_name` - Returns the name of a file from the index of the part
+    ///   and the anchor of its first heading, which is empty if there is none
+    ///
+    /// # Returns
+    /// `(file name, content)` of every part or `std::io::Error` if an element failed to render
+    pub fn split_files<F: Fn(usize, &str) -> String>(
+        &self,
+        file_name: F,
+    ) -> Result<Vec<(String, String)>, io::Error> {
+        let options = Options::new();
+        let parts = self.parts();
+        let mut namespace = AnchorNamespace::new();
+        let mut names = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            let first = part
+                .iter()
+                .flat_map(|child| child.headings(&options))
+                .next()
+                .map(|(_, text, id)| id.unwrap_or_else(|| slugify(&plain_text(&text))))
+                .unwrap_or_default();
+            let name = file_name(i, &first);
+            for child in part {
+                namespace.add(&name, *child);
+            }
+            names.push(name);
+        }
+
+        let namespace = Arc::new(namespace);
+        parts
+            .iter()
+            .zip(names)
+            .map(|(part, name)| {
+                let resolver = namespace.clone();
+                let file = name.clone();
+                let rewriter: Transform =
+                    Arc::new(move |address| resolver.resolve(address, &file).into_owned());
+                let content = self.render_part(part, Some(rewriter))?;
+                Ok((name, content))
+            })
+            .collect()
+    }
+
+    /// Groups elements into parts, each starting at an element containing a heading
+    fn parts(&self) -> Vec<Vec<&dyn MarkdownWritable>> {
+        let options = Options::new();
+        let mut parts: Vec<Vec<&dyn MarkdownWritable>> = Vec::new();
+        for child in &self.children {
+            if parts.is_empty() || !child.headings(&options).is_empty() {
+                parts.push(Vec::new());
+            }
+            parts.last_mut().unwrap().push(child.as_ref());
+        }
+        parts
+    }
+
+    /// Renders `part` followed by definitions of footnotes and reference links it uses
+    fn render_part(
+        &self,
+        part: &[&dyn MarkdownWritable],
+        rewriter: Option<Transform>,
+    ) -> Result<String, io::Error> {
+        let mut md = Markdown::new(Vec::new());
+        if let Some(rewriter) = rewriter {
+            md.options.set_link_rewriter(rewriter);
+        }
+        for child in part {
+            child.write_to(
+                &mut md.writer,
+                &RenderContext::new(false, Normal, None, &md.options),
+            )?;
+        }
+        let written = &md.writer;
+        let footnotes = self.footnote_definitions(&md.options, |label| {
+            let label = format!("[^{}]", label);
+            written.windows(label.len()).any(|w| w == label.as_bytes())
+        })?;
+        write_footnote_definitions(&mut md.writer, &footnotes, md.options.flavor)?;
+        md.write_link_definitions()?;
+        Ok(String::from_utf8_lossy(&md.into_inner()).into_owned())
+    }
+
+    /// Renders the document over `existing` output of a previous regeneration
+    ///
+    /// The document is split into sections, each starting at an element containing a heading.
+    /// Every section is enclosed in comments with its position and a hash of its content.
+    /// Sections at the same position with the same hash in `existing` are copied from it unchanged,
+    /// so only sections whose content changed are rewritten. Text outside of the comments,
+    /// e.g. a hand-written introduction, is kept as is and new sections are added
+    /// after the last previous one. Pass an empty string to generate the file for the first time.
+    ///
+    /// # Returns
+    /// [Regeneration](struct.Regeneration.html) or `std::io::Error` if an element failed to render
+    pub fn regenerate(&self, existing: &str, options: &Options) -> Result<Regeneration, io::Error> {
+        // Text outside of sections and (position, hash, content) of sections
+        let mut segments: Vec<(Option<(usize, &str)>, &str)> = Vec::new();
+        let mut rest = existing;
+        while let Some(start) = rest.find(SECTION_MARKER) {
+            let after = &rest[start + SECTION_MARKER.len()..];
+            let (index, hash, content) = match after.split_once(" -->\n") {
+                Some((id, content)) => match id.split_once(' ') {
+                    Some((index, hash)) => match index.parse::<usize>() {
+                        Ok(index) => (index, hash, content),
+                        Err(_) => break,
+                    },
+                    None => break,
+                },
+                None => break,
+            };
+            segments.push((None, &rest[..start]));
+            let end = content
+                .find(SECTION_END)
+                .or_else(|| content.find(SECTION_MARKER))
+                .unwrap_or(content.len());
+            segments.push((Some((index, hash)), &content[..end]));
+            rest = content[end..]
+                .strip_prefix(SECTION_END)
+                .unwrap_or(&content[end..]);
+        }
+        segments.push((None, rest));
+
+        let mut sections: Vec<Vec<u8>> = Vec::new();
+        for child in &self.children {
+            options.written_headings.lock().clear();
+            let mut buffer = Vec::new();
+            child.write_to(
+                &mut buffer,
+                &RenderContext::new(false, Normal, None, options),
+            )?;
+            if sections.is_empty() || !options.written_headings.lock().is_empty() {
+                sections.push(Vec::new());
+            }
+            sections.last_mut().unwrap().extend(buffer);
+        }
+        options.written_headings.lock().clear();
+
+        let mut regeneration = Regeneration {
+            output: String::new(),
+            changed: Vec::new(),
+            removed: 0,
+        };
+        let mut written = vec![false; sections.len()];
+        // New sections follow the last previous section, or the text if there was none
+        let last_section = segments
+            .iter()
+            .rposition(|(section, _)| section.is_some())
+            .unwrap_or(0);
+        for (i, (section, text)) in segments.iter().enumerate() {
+            match section {
+                None => regeneration.output.push_str(text),
+                Some((index, hash)) if *index < sections.len() && !written[*index] => {
+                    regeneration.push_section(*index, &sections[*index], Some((hash, text)));
+                    written[*index] = true;
+                }
+                Some(_) => regeneration.removed += 1,
+            }
+            if i == last_section {
+                for (index, section) in sections.iter().enumerate() {
+                    if !written[index] {
+                        regeneration.push_section(index, section, None);
+                    }
+                }
+            }
+        }
+        regeneration.changed.sort_unstable();
+        Ok(regeneration)
+    }
+}
+
+const SECTION_MARKER: &str = "<!-- markdown-gen section ";
+const SECTION_END: &str = "<!-- markdown-gen end -->\n";
+
+/// Anchors of headings spread over multiple files
+///
+/// Anchors are numbered as if all files were a single document, e.g. the second `Usage`
+/// heading is `#usage-1`, and resolved to the anchor in the file containing the heading,
+/// where duplicates are numbered separately.
+/// Explicit [ids](struct.Heading.html#method.id) are kept as they are in every file
+/// and take precedence over numbered anchors of the same name.
+#[derive(Clone, Debug, Default)]
+pub struct AnchorNamespace {
+    anchors: BTreeMap<String, (String, String)>,
+    global_counts: BTreeMap<String, usize>,
+    file_counts: BTreeMap<(String, String), usize>,
+}
+
+impl AnchorNamespace {
+    /// Creates an empty namespace
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers headings of `element` written to `file`,
+    /// elements must be added in the order they are written
+    pub fn add<T: MarkdownWritable + ?Sized>(&mut self, file: &str, element: &T) {
+        for (_, text, id) in element.headings(&Options::new()) {
+            if let Some(id) = id {
+                self.anchors.insert(id.clone(), (file.to_string(), id));
+            }
+            let slug = slugify(&plain_text(&text));
+            let global = numbered(self.global_counts.entry(slug.clone()).or_insert(0), &slug);
+            let local = numbered(
+                self.file_counts
+                    .entry((file.to_string(), slug.clone()))
+                    .or_insert(0),
+                &slug,
+            );
+            // Anchors already present are explicit ids, which are reserved
+            self.anchors
+                .entry(global)
+                .or_insert_with(|| (file.to_string(), local));
+        }
+    }
+
+    /// Returns the address of the heading anchor `address` as seen from `file`
+    ///
+    /// Addresses other than `#anchor` and unknown anchors are returned unchanged.
+    pub fn resolve<'t>(&self, address: &'t str, file: &str) -> Cow<'t, str> {
+        let anchor = match address.strip_prefix('#') {
+            Some(anchor) => anchor,
+            None => return Cow::Borrowed(address),
+        };
+        match self.anchors.get(anchor) {
+            Some((target, local)) if target == file && local == anchor => Cow::Borrowed(address),
+            Some((target, local)) if target == file => Cow::Owned(format!("#{}", local)),
+            Some((target, local)) => Cow::Owned(format!("{}#{}", target, local)),
+            None => Cow::Borrowed(address),
+        }
+    }
+}
+
+/// Numbers `slug` the same way as GitHub numbers duplicate anchors
+fn numbered(count: &mut usize, slug: &str) -> String {
+    let anchor = match *count {
+        0 => slug.to_string(),
+        n => format!("{}-{}", slug, n),
+    };
+    *count += 1;
+    anchor
+}
+
+/// 64-bit FNV-1a hash, stable across builds unlike `std` hashers
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Output of [Document::regenerate](struct.Document.html#method.regenerate)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Regeneration {
+    /// New content of the file
+    pub output: String,
+    /// Indices of sections which were added or rewritten
+    pub changed: Vec<usize>,
+    /// Number of sections of the previous output which are no longer generated
+    pub removed: usize,
+}
+
+impl Regeneration {
+    /// Returns whether the output is the same as the previous one and need not be written
+    pub fn is_unchanged(&self) -> bool {
+        self.changed.is_empty() && self.removed == 0
+    }
+
+    /// Writes the section at `index`, copying `previous` (hash, content) if the hash matches
+    fn push_section(&mut self, index: usize, section: &[u8], previous: Option<(&str, &str)>) {
+        let section = String::from_utf8_lossy(section);
+        let hash = format!("{:016x}", fnv1a(section.as_bytes()));
+        self.output
+            .push_str(&format!("{}{} {} -->\n", SECTION_MARKER, index, hash));
+        match previous {
+            Some((previous_hash, text)) if previous_hash == hash => self.output.push_str(text),
+            _ => {
+                self.output.push_str(&section);
+                self.changed.push(index);
+            }
+        }
+        self.output.push_str(SECTION_END);
+    }
+}
+
+impl Default for Document<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownWritable for &'_ Document<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        for child in &self.children {
+            child.write_to(writer, &context.child(false, escape, line_prefix, options))?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in &self.children {
+            let (c, cr) = child.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        self.children
+            .iter()
+            .flat_map(|child| child.headings(options))
+            .collect()
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        self.children
+            .iter()
+            .flat_map(|child| child.pandoc_blocks(options))
+            .collect()
+    }
+}
+
+impl MarkdownWritable for Document<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+//endregion
+
+//region LineBreak
+/// Hard line break inside a paragraph, e.g. in an address or a signature
+///
+/// Written as a backslash or two spaces followed by a line break,
+/// see [Markdown::with_line_break_style](struct.Markdown.html#method.with_line_break_style).
+#[derive(Clone, Copy, Default)]
+pub struct LineBreak;
+
+impl MarkdownWritable for &'_ LineBreak {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let line_break: &[u8] = match (options.flavor, options.line_break) {
+            (Flavor::Telegram | Flavor::Discord, _) => b"\n",
+            (_, LineBreakStyle::Backslash) => b"\\\n",
+            (_, LineBreakStyle::Spaces) => b"  \n",
+        };
+        write_line_prefixed(writer, line_break, line_prefix)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, _char: u8, _carry: usize) -> (usize, usize) {
+        (0, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, _options: &Options) -> Vec<serde_json::Value> {
+        vec![serde_json::json!({"t": "LineBreak"})]
+    }
+}
+
+impl MarkdownWritable for LineBreak {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+}
+//endregion
+
+//region Paragraph
+/// Markdown paragraph
+pub struct Paragraph<'a> {
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+}
+
+impl<'a> Paragraph<'a> {
+    /// Creates an empty paragraph
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+        }
+    }
+
+    /// Appends an element to the paragraph
+    pub fn append<T: 'a + InlineElement>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+
+    /// Appends a [LineBreak](struct.LineBreak.html), the following elements start on a new line
+    pub fn line_break(self) -> Self {
+        self.append(LineBreak)
+    }
+
+    /// Creates a paragraph of text joined from sentences or clause fragments
+    ///
+    /// Pieces are trimmed and separated by a single space, runs of whitespace are collapsed
+    /// and no space is inserted before closing punctuation, e.g. `,` or `)`, or after an opening bracket.
+    /// Punctuation repeated at the boundary of two pieces, e.g. `done.` and `.`, is written once.
+    pub fn from_sentences<I, S>(sentences: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut text = String::new();
+        for sentence in sentences {
+            let mut words = sentence.as_ref().split_whitespace().peekable();
+            let first = match words.peek() {
+                Some(first) => *first,
+                None => continue,
+            };
+            if let (Some(last), Some(next)) = (text.chars().last(), first.chars().next()) {
+                if ".,;:!?".contains(next) && last == next {
+                    text.pop();
+                } else if !",.;:!?)]}".contains(next) && !"([{".contains(last) {
+                    text.push(' ');
+                }
+            }
+            for (i, word) in words.enumerate() {
+                if i > 0 {
+                    text.push(' ');
+                }
+                text.push_str(word);
+            }
+        }
+        Self::new().append(text)
+    }
+}
+
+impl MarkdownWritable for &'_ Paragraph<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let preserve = options.whitespace != WhitespacePolicy::AsIs;
+        match options.line_width {
+            width if !inner && (width.is_some() || preserve) => {
+                let mut text = Vec::new();
+                let mut tracker = LineTracker::new(&mut text, context.line, None);
+                for child in &self.children {
+                    let line = tracker.line.clone();
+                    child.write_to(
+                        &mut tracker,
+                        &RenderContext {
+                            line: &line,
+                            ..context.child(true, escape, None, options)
+                        },
+                    )?;
+                }
+                let text = String::from_utf8_lossy(&text);
+                let text = match text.find(|c| c != ' ') {
+                    Some(start) if preserve => {
+                        let end = text.trim_end_matches(' ').len();
+                        let inner = Cow::Borrowed(&text[start..end]);
+                        let leading = options.whitespace(Cow::Borrowed(&text[..start]), true)?;
+                        let trailing = options.whitespace(Cow::Borrowed(&text[end..]), true)?;
+                        Cow::Owned(format!("{}{}{}", leading, inner, trailing))
+                    }
+                    _ => text,
+                };
+                let text = match width {
+                    Some(width) => Cow::Owned(wrap(&text, width)),
+                    None => text,
+                };
+                write_line_prefixed(writer, text.as_bytes(), line_prefix)?;
+            }
+            _ => {
+                let mut tracker = LineTracker::new(writer, context.line, line_prefix);
+                for child in &self.children {
+                    let line = tracker.line.clone();
+                    child.write_to(
+                        &mut tracker,
+                        &RenderContext {
+                            line: &line,
+                            ..context.child(true, escape, line_prefix, options)
+                        },
+                    )?;
+                }
+            }
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        let mut carry = carry;
+        let mut count = 0;
+        for child in &self.children {
+            let (c, cr) = child.count_max_streak(char, carry);
+            count += c;
+            carry = cr;
+        }
+        count += carry;
+        (count, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        self.children
+            .iter()
+            .flat_map(|child| child.pandoc_inlines(options))
+            .collect()
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        vec![serde_json::json!({"t": "Para", "c": self.pandoc_inlines(options)})]
+    }
+}
+
+impl MarkdownWritable for Paragraph<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+
+impl Default for Paragraph<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+//endregion
+
+//region Heading
+/// Prefixes of heading levels 1-6
+const HEADING_PREFIXES: [&[u8]; 6] = [b"# ", b"## ", b"### ", b"#### ", b"##### ", b"###### "];
+
+/// Markdown heading
+pub struct Heading<'a> {
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+    suffix: Vec<Box<dyn 'a + MarkdownWritable>>,
+    level: usize,
+    emit_anchor: bool,
+    numbered: bool,
+    attributes: Attributes<'a>,
+}
+
+impl<'a> Heading<'a> {
+    /// Creates an empty heading
+    ///
+    /// # Arguments
+    /// * `level` - Heading level (1-6)
+    pub fn new(level: usize) -> Self {
+        assert!(level > 0 && level <= 6, "Heading level must be range 1-6.");
+        Self {
+            children: Vec::new(),
+            suffix: Vec::new(),
+            level,
+            emit_anchor: false,
+            numbered: true,
+            attributes: Attributes::new(),
+        }
+    }
+
+    /// Creates an empty heading, levels outside 1-6 are clamped instead of panicking
+    pub fn clamped(level: usize) -> Self {
+        Self::new(level.clamp(1, 6))
+    }
+
+    /// Appends an element to the heading
+    pub fn append<T: 'a + InlineElement>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+
+    /// Appends an element written after the heading text and a space, e.g. a badge or a version tag
+    ///
+    /// The suffix is not part of the [anchor](#method.anchor) or of [Toc](struct.Toc.html) entries.
+    /// Renderers would include it in their own anchors, so an explicit anchor is written
+    /// like with [emit_anchor](#method.emit_anchor) to keep links stable.
+    pub fn suffix<T: 'a + InlineElement>(mut self, element: T) -> Self {
+        self.suffix.push(Box::new(element));
+        self
+    }
+
+    /// Writes an explicit `<a id="anchor"></a>` before the heading,
+    /// for renderers which do not generate anchors of headings
+    pub fn emit_anchor(mut self) -> Self {
+        self.emit_anchor = true;
+        self
+    }
+
+    /// Sets the identifier of the heading, written as Pandoc and kramdown attribute `# Title {#id}`
+    ///
+    /// The identifier stays the same when the title changes. It is used as the [anchor](#method.anchor)
+    /// and by [Toc](struct.Toc.html) entries. GitHub does not support attributes, so with
+    /// the GFM flavors an `<a id="id"></a>` anchor is written before the heading instead
+    /// and classes and other attributes are dropped.
+    pub fn id<T: Into<Cow<'a, str>>>(mut self, id: T) -> Self {
+        self.attributes = self.attributes.id(id);
+        self
+    }
+
+    /// Adds a class to the heading, written as attribute `{.class}`
+    pub fn class<T: Into<Cow<'a, str>>>(mut self, class: T) -> Self {
+        self.attributes = self.attributes.class(class);
+        self
+    }
+
+    /// Adds a `key="value"` attribute to the heading
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.attributes = self.attributes.attribute(key, value);
+        self
+    }
+
+    /// Excludes the heading from [automatic numbering](struct.Markdown.html#method.with_heading_numbers),
+    /// e.g. a preface, the counters are not advanced
+    pub fn unnumbered(mut self) -> Self {
+        self.numbered = false;
+        self
+    }
+
+    /// Returns the GitHub-compatible anchor of the heading, see [slugify](fn.slugify.html),
+    /// or the [identifier](#method.id) if set
+    ///
+    /// Transforms are not applied to the heading text
+    /// and duplicate headings are not numbered.
+    pub fn anchor(&self) -> String {
+        if let Some(id) = self.attributes.get_id() {
+            return id.to_string();
+        }
+        let text = self
+            .headings(&Options::new())
+            .pop()
+            .map(|(_, text, _)| text)
+            .unwrap_or_default();
+        slugify(&plain_text(&text))
+    }
+}
+
+impl Heading<'_> {
+    /// Returns the level shifted by the [heading offset](struct.Options.html#method.set_heading_offset)
+    fn shifted_level(&self, options: &Options) -> usize {
+        (self.level + options.heading_offset).min(6)
+    }
+}
+
+impl MarkdownWritable for &'_ Heading<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        if inner {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "heading written inside of another element",
+            ));
+        }
+        let flavor = options.flavor;
+        let level = self.shifted_level(options);
+        let degraded = level > flavor.max_heading_level();
+        let explicit_id = self.attributes.get_id().is_some();
+        // Telegram and Discord do not render HTML
+        let html = !matches!(flavor, Flavor::Telegram | Flavor::Discord);
+        let attributes = flavor.heading_attributes() && !degraded;
+        // Renderers include the number in their anchors, links use the anchor without it
+        let numbered_id = match options.heading_numbers {
+            Some(_) if self.numbered && !explicit_id => Some(self.anchor()),
+            _ => None,
+        };
+        if self.emit_anchor
+            || (!self.suffix.is_empty() && !degraded && !explicit_id)
+            || ((explicit_id || numbered_id.is_some()) && !attributes && html)
+        {
+            write!(writer, "<a id=\"{}\"></a>", self.anchor())?;
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        if degraded {
+            options.warn(
+                WarningKind::Degraded,
+                format!("heading level {} written as bold text", level),
+            );
+            writer.write_all(options.strong_delimiter())?;
+        } else {
+            writer.write_all(HEADING_PREFIXES[level - 1])?;
+        }
+        let options = options.with_role(TextRole::Heading);
+        let mut title = Vec::new();
+        if let Some(numbers) = &options.heading_numbers {
+            if self.numbered {
+                let next = numbers.lock().next(level);
+                next.as_str()
+                    .write_to(&mut title, &context.child(true, Normal, None, &options))?;
+                title.push(b' ');
+            }
+        }
+        let number_len = title.len();
+        for child in &self.children {
+            child.write_to(
+                &mut title,
+                &context.child(true, Normal, line_prefix, &options),
+            )?;
+        }
+        writer.write_all(&title)?;
+        options.heading_path.enter(
+            level,
+            plain_text(&String::from_utf8_lossy(&title[number_len..])),
+        );
+        options.written_headings.lock().push((
+            level,
+            String::from_utf8_lossy(&title).into_owned(),
+            self.attributes
+                .get_id()
+                .map(str::to_string)
+                .or_else(|| numbered_id.clone()),
+        ));
+        for (i, element) in self.suffix.iter().enumerate() {
+            if i == 0 {
+                writer.write_all(b" ")?;
+            }
+            element.write_to(writer, &context.child(true, Normal, line_prefix, &options))?;
+        }
+        if attributes && numbered_id.is_some() {
+            writer.write_all(b" ")?;
+            let id = numbered_id.unwrap_or_default();
+            self.attributes.clone().id(id).write_to(writer)?;
+        } else if attributes && !self.attributes.is_empty() {
+            writer.write_all(b" ")?;
+            self.attributes.write_to(writer)?;
+        } else if !self.attributes.classes.is_empty() || !self.attributes.pairs.is_empty() {
+            options.warn(
+                WarningKind::Degraded,
+                "heading attributes dropped, the flavor does not support them",
+            );
+        }
+        if degraded {
+            writer.write_all(options.strong_delimiter())?;
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut carry = 0;
+        let mut count = 0;
+        for child in self.children.iter().chain(&self.suffix) {
+            let (c, cr) = child.count_max_streak(char, carry);
+            count += c;
+            carry = cr;
+        }
+        (count, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        let options = options.with_role(TextRole::Heading);
+        let mut text = Vec::new();
+        for child in &self.children {
+            // The error is returned when the heading is written, the TOC only leaves it out
+            if let Err(error) =
+                child.write_to(&mut text, &RenderContext::new(true, Normal, None, &options))
+            {
+                options.warn(
+                    WarningKind::Degraded,
+                    format!("heading left out of the table of contents: {}", error),
+                );
+                return Vec::new();
+            }
+        }
+        vec![(
+            self.shifted_level(&options),
+            String::from_utf8_lossy(&text).into_owned(),
+            self.attributes.get_id().map(str::to_string),
+        )]
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        let options = options.with_role(TextRole::Heading);
+        let mut inlines: Vec<serde_json::Value> = self
+            .children
+            .iter()
+            .flat_map(|child| child.pandoc_inlines(&options))
+            .collect();
+        // Pandoc derives identifiers from the whole text, so the anchor is set explicitly
+        let mut attr = pandoc::attributes(&self.attributes);
+        if !self.suffix.is_empty() {
+            attr[0] = self.anchor().into();
+            inlines.push(serde_json::json!({"t": "Space"}));
+            inlines.extend(
+                self.suffix
+                    .iter()
+                    .flat_map(|element| element.pandoc_inlines(&options)),
+            );
+        }
+        vec![serde_json::json!({
+            "t": "Header",
+            "c": [self.shifted_level(&options), attr, inlines],
+        })]
+    }
+}
+
+impl MarkdownWritable for Heading<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+//endregion
+
+//region Link
+/// Markdown link
+pub struct Link<'a> {
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+    address: Cow<'a, str>,
+    reference: bool,
+}
+
+impl<'a> Link<'a> {
+    /// Creates an empty link, which leads to `address`
+    pub fn new<A: Into<Cow<'a, str>>>(address: A) -> Self {
+        Self {
+            children: Vec::new(),
+            address: address.into(),
+            reference: false,
+        }
+    }
+
+    /// Writes the link as `[text][label]` with the label derived from the text
+    ///
+    /// The definition `[label]: address` is collected by [Markdown](struct.Markdown.html)
+    /// and written by [write_link_definitions](struct.Markdown.html#method.write_link_definitions).
+    /// Without a collecting writer, or in flavors without reference links,
+    /// the link is written inline.
+    pub fn reference(mut self) -> Self {
+        self.reference = true;
+        self
+    }
+
+    /// Creates an empty link, which leads to the anchor of `heading` in the same document
+    pub fn to_heading(heading: &Heading) -> Self {
+        Self::new(format!("#{}", heading.anchor()))
+    }
+
+    /// Appends an element to the link's text
+    pub fn append<T: 'a + InlineElement>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+}
+
+impl MarkdownWritable for &'_ Link<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let mut text = Vec::new();
+        for child in &self.children {
+            child.write_to(
+                &mut text,
+                &context.child(true, escape, line_prefix, options),
+            )?;
+        }
+        if text.is_empty() {
+            options.empty("empty link text")?;
+            self.address.as_ref().write_to(
+                &mut text,
+                &context.child(true, escape, line_prefix, options),
+            )?;
+        }
+        writer.write_all(b"[")?;
+        writer.write_all(&text)?;
+        let address = options.rewrite_link(&self.address);
+        let too_long = options.long_links == LongLinks::Reference
+            && options
+                .line_width
+                .is_some_and(|width| text.len() + address.len() + 4 > width);
+        let label = if self.reference || too_long {
+            let slug = slugify(&plain_text(&String::from_utf8_lossy(&text)));
+            let slug = if slug.is_empty() {
+                "link".to_string()
+            } else {
+                slug
+            };
+            options.define_link(&slug, &address)
+        } else {
+            None
+        };
+        match label {
+            Some(label) => write!(writer, "][{}]", label)?,
+            None => {
+                writer.write_all(b"](")?;
+                write_address(writer, &address, options.flavor, line_prefix)?;
+                writer.write_all(b")")?;
+            }
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let (mut addr, addr_cr) = self.address.count_max_streak(char, 0);
+        addr += addr_cr;
+        let mut carry = 0;
+        let mut count = 0;
+        for child in &self.children {
+            let (c, cr) = child.count_max_streak(char, carry);
+            count += c;
+            carry = cr;
+        }
+        count += carry;
+        if count > addr {
+            (count, 0)
+        } else {
+            (addr, 0)
+        }
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        let inlines: Vec<serde_json::Value> = self
+            .children
+            .iter()
+            .flat_map(|child| child.pandoc_inlines(options))
+            .collect();
+        vec![serde_json::json!({
+            "t": "Link",
+            "c": [pandoc::attr(&[]), inlines, [options.rewrite_link(&self.address), ""]],
+        })]
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        vec![serde_json::json!({"t": "Para", "c": self.pandoc_inlines(options)})]
+    }
+}
+
+impl MarkdownWritable for Link<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+
+impl<'a> AsMarkdown<'a> for &'a Link<'a> {
+    fn paragraph(self) -> Paragraph<'a> {
+        Paragraph::new().append(self)
+    }
+
+    fn heading(self, level: usize) -> Heading<'a> {
+        Heading::new(level).append(self)
+    }
+
+    fn link_to<A: Into<Cow<'a, str>>>(self, _address: A) -> Link<'a> {
+        panic!("Link cannot contain another link.");
+    }
+
+    fn bold(self) -> RichText<'a> {
+        panic!("Cannot change link's body. Please use 'x.as_bold().as_link_to(...);'");
+    }
+
+    fn italic(self) -> RichText<'a> {
+        panic!("Cannot change link's body. Please use 'x.as_italic().as_link_to(...);'");
+    }
+
+    fn code(self) -> RichText<'a> {
+        panic!("Cannot change link's body. Please use 'x.as_code().as_link_to(...);'");
+    }
+
+    fn quote(self) -> Quote<'a> {
+        Quote::new().append(self)
+    }
+
+    fn image_to<A: Into<Cow<'a, str>>>(self, _address: A) -> Image<'a> {
+        panic!("Link cannot be converted to an image.");
+    }
+}
+
+impl<'a> AsMarkdown<'a> for Link<'a> {
+    fn paragraph(self) -> Paragraph<'a> {
+        Paragraph::new().append(self)
+    }
+
+    fn heading(self, level: usize) -> Heading<'a> {
+        Heading::new(level).append(self)
+    }
+
+    fn link_to<A: Into<Cow<'a, str>>>(self, _address: A) -> Link<'a> {
+        panic!("Link cannot contain another link.");
+    }
+
+    fn bold(self) -> RichText<'a> {
+        panic!("Cannot change link's body. Please use 'x.as_bold().as_link_to(...);'");
+    }
+
+    fn italic(self) -> RichText<'a> {
+        panic!("Cannot change link's body. Please use 'x.as_italic().as_link_to(...);'");
+    }
+
+    fn code(self) -> RichText<'a> {
+        panic!("Cannot change link's body. Please use 'x.as_code().as_link_to(...);'");
+    }
+
+    fn quote(self) -> Quote<'a> {
+        Quote::new().append(self)
+    }
+
+    fn image_to<A: Into<Cow<'a, str>>>(self, _address: A) -> Image<'a> {
+        panic!("Link cannot be converted to an image.");
+    }
+}
+//endregion
+
+//region RichText
+/// Text styled with **bold**, *italic* or `code`
+#[derive(Clone)]
+pub struct RichText<'a> {
+    bold: bool,
+    italic: bool,
+    code: bool,
+    text: Cow<'a, str>,
+}
+
+impl<'a> RichText<'a> {
+    /// Creates unstyled text, which can be either borrowed or owned
+    pub fn new<T: Into<Cow<'a, str>>>(text: T) -> Self {
+        Self {
+            bold: false,
+            italic: false,
+            code: false,
+            text: text.into(),
+        }
+    }
+}
+
+impl RichText<'_> {
+    /// Writes the text with Telegram MarkdownV2 markers, where code cannot be padded
+    fn write_telegram(
+        &self,
+        writer: &mut dyn Write,
+        context: &RenderContext<'_>,
+    ) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let (bold, italic) = options.map_emphasis(self.bold, self.italic);
+        let mut symbol = Vec::new();
+        if bold {
+            symbol.push(b'*');
+        }
+        if italic {
+            symbol.push(b'_');
+        }
+        if self.code {
+            symbol.push(b'`');
+        }
+        writer.write_all(&symbol)?;
+        if self.code {
+            write_escaped(
+                writer,
+                self.text.as_bytes(),
+                EscapeContext::InlineCode(Flavor::Telegram).escaped(),
+                line_prefix,
+            )?;
+        } else {
+            let edges = bold || italic || !inner;
+            options
+                .whitespace(
+                    options.tabs(Cow::Borrowed(&self.text), Some(context.line))?,
+                    edges,
+                )?
+                .as_ref()
+                .write_to(
+                    writer,
+                    &context.child(true, context.escape, line_prefix, options),
+                )?;
+        }
+        symbol.reverse();
+        writer.write_all(&symbol)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+}
+
+impl MarkdownWritable for &'_ RichText<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        if self.text.is_empty() {
+            return options.empty("empty text");
+        }
+        if options.flavor == Flavor::Telegram {
+            return self.write_telegram(writer, context);
+        }
+        let (bold, italic) = options.map_emphasis(self.bold, self.italic);
+        let mut symbol = Vec::new();
+        if bold {
+            symbol.extend_from_slice(options.flavor.strong(options.emphasis));
+        }
+        if italic {
+            symbol.extend_from_slice(options.flavor.emphasis(options.emphasis));
+        }
+        let mut code = Cow::Borrowed(self.text.as_ref());
+        if self.code {
+            // Backticks are counted in the same pass which checks if the text needs processing,
+            // so plain code is scanned only once before it is written
+            let (streak, carry, plain) = scan_code(code.as_bytes());
+            let (streak, carry) = if plain
+                || (options.control == ControlChars::Keep && options.tabs == TabPolicy::Preserve)
+            {
+                (streak, carry)
+            } else {
+                code = options.tabs(options.sanitize(code), None)?;
+                code.count_max_streak(b'`', 0)
+            };
+            symbol.extend(vec![b'`'; streak + carry + 1]);
+            symbol.push(b' ');
+        }
+
+        writer.write_all(&symbol)?;
+        if self.code {
+            writer.write_all(code.as_bytes())?;
+        } else {
+            let edges = bold || italic || !inner;
+            options
+                .whitespace(
+                    options.tabs(Cow::Borrowed(&self.text), Some(context.line))?,
+                    edges,
+                )?
+                .as_ref()
+                .write_to(writer, &context.child(true, escape, line_prefix, options))?;
+        }
+        symbol.reverse();
+        writer.write_all(&symbol)?;
+
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let (res, cr) = self.text.count_max_streak(char, 0);
+        (res + cr, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        let mut inlines = if self.code {
+            vec![serde_json::json!({"t": "Code", "c": [pandoc::attr(&[]), self.text]})]
+        } else {
+            self.text.pandoc_inlines(options)
+        };
+        if self.italic {
+            inlines = vec![serde_json::json!({"t": "Emph", "c": inlines})];
+        }
+        if self.bold {
+            inlines = vec![serde_json::json!({"t": "Strong", "c": inlines})];
+        }
+        inlines
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        vec![serde_json::json!({"t": "Para", "c": self.pandoc_inlines(options)})]
+    }
+}
+
+impl MarkdownWritable for RichText<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+
+impl<'a> AsMarkdown<'a> for &'a RichText<'a> {
+    fn paragraph(self) -> Paragraph<'a> {
+        Paragraph::new().append(self)
+    }
+
+    fn heading(self, level: usize) -> Heading<'a> {
+        Heading::new(level).append(self)
+    }
+
+    fn link_to<A: Into<Cow<'a, str>>>(self, address: A) -> Link<'a> {
+        Link::new(address).append(self)
+    }
+
+    fn bold(self) -> RichText<'a> {
+        let mut clone = self.clone();
+        clone.bold = true;
+        clone
+    }
+
+    fn italic(self) -> RichText<'a> {
+        let mut clone = self.clone();
+        clone.italic = true;
+        clone
+    }
+
+    fn code(self) -> RichText<'a> {
+        let mut clone = self.clone();
+        clone.code = true;
+        clone
+    }
+
+    fn quote(self) -> Quote<'a> {
+        Quote::new().append(self)
+    }
+
+    fn image_to<A: Into<Cow<'a, str>>>(self, address: A) -> Image<'a> {
+        Image::new(self.text.clone(), address)
+    }
+}
+
+impl<'a> AsMarkdown<'a> for RichText<'a> {
+    fn paragraph(self) -> Paragraph<'a> {
+        Paragraph::new().append(self)
+    }
+
+    fn heading(self, level: usize) -> Heading<'a> {
+        Heading::new(level).append(self)
+    }
+
+    fn link_to<A: Into<Cow<'a, str>>>(self, address: A) -> Link<'a> {
+        Link::new(address).append(self)
+    }
+
+    fn bold(mut self) -> RichText<'a> {
+        self.bold = true;
+        self
+    }
+
+    fn italic(mut self) -> RichText<'a> {
+        self.italic = true;
+        self
+    }
+
+    fn code(mut self) -> RichText<'a> {
+        self.code = true;
+        self
+    }
+
+    fn quote(self) -> Quote<'a> {
+        Quote::new().append(self)
+    }
+
+    fn image_to<A: Into<Cow<'a, str>>>(self, address: A) -> Image<'a> {
+        Image::new(self.text, address)
+    }
+}
+//endregion
+
+//region Change
+/// Changed value written inline, e.g. `~~old~~ **new**` for a configuration drift
+///
+/// The old value is struck through and the new value is bold. CommonMark has no strikethrough,
+/// so `<del>old</del> <ins>new</ins>` is written there. Equal values are written as plain text.
+/// Values are trimmed, as delimiters next to spaces do not render, and empty values are left out.
+pub struct Change<'a> {
+    old: Option<Cow<'a, str>>,
+    new: Option<Cow<'a, str>>,
+}
+
+impl<'a> Change<'a> {
+    /// Creates a change of `old` value to `new` value
+    pub fn new<O: Into<Cow<'a, str>>, N: Into<Cow<'a, str>>>(old: O, new: N) -> Self {
+        Self {
+            old: Some(old.into()),
+            new: Some(new.into()),
+        }
+    }
+
+    /// Creates a value which was added
+    pub fn added<N: Into<Cow<'a, str>>>(new: N) -> Self {
+        Self {
+            old: None,
+            new: Some(new.into()),
+        }
+    }
+
+    /// Creates a value which was removed
+    pub fn removed<O: Into<Cow<'a, str>>>(old: O) -> Self {
+        Self {
+            old: Some(old.into()),
+            new: None,
+        }
+    }
+
+    /// Returns trimmed `(old, new)` values, empty values are `None`
+    fn values(&self) -> (Option<&str>, Option<&str>) {
+        fn trimmed(value: Option<&str>) -> Option<&str> {
+            value.map(str::trim).filter(|value| !value.is_empty())
+        }
+        (trimmed(self.old.as_deref()), trimmed(self.new.as_deref()))
+    }
+}
+
+impl MarkdownWritable for &'_ Change<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let (old, new) = self.values();
+        if old.is_some() && old == new {
+            return RichText::new(new.unwrap_or_default()).write_to(writer, context);
+        }
+        let html = options.flavor == Flavor::CommonMark;
+        if let Some(old) = old {
+            writer.write_all(match options.flavor {
+                Flavor::CommonMark => b"<del>",
+                Flavor::Telegram => b"~",
+                _ => b"~~",
+            })?;
+            RichText::new(old)
+                .write_to(writer, &context.child(true, escape, line_prefix, options))?;
+            writer.write_all(match options.flavor {
+                Flavor::CommonMark => b"</del>",
+                Flavor::Telegram => b"~",
+                _ => b"~~",
+            })?;
+        }
+        if let Some(new) = new {
+            if old.is_some() {
+                writer.write_all(b" ")?;
+            }
+            if html {
+                writer.write_all(b"<ins>")?;
+                RichText::new(new)
+                    .write_to(writer, &context.child(true, escape, line_prefix, options))?;
+                writer.write_all(b"</ins>")?;
+            } else {
+                RichText::new(new)
+                    .bold()
+                    .write_to(writer, &context.child(true, escape, line_prefix, options))?;
+            }
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for text in self.old.iter().chain(&self.new) {
+            let (c, cr) = text.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        let (old, new) = self.values();
+        if old.is_some() && old == new {
+            return pandoc::text(new.unwrap_or_default());
+        }
+        let mut inlines = Vec::new();
+        if let Some(old) = old {
+            inlines.push(serde_json::json!({"t": "Strikeout", "c": pandoc::text(old)}));
+        }
+        if let Some(new) = new {
+            if old.is_some() {
+                inlines.push(serde_json::json!({"t": "Space"}));
+            }
+            inlines.extend(RichText::new(new).bold().pandoc_inlines(options));
+        }
+        inlines
+    }
+}
+
+impl MarkdownWritable for Change<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+}
+//endregion
+
+//region Image
+/// Markdown image
+#[derive(Clone)]
+pub struct Image<'a> {
+    alt: Cow<'a, str>,
+    address: Cow<'a, str>,
+    title: Option<Cow<'a, str>>,
+}
+
+impl<'a> Image<'a> {
+    /// Creates an image
+    ///
+    /// # Arguments
+    /// * `alt` - Alternative text of the image
+    /// * `address` - Address of the image
+    pub fn new<T: Into<Cow<'a, str>>, A: Into<Cow<'a, str>>>(alt: T, address: A) -> Self {
+        Self {
+            alt: alt.into(),
+            address: address.into(),
+            title: None,
+        }
+    }
+
+    /// Sets the title of the image
+    pub fn title<T: Into<Cow<'a, str>>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+}
+
+impl MarkdownWritable for &'_ Image<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        writer.write_all(b"![")?;
+        self.alt
+            .write_to(writer, &context.child(true, escape, line_prefix, options))?;
+        writer.write_all(b"](")?;
+        write_address(
+            writer,
+            &options.rewrite_link(&self.address),
+            options.flavor,
+            line_prefix,
+        )?;
+        if let Some(title) = &self.title {
+            writer.write_all(b" \"")?;
+            write_escaped(
+                writer,
+                title.as_bytes(),
+                EscapeContext::Quoted.escaped(),
+                line_prefix,
+            )?;
+            writer.write_all(b"\"")?;
+        }
+        writer.write_all(b")")?;
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let (count, carry) = self.alt.count_max_streak(char, 0);
+        (count.max(carry), 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        let title = self.title.as_deref().unwrap_or("");
+        vec![serde_json::json!({
+            "t": "Image",
+            "c": [
+                pandoc::attr(&[]),
+                self.alt.pandoc_inlines(options),
+                [options.rewrite_link(&self.address), title],
+            ],
+        })]
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        vec![serde_json::json!({"t": "Para", "c": self.pandoc_inlines(options)})]
+    }
+}
+
+impl MarkdownWritable for Image<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+
+impl<'a> AsMarkdown<'a> for Image<'a> {
+    fn paragraph(self) -> Paragraph<'a> {
+        Paragraph::new().append(self)
+    }
+
+    fn heading(self, level: usize) -> Heading<'a> {
+        Heading::new(level).append(self)
+    }
+
+    fn link_to<A: Into<Cow<'a, str>>>(self, address: A) -> Link<'a> {
+        Link::new(address).append(self)
+    }
+
+    fn bold(self) -> RichText<'a> {
+        panic!("Cannot make an Image bold");
+    }
+
+    fn italic(self) -> RichText<'a> {
+        panic!("Cannot make an Image italic");
+    }
+
+    fn code(self) -> RichText<'a> {
+        panic!("Cannot make an Image code");
+    }
+
+    fn quote(self) -> Quote<'a> {
+        Quote::new().append(self)
+    }
+
+    fn image_to<A: Into<Cow<'a, str>>>(self, _address: A) -> Image<'a> {
+        panic!("Image cannot contain another image.");
+    }
+}
+//endregion
+
+//region List
+/// Bulleted or numbered list
+pub struct List<'a> {
+    title: Vec<Box<dyn 'a + MarkdownWritable>>,
+    items: Vec<(Option<bool>, Box<dyn 'a + MarkdownWritable>)>,
+    numbered: bool,
+}
+
+impl<'a> List<'a> {
+    /// Creates an empty list
+    /// # Arguments
+    /// * `numbered` - `true` for numbered list, `false` for bulleted list
+    pub fn new(numbered: bool) -> Self {
+        Self {
+            items: Vec::new(),
+            title: Vec::new(),
+            numbered,
+        }
+    }
+
+    /// Append an item to the list title
+    pub fn title<T: 'a + InlineElement>(mut self, item: T) -> Self {
+        self.title.push(Box::new(item));
+        self
+    }
+
+    /// Adds an item to the list
+    pub fn item<T: 'a + MarkdownWritable>(mut self, item: T) -> Self {
+        self.items.push((None, Box::new(item)));
+        self
+    }
+
+    /// Adds a task item with a checkbox to the list
+    ///
+    /// # Arguments
+    /// * `checked` - `true` if the task is done, `false` otherwise
+    /// * `item` - Contents of the item
+    pub fn item_checked<T: 'a + MarkdownWritable>(mut self, checked: bool, item: T) -> Self {
+        self.items.push((Some(checked), Box::new(item)));
+        self
+    }
+
+    /// Creates a list of `items`
+    /// # Arguments
+    /// * `numbered` - `true` for numbered list, `false` for bulleted list
+    /// * `items` - Contents of the items
+    pub fn from_items<T, I>(numbered: bool, items: I) -> Self
+    where
+        T: 'a + MarkdownWritable,
+        I: IntoIterator<Item = T>,
+    {
+        let mut list = Self::new(numbered);
+        list.extend(items);
+        list
+    }
+}
+
+/// Collects items into a bulleted list
+impl<'a, T: 'a + MarkdownWritable> FromIterator<T> for List<'a> {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Self {
+        Self::from_items(false, items)
+    }
+}
+
+impl<'a, T: 'a + MarkdownWritable> Extend<T> for List<'a> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        self.items.extend(
+            items
+                .into_iter()
+                .map(|item| (None, Box::new(item) as Box<dyn 'a + MarkdownWritable>)),
+        );
+    }
+}
+
+impl MarkdownWritable for &'_ List<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        for it in &self.title {
+            it.write_to(writer, &context.child(true, escape, line_prefix, options))?;
+        }
+        let mut prefix = Vec::new();
+        if let Some(line_prefix) = line_prefix {
+            prefix.extend_from_slice(line_prefix);
+        }
+        let (bullet, indent) = options.list_style();
+        let indent = if self.numbered { indent.max(3) } else { indent };
+        prefix.extend(vec![b' '; indent]);
+
+        // Telegram has no lists, so items are written as numbered or bulleted lines
+        let telegram = options.flavor == Flavor::Telegram;
+        // Items are indented unless a list style is set, which starts markers
+        // at the enclosing line prefix as markdownlint expects
+        let marker_prefix = if telegram || options.list_indent.is_some() {
+            line_prefix
+        } else {
+            Some(&prefix[..])
+        };
+        for (number, (checked, it)) in self.items.iter().enumerate() {
+            let mut marker: Vec<u8> = if telegram && self.numbered {
+                format!("\n{}\\. ", number + 1).into_bytes()
+            } else if telegram {
+                "\n• ".as_bytes().to_vec()
+            } else if self.numbered {
+                b"\n1. ".to_vec()
+            } else {
+                let bullet = match bullet {
+                    Bullet::Asterisk => b'*',
+                    Bullet::Dash => b'-',
+                    Bullet::Plus => b'+',
+                };
+                vec![b'\n', bullet, b' ']
+            };
+            match (checked, telegram) {
+                (Some(true), false) => marker.extend_from_slice(b"[x] "),
+                (Some(false), false) => marker.extend_from_slice(b"[ ] "),
+                (Some(true), true) => marker.extend_from_slice(b"\\[x\\] "),
+                (Some(false), true) => marker.extend_from_slice(b"\\[ \\] "),
+                (None, _) => {}
+            }
+            let mut item = Vec::new();
+            it.write_to(
+                &mut item,
+                &context.nested(Nesting::List, true, escape, Some(&prefix), options),
+            )?;
+            if item.is_empty() {
+                options.empty("empty list item")?;
+                marker.pop();
+            }
+            write_line_prefixed(writer, &marker, marker_prefix)?;
+            writer.write_all(&item)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for (_, child) in &self.items {
+            let (c, _) = child.count_max_streak(char, 0);
+            if c > count {
+                count = c;
+            }
+        }
+        (count, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        let title: Vec<_> = self
+            .title
+            .iter()
+            .flat_map(|element| element.pandoc_inlines(options))
+            .collect();
+        let items = self
+            .items
+            .iter()
+            .map(|(checked, item)| {
+                let blocks = pandoc::plain(item.pandoc_blocks(options));
+                match checked {
+                    Some(checked) => pandoc::task(*checked, blocks),
+                    None => blocks,
+                }
+            })
+            .collect();
+        let mut blocks = Vec::new();
+        if !title.is_empty() {
+            blocks.push(serde_json::json!({"t": "Para", "c": title}));
+        }
+        blocks.push(pandoc::list(self.numbered, items));
+        blocks
+    }
+}
+
+impl<'a> MarkdownWritable for List<'a> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+
+impl<'a> AsMarkdown<'a> for List<'a> {
+    fn paragraph(self) -> Paragraph<'a> {
+        panic!(
+            "Cannot make a Paragraph from List, use List::title for the text before a nested list"
+        );
+    }
+
+    fn heading(self, _level: usize) -> Heading<'a> {
+        panic!("Cannot make a Heading from List");
+    }
+
+    fn link_to<A: Into<Cow<'a, str>>>(self, _address: A) -> Link<'a> {
+        panic!("Cannot make a Link from List");
+    }
+
+    fn bold(self) -> RichText<'a> {
+        panic!("Cannot make a List bold");
+    }
+
+    fn italic(self) -> RichText<'a> {
+        panic!("Cannot make a List italic");
+    }
+
+    fn code(self) -> RichText<'a> {
+        panic!("Cannot make a List code");
+    }
+
+    fn quote(self) -> Quote<'a> {
+        Quote::new().append(self)
+    }
+
+    fn image_to<A: Into<Cow<'a, str>>>(self, _address: A) -> Image<'a> {
+        panic!("Cannot make an Image from List");
+    }
+}
+//endregion
+
+//region Quote
+/// A quote block
+pub struct Quote<'a> {
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+}
+
+impl<'a> Quote<'a> {
+    /// Creates an empty quote block
+    fn new() -> Self {
+        Self {
+            children: Vec::new(),
+        }
+    }
+
+    /// Appends an element to the quote block
+    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+}
+
+impl MarkdownWritable for &'_ Quote<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let mut prefix = Vec::new();
+        if let Some(line_prefix) = line_prefix {
+            prefix.extend_from_slice(line_prefix);
+        }
+        prefix.extend_from_slice(b">");
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        writer.write_all(b">")?;
+        for child in &self.children {
+            child.write_to(
+                writer,
+                &context.nested(Nesting::Quote, true, escape, Some(&prefix), options),
+            )?;
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in &self.children {
+            let (c, _) = child.count_max_streak(char, 0);
+            if c > count {
+                count = c;
+            }
+        }
+        (count, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        let inlines: Vec<_> = self
+            .children
+            .iter()
+            .flat_map(|child| child.pandoc_inlines(options))
+            .collect();
+        vec![serde_json::json!({"t": "BlockQuote", "c": [{"t": "Para", "c": inlines}]})]
+    }
+}
+impl<'a> MarkdownWritable for Quote<'a> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+//endregion
+
+//region Table
+/// Alignment of a [Table](struct.Table.html) column
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Alignment {
+    /// Alignment is not specified
+    None,
+    /// Column is aligned to the left
+    Left,
+    /// Column is centered
+    Center,
+    /// Column is aligned to the right
+    Right,
+}
+
+/// Syntax used for writing a [Table](struct.Table.html)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TableStyle {
+    /// GFM pipe table, cells can contain only inline elements
+    Pipe,
+    /// Pandoc grid table (`+---+---+`), cells can contain multiple lines and block elements
+    Grid,
+}
+
+/// Table with a header row and data rows
+pub struct Table<'a> {
+    header: Vec<Box<dyn 'a + MarkdownWritable>>,
+    alignment: Vec<Alignment>,
+    rows: Vec<Vec<Box<dyn 'a + MarkdownWritable>>>,
+    style: TableStyle,
+    limit: Option<usize>,
+    omitted: usize,
+    omitted_template: Option<Cow<'a, str>>,
+    more_link: Option<Cow<'a, str>>,
+    aligned: bool,
+    row_anchors: Option<(usize, Cow<'a, str>)>,
+    /// Some rows were added by [block_row](#method.block_row)
+    blocks: bool,
+}
+
+impl<'a> Table<'a> {
+    /// Creates an empty table
+    pub fn new() -> Self {
+        Self {
+            header: Vec::new(),
+            alignment: Vec::new(),
+            rows: Vec::new(),
+            style: TableStyle::Pipe,
+            limit: None,
+            omitted: 0,
+            omitted_template: None,
+            more_link: None,
+            aligned: false,
+            row_anchors: None,
+            blocks: false,
+        }
+    }
+
+    /// Limits the number of written rows to `max_rows`
+    ///
+    /// Rows over the limit are dropped and counted, the table is followed by
+    /// a line like `… and 1,234 more rows`.
+    pub fn limit(mut self, max_rows: usize) -> Self {
+        if self.rows.len() > max_rows {
+            self.omitted += self.rows.len() - max_rows;
+            self.rows.truncate(max_rows);
+        }
+        self.limit = Some(max_rows);
+        self
+    }
+
+    /// Sets text of the line about omitted rows, e.g. `"… und {count} weitere Zeilen"`
+    ///
+    /// Placeholder `{count}` is replaced by the number of omitted rows.
+    /// Defaults to `"… and {count} more rows"`, or `"… and 1 more row"`.
+    pub fn omitted_template<T: Into<Cow<'a, str>>>(mut self, template: T) -> Self {
+        self.omitted_template = Some(template.into());
+        self
+    }
+
+    /// Makes the line about omitted rows a link, e.g. to the full data
+    pub fn more_link<A: Into<Cow<'a, str>>>(mut self, address: A) -> Self {
+        self.more_link = Some(address.into());
+        self
+    }
+
+    /// Adds data rows
+    pub fn rows<R, I, T>(self, rows: R) -> Self
+    where
+        R: IntoIterator<Item = I>,
+        I: IntoIterator<Item = T>,
+        T: 'a + InlineElement,
+    {
+        rows.into_iter().fold(self, |table, cells| table.row(cells))
+    }
+
+    /// Pads cells of a pipe table, so the pipes line up in the markdown source
+    ///
+    /// With the `unicode-width` feature, wide characters, e.g. CJK, are counted as two columns.
+    pub fn aligned(mut self) -> Self {
+        self.aligned = true;
+        self
+    }
+
+    /// Writes an `<a id="anchor"></a>` before the cell in `column` of every row, so rows can be linked
+    ///
+    /// The anchor is `prefix` followed by the [slug](fn.slugify.html) of the cell text,
+    /// duplicates are numbered like heading anchors, e.g. `user-jane-1`. Rows with an empty
+    /// cell get no anchor. Telegram and Discord do not render HTML, so no anchors are written there.
+    pub fn row_anchors<P: Into<Cow<'a, str>>>(mut self, column: usize, prefix: P) -> Self {
+        self.row_anchors = Some((column, prefix.into()));
+        self
+    }
+
+    /// Sets the syntax used for writing the table, [TableStyle::Pipe](enum.TableStyle.html#variant.Pipe) is used by default
+    pub fn style(mut self, style: TableStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets cells of the header row
+    pub fn header<I, T>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: 'a + InlineElement,
+    {
+        self.header = cells
+            .into_iter()
+            .map(|cell| Box::new(cell) as Box<dyn 'a + MarkdownWritable>)
+            .collect();
+        self
+    }
+
+    /// Sets alignment of the columns
+    ///
+    /// Columns without specified alignment use the [preferred alignment](trait.MarkdownWritable.html#method.preferred_alignment)
+    /// of their cells, e.g. of [Number](locale/struct.Number.html), or [Alignment::None](enum.Alignment.html#variant.None)
+    pub fn alignment<I: IntoIterator<Item = Alignment>>(mut self, alignment: I) -> Self {
+        self.alignment = alignment.into_iter().collect();
+        self
+    }
+
+    /// Sets alignment of a single column, e.g. to override alignment inferred by
+    /// [from_serialize](#method.from_serialize)
+    pub fn column_alignment(mut self, column: usize, alignment: Alignment) -> Self {
+        if self.alignment.len() <= column {
+            self.alignment.resize(column + 1, Alignment::None);
+        }
+        self.alignment[column] = alignment;
+        self
+    }
+
+    /// Adds a data row, which is only counted if the [limit](#method.limit) is reached
+    pub fn row<I, T>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: 'a + InlineElement,
+    {
+        self.push_row(
+            cells
+                .into_iter()
+                .map(|cell| Box::new(cell) as Box<dyn 'a + MarkdownWritable>)
+                .collect(),
+        );
+        self
+    }
+
+    /// Adds a data row of block elements, e.g. lists or multiple paragraphs
+    ///
+    /// Only grid tables can contain blocks, so the table is written as a
+    /// [TableStyle::Grid](enum.TableStyle.html#variant.Grid) table regardless of its [style](#method.style).
+    pub fn block_row<I, T>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: 'a + MarkdownWritable,
+    {
+        self.blocks = true;
+        self.push_row(
+            cells
+                .into_iter()
+                .map(|cell| Box::new(cell) as Box<dyn 'a + MarkdownWritable>)
+                .collect(),
+        );
+        self
+    }
+
+    fn push_row(&mut self, cells: Vec<Box<dyn 'a + MarkdownWritable>>) {
+        if self.limit.is_some_and(|limit| self.rows.len() >= limit) {
+            self.omitted += 1;
+            return;
+        }
+        self.rows.push(cells);
+    }
+
+    /// Returns alignment of `columns` columns, unspecified alignment is inferred from the cells
+    fn column_alignments(&self, columns: usize) -> Vec<Alignment> {
+        (0..columns)
+            .map(|column| match self.alignment.get(column) {
+                Some(alignment) if *alignment != Alignment::None => *alignment,
+                _ => {
+                    let mut preferred = self
+                        .rows
+                        .iter()
+                        .filter_map(|row| row.get(column)?.preferred_alignment());
+                    match preferred.next() {
+                        Some(first) if preferred.all(|other| other == first) => first,
+                        _ => Alignment::None,
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn columns(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .chain(Some(self.header.len()))
+            .chain(Some(self.alignment.len()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Prepends [row anchors](#method.row_anchors) to the key cells of rendered `rows`
+    fn anchor_rows(&self, rows: &mut [Vec<String>], options: &Options) {
+        let (column, prefix) = match &self.row_anchors {
+            Some(row_anchors) => row_anchors,
+            None => return,
+        };
+        if matches!(options.flavor, Flavor::Telegram | Flavor::Discord) {
+            options.warn(WarningKind::Degraded, "row anchors dropped");
+            return;
+        }
+        let mut counts = BTreeMap::new();
+        for cell in rows.iter_mut().filter_map(|row| row.get_mut(*column)) {
+            let slug = slugify(&plain_text(cell));
+            if slug.is_empty() {
+                continue;
+            }
+            let anchor = numbered(counts.entry(slug.clone()).or_insert(0), &slug);
+            cell.insert_str(0, &format!("<a id=\"{}{}\"></a>", prefix, anchor));
+        }
+    }
+
+    /// Writes the line about rows omitted by the [limit](#method.limit)
+    fn write_omitted(
+        &self,
+        writer: &mut dyn Write,
+        context: &RenderContext<'_>,
+    ) -> Result<(), Error> {
+        if self.omitted == 0 {
+            return Ok(());
+        }
+        context.options.warn(
+            WarningKind::Truncated,
+            format!(
+                "{} table row{} over the limit omitted",
+                self.omitted,
+                if self.omitted == 1 { "" } else { "s" }
+            ),
+        );
+        let RenderContext {
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        self.omitted_note()
+            .write_to(writer, &context.child(false, Normal, line_prefix, options))
+    }
+
+    /// Returns the paragraph written after the table about rows over the limit
+    fn omitted_note(&self) -> Paragraph<'_> {
+        let template = match &self.omitted_template {
+            Some(template) => template.as_ref(),
+            None if self.omitted == 1 => "… and {count} more row",
+            None => "… and {count} more rows",
+        };
+        let text = template.replace("{count}", &group_thousands(self.omitted));
+        let text = RichText::new(text).italic();
+        match &self.more_link {
+            Some(address) => Paragraph::new().append(Link::new(address.as_ref()).append(text)),
+            None => Paragraph::new().append(text),
+        }
+    }
+}
+
+/// Formats `value` with thousands separated by commas, e.g. `1,234`
+fn group_thousands(value: usize) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+impl Default for Table<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder of a [Table](struct.Table.html) from columns instead of rows
+pub struct ColumnTable<'a> {
+    names: Vec<Box<dyn 'a + MarkdownWritable>>,
+    columns: Vec<Vec<Box<dyn 'a + MarkdownWritable>>>,
+}
+
+impl<'a> ColumnTable<'a> {
+    /// Creates a builder without columns
+    pub fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Adds a column with header `name` and cells `values`
+    pub fn column<N, I, T>(mut self, name: N, values: I) -> Self
+    where
+        N: 'a + InlineElement,
+        I: IntoIterator<Item = T>,
+        T: 'a + InlineElement,
+    {
+        self.names.push(Box::new(name));
+        self.columns.push(
+            values
+                .into_iter()
+                .map(|cell| Box::new(cell) as Box<dyn 'a + MarkdownWritable>)
+                .collect(),
+        );
+        self
+    }
+
+    /// Zips the columns into rows of a table
+    ///
+    /// # Returns
+    /// [Table](struct.Table.html) or [ColumnLengthError](struct.ColumnLengthError.html)
+    /// if a column has a different number of values than the first one
+    pub fn build(self) -> Result<Table<'a>, ColumnLengthError> {
+        let expected = self.columns.first().map_or(0, Vec::len);
+        if let Some((column, values)) = self
+            .columns
+            .iter()
+            .enumerate()
+            .find(|(_, values)| values.len() != expected)
+        {
+            return Err(ColumnLengthError {
+                column,
+                expected,
+                found: values.len(),
+            });
+        }
+
+        let mut table = Table::new();
+        table.header = self.names;
+        let mut columns: Vec<_> = self.columns.into_iter().map(Vec::into_iter).collect();
+        for _ in 0..expected {
+            table
+                .rows
+                .push(columns.iter_mut().filter_map(Iterator::next).collect());
+        }
+        Ok(table)
+    }
+}
+
+impl Default for ColumnTable<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned by [ColumnTable::build](struct.ColumnTable.html#method.build)
+/// when columns have different lengths
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnLengthError {
+    /// Index of the first column with a different length
+    pub column: usize,
+    /// Number of values in the first column
+    pub expected: usize,
+    /// Number of values in the mismatched column
+    pub found: usize,
+}
+
+impl fmt::Display for ColumnLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "column {} has {} values, expected {}",
+            self.column, self.found, self.expected
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ColumnLengthError {}
+
+impl MarkdownWritable for &'_ Table<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        if options.flavor == Flavor::Telegram {
+            write_code_table(self, writer, context)?;
+            return self.write_omitted(writer, context);
+        }
+        if self.style == TableStyle::Grid || self.blocks {
+            write_grid_table(self, writer, context)?;
+            return self.write_omitted(writer, context);
+        }
+        let columns = self.columns();
+
+        let header_options = options.with_role(TextRole::TableHeader);
+        let header = render_table_row(
+            &self.header,
+            columns,
+            &context.child(inner, escape, line_prefix, &header_options),
+        )?;
+        let mut rows = Vec::new();
+        for row in &self.rows {
+            rows.push(render_table_row(row, columns, context)?);
+        }
+        self.anchor_rows(&mut rows, options);
+        let alignments = self.column_alignments(columns);
+        let alignment = |column: usize| alignments[column];
+
+        let mut widths = vec![0; columns];
+        if self.aligned {
+            for (column, width) in widths.iter_mut().enumerate() {
+                *width = match alignment(column) {
+                    Alignment::None => 3,
+                    Alignment::Left | Alignment::Right => 4,
+                    Alignment::Center => 5,
+                };
+                for row in rows.iter().chain(Some(&header)) {
+                    *width = (*width).max(display_width(&row[column]));
+                }
+            }
+        }
+
+        write_table_row(writer, &header, &widths, &alignments, line_prefix)?;
+        for (column, width) in widths.iter().enumerate() {
+            let (left, right) = match alignment(column) {
+                Alignment::None => ("", ""),
+                Alignment::Left => (":", ""),
+                Alignment::Center => (":", ":"),
+                Alignment::Right => ("", ":"),
+            };
+            let dashes = width.saturating_sub(left.len() + right.len()).max(3);
+            write!(writer, "| {}{}{} ", left, "-".repeat(dashes), right)?;
+        }
+        write_line_prefixed(writer, b"|\n", line_prefix)?;
+        for row in &rows {
+            write_table_row(writer, row, &widths, &alignments, line_prefix)?;
+        }
+
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        self.write_omitted(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for cell in self.header.iter().chain(self.rows.iter().flatten()) {
+            let (c, cr) = cell.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        let columns = self.columns();
+        let cells = |cells: &[Box<dyn MarkdownWritable + '_>], options: &Options| {
+            cells
+                .iter()
+                .map(|cell| {
+                    if self.blocks {
+                        return pandoc::plain(cell.pandoc_blocks(options));
+                    }
+                    let inlines = cell.pandoc_inlines(options);
+                    if inlines.is_empty() {
+                        Vec::new()
+                    } else {
+                        vec![serde_json::json!({"t": "Plain", "c": inlines})]
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        let header = cells(&self.header, &options.with_role(TextRole::TableHeader));
+        let rows = self.rows.iter().map(|row| cells(row, options)).collect();
+        let mut blocks = vec![pandoc::table(
+            &self.column_alignments(columns),
+            header,
+            rows,
+        )];
+        if self.omitted > 0 {
+            blocks.extend(self.omitted_note().pandoc_blocks(options));
+        }
+        blocks
+    }
+}
+
+impl MarkdownWritable for Table<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+/// Table written row by row directly to the underlying writer,
+/// created by [Markdown::begin_table](struct.Markdown.html#method.begin_table)
+///
+/// Rows with fewer cells than the header are filled with empty cells,
+/// extra cells are dropped like GFM renderers do.
+///
+/// As rows are not kept, features of [Table](struct.Table.html) which need all of them are not available:
+/// alignment is not inferred from the cells, columns are not padded as with `Table::aligned`
+/// and rows can't be anchored as with `Table::row_anchors`.
+/// The table is always written as a pipe table, also in flavors writing tables as code blocks.
+pub struct TableWriter<'m, W: Write> {
+    markdown: &'m mut Markdown<W>,
+    columns: usize,
+    header: Option<Vec<String>>,
+    alignment: Vec<Alignment>,
+    written: usize,
+}
+
+impl<W: Write> TableWriter<'_, W> {
+    /// Sets alignment of the columns, must be set before the first row is added
+    pub fn alignment<I: IntoIterator<Item = Alignment>>(mut self, alignment: I) -> Self {
+        self.alignment = alignment.into_iter().collect();
+        self
+    }
+
+    /// Writes a data row
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn row<I, T>(&mut self, cells: I) -> Result<(), io::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: InlineElement,
+    {
+        self.write_header()?;
+        let cells: Vec<Box<dyn MarkdownWritable>> = cells
+            .into_iter()
+            .map(|cell| Box::new(cell) as Box<dyn MarkdownWritable>)
+            .collect();
+        let context = RenderContext::new(false, Normal, None, &self.markdown.options);
+        let row = render_table_row(&cells, self.columns, &context)?;
+        let mut counter = CountingWriter::new(&mut self.markdown.writer);
+        write_table_row(&mut counter, &row, &vec![0; self.columns], &[], None)?;
+        self.written += counter.count;
+        Ok(())
+    }
+
+    /// Ends the table, the header is written if no row was added
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn finish(mut self) -> Result<(), io::Error> {
+        self.write_header()?;
+        self.markdown.writer.write_all(b"\n")?;
+        self.markdown.record(None, self.written + 1);
+        Ok(())
+    }
+
+    /// Writes the header and the delimiter row before the first row
+    fn write_header(&mut self) -> Result<(), io::Error> {
+        let header = match self.header.take() {
+            Some(header) => header,
+            None => return Ok(()),
+        };
+        self.alignment.resize(self.columns, Alignment::None);
+        let mut counter = CountingWriter::new(&mut self.markdown.writer);
+        write_table_row(&mut counter, &header, &vec![0; self.columns], &[], None)?;
+        for alignment in &self.alignment {
+            counter.write_all(match alignment {
+                Alignment::None => b"| --- ",
+                Alignment::Left => b"| :--- ",
+                Alignment::Center => b"| :---: ",
+                Alignment::Right => b"| ---: ",
+            })?;
+        }
+        counter.write_all(b"|\n")?;
+        self.written += counter.count;
+        Ok(())
+    }
+}
+
+/// Renders cells of a table row written in `context`, missing cells are left empty
+fn render_table_row(
+    cells: &[Box<dyn '_ + MarkdownWritable>],
+    columns: usize,
+    context: &RenderContext<'_>,
+) -> Result<Vec<String>, Error> {
+    let cell_context = context.nested(Nesting::Table, true, context.escape, None, context.options);
+    let mut rendered = Vec::new();
+    for column in 0..columns {
+        let mut cell = Vec::new();
+        if let Some(element) = cells.get(column) {
+            let mut buffer = Vec::new();
+            element.write_to(&mut buffer, &cell_context)?;
+            write_table_cell(&mut cell, &buffer)?;
+        }
+        rendered.push(String::from_utf8_lossy(&cell).into_owned());
+    }
+    Ok(rendered)
+}
+
+/// Writes a single rendered table row, padding cells to `widths` according to their alignment
+fn write_table_row(
+    writer: &mut dyn Write,
+    cells: &[String],
+    widths: &[usize],
+    alignment: &[Alignment],
+    line_prefix: Option<&[u8]>,
+) -> Result<(), Error> {
+    for (column, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        let padding = width.saturating_sub(display_width(cell));
+        let left = match alignment.get(column) {
+            Some(Alignment::Right) => padding,
+            Some(Alignment::Center) => padding / 2,
+            _ => 0,
+        };
+        write!(
+            writer,
+            "| {}{}{} ",
+            " ".repeat(left),
+            cell,
+            " ".repeat(padding - left)
+        )?;
+    }
+    write_line_prefixed(writer, b"|\n", line_prefix)
+}
+
+/// Writes `table` as plain text in a code block, Telegram has no tables
+fn write_code_table(
+    table: &Table,
+    writer: &mut dyn Write,
+    context: &RenderContext<'_>,
+) -> Result<(), Error> {
+    let RenderContext {
+        inner,
+        escape,
+        line_prefix,
+        options,
+        ..
+    } = *context;
+    options.warn(WarningKind::Degraded, "table written as a code block");
+    let columns = table.columns();
+    let cell_context = context.nested(Nesting::Table, true, escape, None, options);
+    let render_row = |cells: &[Box<dyn '_ + MarkdownWritable>]| -> Result<Vec<String>, Error> {
+        let mut rendered = Vec::new();
+        for column in 0..columns {
+            let mut buffer = Vec::new();
+            if let Some(cell) = cells.get(column) {
+                cell.write_to(&mut buffer, &cell_context)?;
+            }
+            let text = plain_text(&String::from_utf8_lossy(&buffer));
+            rendered.push(text.trim().replace('\n', " "));
+        }
+        Ok(rendered)
+    };
+    let header = render_row(&table.header)?;
+    let mut rows = Vec::new();
+    for row in &table.rows {
+        rows.push(render_row(row)?);
+    }
+    table.anchor_rows(&mut rows, options);
+
+    let mut widths = vec![0; columns];
+    for row in rows.iter().chain(Some(&header)) {
+        for (column, cell) in row.iter().enumerate() {
+            widths[column] = widths[column].max(display_width(cell));
+        }
+    }
+    let alignments = table.column_alignments(columns);
+    let mut text = String::new();
+    let mut push_row = |cells: &[String]| {
+        let mut line = String::new();
+        for (column, cell) in cells.iter().enumerate() {
+            let padding = widths[column].saturating_sub(display_width(cell));
+            let left = match alignments[column] {
+                Alignment::Right => padding,
+                Alignment::Center => padding / 2,
+                _ => 0,
+            };
+            if column > 0 {
+                line.push_str(" | ");
+            }
+            line.push_str(&" ".repeat(left));
+            line.push_str(cell);
+            line.push_str(&" ".repeat(padding - left));
+        }
+        text.push_str(line.trim_end());
+        text.push('\n');
+    };
+    if !table.header.is_empty() {
+        push_row(&header);
+        let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        push_row(&rule);
+    }
+    for row in &rows {
+        push_row(row);
+    }
+
+    write_line_prefixed(writer, b"```\n", line_prefix)?;
+    write_escaped(writer, text.as_bytes(), b"\\`", line_prefix)?;
+    write_line_prefixed(writer, b"```\n", line_prefix)?;
+    if !inner {
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+    }
+    Ok(())
+}
+
+/// Writes `table` as a grid table
+fn write_grid_table(
+    table: &Table,
+    writer: &mut dyn Write,
+    context: &RenderContext<'_>,
+) -> Result<(), Error> {
+    let RenderContext {
+        inner,
+        escape,
+        line_prefix,
+        options,
+        ..
+    } = *context;
+    let columns = table.columns();
+    let render_row = |cells: &[Box<dyn '_ + MarkdownWritable>],
+                      options: &Options|
+     -> Result<Vec<String>, Error> {
+        let mut rendered = Vec::new();
+        for column in 0..columns {
+            let mut buffer = Vec::new();
+            if let Some(cell) = cells.get(column) {
+                cell.write_to(
+                    &mut buffer,
+                    &context.nested(Nesting::Table, false, escape, None, options),
+                )?;
+            }
+            let text = String::from_utf8_lossy(&buffer);
+            rendered.push(text.trim_matches('\n').to_string());
+        }
+        Ok(rendered)
+    };
+    let header = render_row(&table.header, &options.with_role(TextRole::TableHeader))?;
+    let mut rows = Vec::new();
+    for row in &table.rows {
+        rows.push(render_row(row, options)?);
+    }
+    table.anchor_rows(&mut rows, options);
+
+    let mut widths = vec![1; columns];
+    for row in rows.iter().chain(Some(&header)) {
+        for (column, cell) in row.iter().enumerate() {
+            for line in cell.lines() {
+                widths[column] = widths[column].max(display_width(line));
+            }
+        }
+    }
+
+    let has_header = !table.header.is_empty();
+    let alignments = table.column_alignments(columns);
+    let aligned = |fill: u8| grid_separator(&widths, fill, Some(&alignments));
+    let plain = grid_separator(&widths, b'-', None);
+
+    if has_header {
+        write_line_prefixed(writer, &plain, line_prefix)?;
+        write_grid_row(writer, &header, &widths, line_prefix)?;
+        write_line_prefixed(writer, &aligned(b'='), line_prefix)?;
+    } else {
+        write_line_prefixed(writer, &aligned(b'-'), line_prefix)?;
+    }
+    for row in &rows {
+        write_grid_row(writer, row, &widths, line_prefix)?;
+        write_line_prefixed(writer, &plain, line_prefix)?;
+    }
+    if !inner {
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+    }
+    Ok(())
+}
+
+/// Creates a grid table separator line like `+---+:---:+`
+fn grid_separator(widths: &[usize], fill: u8, alignment: Option<&[Alignment]>) -> Vec<u8> {
+    let mut line = Vec::new();
+    for (column, width) in widths.iter().enumerate() {
+        line.push(b'+');
+        let alignment = alignment
+            .and_then(|alignment| alignment.get(column))
+            .copied()
+            .unwrap_or(Alignment::None);
+        let (left, right) = match alignment {
+            Alignment::None => (fill, fill),
+            Alignment::Left => (b':', fill),
+            Alignment::Center => (b':', b':'),
+            Alignment::Right => (fill, b':'),
+        };
+        line.push(left);
+        line.extend(vec![fill; *width]);
+        line.push(right);
+    }
+    line.extend_from_slice(b"+\n");
+    line
+}
+
+/// Writes a single grid table row, which may span multiple lines
+fn write_grid_row(
+    writer: &mut dyn Write,
+    cells: &[String],
+    widths: &[usize],
+    line_prefix: Option<&[u8]>,
+) -> Result<(), Error> {
+    let cells: Vec<Vec<&str>> = cells.iter().map(|cell| cell.lines().collect()).collect();
+    let height = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+    for line in 0..height {
+        for (cell, width) in cells.iter().zip(widths) {
+            let text = cell.get(line).copied().unwrap_or("");
+            let padding = width - display_width(text);
+            write!(writer, "| {}{} ", text, " ".repeat(padding))?;
+        }
+        write_line_prefixed(writer, b"|\n", line_prefix)?;
+    }
+    Ok(())
+}
+
+/// Writes already rendered cell contents, escaping `|` and replacing line breaks with `<br>`
+fn write_table_cell(writer: &mut dyn Write, mut data: &[u8]) -> Result<(), Error> {
+    loop {
+        let slice_at = data.iter().position(|x| *x == b'|' || *x == b'\n');
+        match slice_at {
+            None => {
+                writer.write_all(data)?;
+                return Ok(());
+            }
+            Some(slice_at) => {
+                writer.write_all(&data[..slice_at])?;
+                if data[slice_at] == b'|' {
+                    writer.write_all(b"\\|")?;
+                } else {
+                    writer.write_all(b"<br>")?;
+                }
+                data = &data[slice_at + 1..];
+            }
+        }
+    }
+}
+//endregion
+
+//region ExampleList
+type ExampleItem<'a> = (Option<Cow<'a, str>>, Box<dyn 'a + MarkdownWritable>);
+
+/// Pandoc numbered example list
+///
+/// Numbering of examples continues across the whole document.
+/// Labeled examples can be referenced with [ExampleRef](struct.ExampleRef.html).
+pub struct ExampleList<'a> {
+    items: Vec<ExampleItem<'a>>,
+}
+
+impl<'a> ExampleList<'a> {
+    /// Creates an empty example list
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    /// Adds an unlabeled example
+    pub fn item<T: 'a + MarkdownWritable>(mut self, item: T) -> Self {
+        self.items.push((None, Box::new(item)));
+        self
+    }
+
+    /// Adds a labeled example
+    ///
+    /// # Arguments
+    /// * `label` - Label of the example, may contain only alphanumeric characters, `_` and `-`
+    /// * `item` - Contents of the example
+    pub fn labeled_item<L, T>(mut self, label: L, item: T) -> Self
+    where
+        L: Into<Cow<'a, str>>,
+        T: 'a + MarkdownWritable,
+    {
+        let label = label.into();
+        assert_example_label(&label);
+        self.items.push((Some(label), Box::new(item)));
+        self
+    }
+}
+
+impl Default for ExampleList<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownWritable for &'_ ExampleList<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let mut prefix = Vec::new();
+        if let Some(line_prefix) = line_prefix {
+            prefix.extend_from_slice(line_prefix);
+        }
+        prefix.extend_from_slice(b"    ");
+
+        for (i, (label, item)) in self.items.iter().enumerate() {
+            if i > 0 {
+                write_line_prefixed(writer, b"\n", line_prefix)?;
+            }
+            write!(writer, "(@{}) ", label.as_deref().unwrap_or(""))?;
+            item.write_to(
+                writer,
+                &context.nested(Nesting::List, true, escape, Some(&prefix), options),
+            )?;
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for (_, item) in &self.items {
+            let (c, cr) = item.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
+    }
+
+    // Labels are not part of the Pandoc AST, references to them are written as raw markdown
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .map(|(_, item)| pandoc::plain(item.pandoc_blocks(options)))
+            .collect();
+        vec![serde_json::json!({
+            "t": "OrderedList",
+            "c": [[1, {"t": "Example"}, {"t": "TwoParens"}], items]
+        })]
+    }
+}
+
+impl MarkdownWritable for ExampleList<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+
+/// Inline reference to a labeled example in an [ExampleList](struct.ExampleList.html)
+pub struct ExampleRef<'a> {
+    label: Cow<'a, str>,
+}
+
+impl<'a> ExampleRef<'a> {
+    /// Creates a reference to the example with `label`
+    pub fn new<L: Into<Cow<'a, str>>>(label: L) -> Self {
+        let label = label.into();
+        assert_example_label(&label);
+        Self { label }
+    }
+}
+
+impl MarkdownWritable for &'_ ExampleRef<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner, line_prefix, ..
+        } = *context;
+        write!(writer, "(@{})", self.label)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
+    }
+}
+
+impl MarkdownWritable for ExampleRef<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+fn assert_example_label(label: &str) {
+    assert!(
+        !label.is_empty()
+            && label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+        "Example label may contain only alphanumeric characters, '_' and '-'."
+    );
+}
+//endregion
+
+//region Attributes
+/// Pandoc attributes `{#id .class key="value"}`
+#[derive(Clone, Default)]
+pub struct Attributes<'a> {
+    id: Option<Cow<'a, str>>,
+    classes: Vec<Cow<'a, str>>,
+    pairs: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> Attributes<'a> {
+    /// Creates empty attributes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the identifier
+    pub fn id<T: Into<Cow<'a, str>>>(mut self, id: T) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Adds a class
+    pub fn class<T: Into<Cow<'a, str>>>(mut self, class: T) -> Self {
+        self.classes.push(class.into());
+        self
+    }
+
+    /// Adds a `key="value"` pair
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.pairs.push((key.into(), value.into()));
+        self
+    }
+
+    /// Returns `true` if no identifier, class or pair is set
+    pub fn is_empty(&self) -> bool {
+        self.id.is_none() && self.classes.is_empty() && self.pairs.is_empty()
+    }
+
+    /// Returns the identifier
+    pub fn get_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns `true` if `class` was added
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes.iter().any(|c| c == class)
+    }
+
+    /// Returns the value of the pair with `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_ref())
+    }
+
+    fn write_to(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"{")?;
+        let mut separator: &[u8] = b"";
+        if let Some(id) = &self.id {
+            write!(writer, "#{}", id)?;
+            separator = b" ";
+        }
+        for class in &self.classes {
+            writer.write_all(separator)?;
+            write!(writer, ".{}", class)?;
+            separator = b" ";
+        }
+        for (key, value) in &self.pairs {
+            writer.write_all(separator)?;
+            write!(writer, "{}=\"", key)?;
+            write_escaped(
+                writer,
+                value.as_bytes(),
+                EscapeContext::Quoted.escaped(),
+                None,
+            )?;
+            writer.write_all(b"\"")?;
+            separator = b" ";
+        }
+        writer.write_all(b"}")
+    }
+}
+//endregion
+
+//region WithAttributes
+/// Element with attached [Attributes](struct.Attributes.html)
+///
+/// Attributes are not written to markdown, they are only used by backends,
+/// e.g. Pandoc output wraps the element in a `Div` or `Span`.
+pub struct WithAttributes<'a, T: MarkdownWritable> {
+    element: T,
+    attributes: Attributes<'a>,
+}
+
+impl<'a, T: MarkdownWritable> WithAttributes<'a, T> {
+    /// Wraps `element` with empty attributes
+    pub fn new(element: T) -> Self {
+        Self {
+            element,
+            attributes: Attributes::new(),
+        }
+    }
+
+    /// Sets the identifier
+    pub fn id<I: Into<Cow<'a, str>>>(mut self, id: I) -> Self {
+        self.attributes = self.attributes.id(id);
+        self
+    }
+
+    /// Adds a class
+    pub fn class<C: Into<Cow<'a, str>>>(mut self, class: C) -> Self {
+        self.attributes = self.attributes.class(class);
+        self
+    }
+
+    /// Adds a `key="value"` pair
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.attributes = self.attributes.attribute(key, value);
+        self
+    }
+}
+
+impl<T: MarkdownWritable> MarkdownWritable for &'_ WithAttributes<'_, T> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        self.element.write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.element.count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        self.element.headings(options)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+
+    fn preferred_alignment(&self) -> Option<Alignment> {
+        self.element.preferred_alignment()
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        let inlines = self.element.pandoc_inlines(options);
+        vec![serde_json::json!({"t": "Span", "c": [pandoc::attributes(&self.attributes), inlines]})]
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        let blocks = self.element.pandoc_blocks(options);
+        vec![serde_json::json!({"t": "Div", "c": [pandoc::attributes(&self.attributes), blocks]})]
+    }
+}
+
+impl<T: MarkdownWritable> MarkdownWritable for WithAttributes<'_, T> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+
+    fn preferred_alignment(&self) -> Option<Alignment> {
+        (&self).preferred_alignment()
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+//endregion
+
+//region Div
+/// Pandoc fenced div, a block container with [Attributes](struct.Attributes.html)
+pub struct Div<'a> {
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+    attributes: Attributes<'a>,
+}
+
+impl<'a> Div<'a> {
+    /// Creates an empty div
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            attributes: Attributes::new(),
+        }
+    }
+
+    /// Sets the identifier of the div
+    pub fn id<T: Into<Cow<'a, str>>>(mut self, id: T) -> Self {
+        self.attributes = self.attributes.id(id);
+        self
+    }
+
+    /// Adds a class to the div
+    pub fn class<T: Into<Cow<'a, str>>>(mut self, class: T) -> Self {
+        self.attributes = self.attributes.class(class);
+        self
+    }
+
+    /// Adds a `key="value"` attribute to the div
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.attributes = self.attributes.attribute(key, value);
+        self
+    }
+
+    /// Appends a block element to the div
+    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+}
+
+impl Div<'_> {
+    /// Returns the number of colons of the fence, which is longer than fences
+    /// of divs nested inside of it
+    fn fence(&self) -> usize {
+        let nested = self
+            .children
+            .iter()
+            .map(|child| child.count_max_streak(b':', 0).0)
+            .max()
+            .unwrap_or(0);
+        (nested + 1).max(3)
+    }
+}
+
+impl Default for Div<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownWritable for &'_ Div<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let fence = b":".repeat(self.fence());
+        writer.write_all(&fence)?;
+        writer.write_all(b" ")?;
+        self.attributes.write_to(writer)?;
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+        for child in &self.children {
+            child.write_to(
+                writer,
+                &context.nested(Nesting::Div, false, escape, line_prefix, options),
+            )?;
+        }
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+        writer.write_all(&fence)?;
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in &self.children {
+            let (c, cr) = child.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        if char == b':' {
+            count = (count + 1).max(3);
+        }
+        (count, 0)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        self.children
+            .iter()
+            .flat_map(|child| child.headings(options))
+            .collect()
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        let blocks: Vec<serde_json::Value> = self
+            .children
+            .iter()
+            .flat_map(|child| child.pandoc_blocks(options))
+            .collect();
+        vec![serde_json::json!({"t": "Div", "c": [pandoc::attributes(&self.attributes), blocks]})]
+    }
+}
+
+impl MarkdownWritable for Div<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+//endregion
+
+//region Span
+/// Group of inline elements, usable anywhere a single inline element is expected
+///
+/// Without attributes the children are written one after another,
+/// with [Attributes](struct.Attributes.html) the group is written as a Pandoc bracketed span.
+pub struct Span<'a> {
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+    attributes: Attributes<'a>,
+}
+
+impl<'a> Span<'a> {
+    /// Creates an empty span
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            attributes: Attributes::new(),
+        }
+    }
+
+    /// Sets the identifier of the span
+    pub fn id<T: Into<Cow<'a, str>>>(mut self, id: T) -> Self {
+        self.attributes = self.attributes.id(id);
+        self
+    }
+
+    /// Adds a class to the span
+    pub fn class<T: Into<Cow<'a, str>>>(mut self, class: T) -> Self {
+        self.attributes = self.attributes.class(class);
+        self
+    }
+
+    /// Adds a `key="value"` attribute to the span
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.attributes = self.attributes.attribute(key, value);
+        self
+    }
+
+    /// Appends an inline element to the span
+    pub fn append<T: 'a + InlineElement>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+}
+
+impl Default for Span<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownWritable for &'_ Span<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let bracketed = !self.attributes.is_empty();
+        if bracketed {
+            writer.write_all(b"[")?;
+        }
+        for child in &self.children {
+            child.write_to(writer, &context.child(true, escape, line_prefix, options))?;
+        }
+        if bracketed {
+            writer.write_all(b"]")?;
+            self.attributes.write_to(writer)?;
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        // Brackets interrupt streaks, a plain group continues the surrounding text
+        let bracketed = !self.attributes.is_empty();
+        let mut carry = if bracketed { 0 } else { carry };
+        let mut count = 0;
+        for child in &self.children {
+            let (c, cr) = child.count_max_streak(char, carry);
+            count = count.max(c);
+            carry = cr;
+        }
+        if bracketed {
+            (count.max(carry), 0)
+        } else {
+            (count, carry)
+        }
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        let inlines: Vec<serde_json::Value> = self
+            .children
+            .iter()
+            .flat_map(|child| child.pandoc_inlines(options))
+            .collect();
+        if self.attributes.is_empty() {
+            return inlines;
+        }
+        vec![serde_json::json!({"t": "Span", "c": [pandoc::attributes(&self.attributes), inlines]})]
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        vec![serde_json::json!({"t": "Para", "c": self.pandoc_inlines(options)})]
+    }
+}
+
+impl MarkdownWritable for Span<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+//endregion
+
+//region Tuples
+/// Implements [MarkdownWritable](trait.MarkdownWritable.html) for a tuple,
+/// writing its elements one after another like an attribute-less [Span](struct.Span.html)
+macro_rules! impl_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: InlineElement),+> InlineElement for ($($name,)+) {}
+
+        impl<$($name: MarkdownWritable),+> MarkdownWritable for ($($name,)+) {
+            fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+                let RenderContext {
+                    inner,
+                    escape,
+                    line_prefix,
+                    options,
+        ..
+                } = *context;
+                $(self.$index.write_to(writer, &context.child(true, escape, line_prefix, options))?;)+
+                if !inner {
+                    write_line_prefixed(writer, b"\n\n", line_prefix)?;
+                }
+                Ok(())
+            }
+
+            fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+                let mut carry = carry;
+                let mut count = 0;
+                $(
+                    let (c, cr) = self.$index.count_max_streak(char, carry);
+                    count = count.max(c);
+                    carry = cr;
+                )+
+                (count, carry)
+            }
+
+            #[cfg(feature = "pandoc")]
+            fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+                let mut inlines = Vec::new();
+                $(inlines.extend(self.$index.pandoc_inlines(options));)+
+                inlines
+            }
+
+            #[cfg(feature = "pandoc")]
+            fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+                vec![serde_json::json!({"t": "Para", "c": self.pandoc_inlines(options)})]
+            }
+        }
+    };
+}
+
+impl_tuple!(A 0, B 1);
+impl_tuple!(A 0, B 1, C 2);
+impl_tuple!(A 0, B 1, C 2, D 3);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+//endregion
+
+//region Directive
+/// Form of a [Directive](struct.Directive.html)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DirectiveKind {
+    /// Inline directive `:name[content]{attributes}`
+    Text,
+    /// Block directive on a single line `::name[content]{attributes}`
+    Leaf,
+    /// Block directive containing other blocks `:::name[label]{attributes}`
+    Container,
+}
+
+/// Generic directive as used by e.g. remark-directive
+pub struct Directive<'a> {
+    kind: DirectiveKind,
+    name: Cow<'a, str>,
+    label: Vec<Box<dyn 'a + MarkdownWritable>>,
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+    attributes: Attributes<'a>,
+}
+
+impl<'a> Directive<'a> {
+    /// Creates an empty directive
+    ///
+    /// # Arguments
+    /// * `kind` - Form of the directive
+    /// * `name` - Name of the directive, may contain only alphanumeric characters, `_` and `-`
+    pub fn new<N: Into<Cow<'a, str>>>(kind: DirectiveKind, name: N) -> Self {
+        let name = name.into();
+        assert!(
+            name.starts_with(|c: char| c.is_ascii_alphabetic())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+            "Directive name must start with a letter and contain only alphanumeric characters, '_' and '-'."
+        );
+        Self {
+            kind,
+            name,
+            label: Vec::new(),
+            children: Vec::new(),
+            attributes: Attributes::new(),
+        }
+    }
+
+    /// Creates an empty inline directive, which can be appended to a [Paragraph](struct.Paragraph.html)
+    pub fn text<N: Into<Cow<'a, str>>>(name: N) -> TextDirective<'a> {
+        TextDirective(Self::new(DirectiveKind::Text, name))
+    }
+
+    /// Creates an empty leaf block directive
+    pub fn leaf<N: Into<Cow<'a, str>>>(name: N) -> Self {
+        Self::new(DirectiveKind::Leaf, name)
+    }
+
+    /// Creates an empty container block directive
+    pub fn container<N: Into<Cow<'a, str>>>(name: N) -> Self {
+        Self::new(DirectiveKind::Container, name)
+    }
+
+    /// Appends an inline element to the content (label of a container directive) in square brackets
+    pub fn label<T: 'a + InlineElement>(mut self, element: T) -> Self {
+        self.label.push(Box::new(element));
+        self
+    }
+
+    /// Appends a block element to the body of a container directive
+    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
+        assert!(
+            self.kind == DirectiveKind::Container,
+            "Only container directives can contain blocks."
+        );
+        self.children.push(Box::new(element));
+        self
+    }
+
+    /// Sets the identifier of the directive
+    pub fn id<T: Into<Cow<'a, str>>>(mut self, id: T) -> Self {
+        self.attributes = self.attributes.id(id);
+        self
+    }
+
+    /// Adds a class to the directive
+    pub fn class<T: Into<Cow<'a, str>>>(mut self, class: T) -> Self {
+        self.attributes = self.attributes.class(class);
+        self
+    }
+
+    /// Adds a `key="value"` attribute to the directive
+    pub fn attribute<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.attributes = self.attributes.attribute(key, value);
+        self
+    }
+}
+
+impl MarkdownWritable for &'_ Directive<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let marker = match self.kind {
+            DirectiveKind::Text => 1,
+            DirectiveKind::Leaf => 2,
+            DirectiveKind::Container => self.fence(),
+        };
+        writer.write_all(&b":".repeat(marker))?;
+        writer.write_all(self.name.as_bytes())?;
+        if !self.label.is_empty() {
+            writer.write_all(b"[")?;
+            for element in &self.label {
+                element.write_to(writer, &context.child(true, escape, line_prefix, options))?;
+            }
+            writer.write_all(b"]")?;
+        }
+        if !self.attributes.is_empty() {
+            self.attributes.write_to(writer)?;
+        }
+
+        match self.kind {
+            DirectiveKind::Text => {
+                if !inner {
+                    write_line_prefixed(writer, b"\n\n", line_prefix)?;
+                }
+            }
+            DirectiveKind::Leaf => {
+                write_line_prefixed(writer, b"\n", line_prefix)?;
+                if !inner {
+                    write_line_prefixed(writer, b"\n", line_prefix)?;
+                }
+            }
+            DirectiveKind::Container => {
+                write_line_prefixed(writer, b"\n", line_prefix)?;
+                for child in &self.children {
+                    child.write_to(
+                        writer,
+                        &context.nested(Nesting::Div, false, escape, line_prefix, options),
+                    )?;
+                }
+                write_line_prefixed(writer, b"\n", line_prefix)?;
+                writer.write_all(&b":".repeat(marker))?;
+                write_line_prefixed(writer, b"\n", line_prefix)?;
+                if !inner {
+                    write_line_prefixed(writer, b"\n", line_prefix)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in self.label.iter().chain(&self.children) {
+            let (c, cr) = child.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        if char == b':' && self.kind == DirectiveKind::Container {
+            count = count.max(self.fence());
+        }
+        (count, 0)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+}
+
+impl Directive<'_> {
+    /// Returns the number of colons of a container directive, which is longer
+    /// than fences of container directives nested inside of it
+    fn fence(&self) -> usize {
+        let nested = self
+            .children
+            .iter()
+            .map(|child| child.count_max_streak(b':', 0).0)
+            .max()
+            .unwrap_or(0);
+        (nested + 1).max(3)
+    }
+}
+
+impl MarkdownWritable for Directive<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.attributes)
+    }
+}
+
+/// Inline directive `:name[content]{attributes}` created by [Directive::text](struct.Directive.html#method.text)
+pub struct TextDirective<'a>(Directive<'a>);
+
+impl<'a> TextDirective<'a> {
+    /// Appends an inline element to the content in square brackets
+    pub fn label<T: 'a + InlineElement>(self, element: T) -> Self {
+        Self(self.0.label(element))
+    }
+
+    /// Sets the identifier of the directive
+    pub fn id<T: Into<Cow<'a, str>>>(self, id: T) -> Self {
+        Self(self.0.id(id))
+    }
+
+    /// Adds a class to the directive
+    pub fn class<T: Into<Cow<'a, str>>>(self, class: T) -> Self {
+        Self(self.0.class(class))
+    }
+
+    /// Adds a `key="value"` attribute to the directive
+    pub fn attribute<K, V>(self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        Self(self.0.attribute(key, value))
+    }
+}
+
+impl MarkdownWritable for &'_ TextDirective<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        self.0.write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.0.count_max_streak(char, carry)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.0.attributes)
+    }
+}
+
+impl MarkdownWritable for TextDirective<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn attributes(&self) -> Option<&Attributes<'_>> {
+        Some(&self.0.attributes)
+    }
+}
+//endregion
+
+//region Footnote
+/// Reference to a footnote created by [Markdown::footnote](struct.Markdown.html#method.footnote)
+/// or [Document::footnote](struct.Document.html#method.footnote)
+#[derive(Clone, Copy)]
+pub struct FootnoteRef {
+    label: usize,
+}
+
+impl MarkdownWritable for &'_ FootnoteRef {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        match options.flavor {
+            Flavor::CommonMark | Flavor::Gfm => write!(writer, "[^{}]", self.label)?,
+            Flavor::GfmComment => {
+                options.warn(
+                    WarningKind::Degraded,
+                    "footnote written as a superscript link",
+                );
+                write!(writer, "<sup><a href=\"#fn-{0}\">{0}</a></sup>", self.label)?
+            }
+            Flavor::Telegram | Flavor::Discord => {
+                options.warn(WarningKind::Degraded, "footnote written as a superscript");
+                writer.write_all(superscript(self.label).as_bytes())?
+            }
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
+    }
+}
+
+impl MarkdownWritable for FootnoteRef {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+//endregion
+
+//region Summary
+/// Statistical summary of a series of values
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Summary {
+    /// Number of values
+    pub count: usize,
+    /// Minimal value
+    pub min: f64,
+    /// Maximal value
+    pub max: f64,
+    /// Arithmetic mean
+    pub mean: f64,
+    /// Median
+    pub median: f64,
+    /// 95th percentile (nearest-rank)
+    pub p95: f64,
+}
+
+impl Summary {
+    /// Computes summary of `values`
+    ///
+    /// # Returns
+    /// [Summary](struct.Summary.html) or `None` if `values` are empty
+    pub fn from_values<I, T>(values: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<f64>,
+    {
+        let mut values: Vec<f64> = values.into_iter().map(Into::into).collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let count = values.len();
+        let median = if count % 2 == 0 {
+            (values[count / 2 - 1] + values[count / 2]) / 2.0
+        } else {
+            values[count / 2]
+        };
+        let p95_rank = (count * 95).div_ceil(100);
+        Some(Self {
+            count,
+            min: values[0],
+            max: values[count - 1],
+            mean: values.iter().sum::<f64>() / count as f64,
+            median,
+            p95: values[p95_rank - 1],
+        })
+    }
+}
+
+/// Table of [Summaries](struct.Summary.html) with one labeled row per series
+pub struct SummaryTable<'a> {
+    series: Vec<(Cow<'a, str>, Option<Summary>)>,
+    precision: usize,
+    #[cfg(feature = "locale")]
+    locale: Option<locale::Locale>,
+}
+
+impl<'a> SummaryTable<'a> {
+    /// Creates an empty summary table
+    pub fn new() -> Self {
+        Self {
+            series: Vec::new(),
+            precision: 2,
+            #[cfg(feature = "locale")]
+            locale: None,
+        }
+    }
+
+    /// Sets number of decimal places of written values, `2` is used by default
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Formats written values with separators of `locale`
+    ///
+    /// Available with the `locale` feature.
+    #[cfg(feature = "locale")]
+    pub fn locale(mut self, locale: locale::Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    fn format_value(&self, value: f64) -> String {
+        #[cfg(feature = "locale")]
+        {
+            if let Some(locale) = self.locale {
+                return locale::Number::new(value)
+                    .precision(self.precision)
+                    .locale(locale)
+                    .to_string();
+            }
+        }
+        format!("{:.*}", self.precision, value)
+    }
+
+    /// Adds a row summarizing `values`
+    ///
+    /// # Arguments
+    /// * `label` - Name of the series
+    /// * `values` - Values of the series, empty series are written with empty cells
+    pub fn series<L, I, T>(mut self, label: L, values: I) -> Self
+    where
+        L: Into<Cow<'a, str>>,
+        I: IntoIterator<Item = T>,
+        T: Into<f64>,
+    {
+        self.series
+            .push((label.into(), Summary::from_values(values)));
+        self
+    }
+
+    fn table(&self) -> Table<'_> {
+        let mut table = Table::new()
+            .header(vec![
+                "series", "count", "min", "max", "mean", "median", "p95",
+            ])
+            .alignment(vec![
+                Alignment::Left,
+                Alignment::Right,
+                Alignment::Right,
+                Alignment::Right,
+                Alignment::Right,
+                Alignment::Right,
+                Alignment::Right,
+            ]);
+        for (label, summary) in &self.series {
+            let mut row = vec![label.to_string()];
+            if let Some(summary) = summary {
+                row.push(summary.count.to_string());
+                for value in &[
+                    summary.min,
+                    summary.max,
+                    summary.mean,
+                    summary.median,
+                    summary.p95,
+                ] {
+                    row.push(self.format_value(*value));
+                }
+            } else {
+                row.push(String::from("0"));
+            }
+            table = table.row(row);
+        }
+        table
+    }
+}
+
+impl Default for SummaryTable<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownWritable for &'_ SummaryTable<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        self.table().write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.table().count_max_streak(char, carry)
+    }
+}
+
+impl MarkdownWritable for SummaryTable<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+//endregion
+
+//region Bar
+/// Characters used for drawing a [Bar](struct.Bar.html)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BarStyle {
+    /// Unicode blocks, e.g. `▇▇▇▁▁`
+    Unicode,
+    /// Fixed-width ASCII characters, e.g. `[###..]`
+    Ascii,
+}
+
+/// Inline horizontal bar visualizing a fraction, e.g. `▇▇▇▁▁ 60%`
+#[derive(Clone, Copy)]
+pub struct Bar {
+    fraction: f64,
+    width: usize,
+    style: BarStyle,
+    percentage: bool,
+}
+
+impl Bar {
+    /// Creates a bar
+    ///
+    /// # Arguments
+    /// * `fraction` - Filled part of the bar, clamped to range 0-1
+    pub fn new(fraction: f64) -> Self {
+        let fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        Self {
+            fraction,
+            width: 10,
+            style: BarStyle::Unicode,
+            percentage: true,
+        }
+    }
+
+    /// Creates a bar filled in ratio `value` / `max`
+    pub fn from_value(value: f64, max: f64) -> Self {
+        Self::new(if max == 0.0 { 0.0 } else { value / max })
+    }
+
+    /// Sets number of characters of the bar, `10` is used by default
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the characters used for drawing, [BarStyle::Unicode](enum.BarStyle.html#variant.Unicode) is used by default
+    pub fn style(mut self, style: BarStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets if the percentage is written after the bar, `true` by default
+    pub fn percentage(mut self, percentage: bool) -> Self {
+        self.percentage = percentage;
+        self
+    }
+}
+
+impl MarkdownWritable for &'_ Bar {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner, line_prefix, ..
+        } = *context;
+        let filled = (self.fraction * self.width as f64 + 0.5) as usize;
+        let empty = self.width - filled;
+        match self.style {
+            BarStyle::Unicode => {
+                write!(writer, "{}{}", "▇".repeat(filled), "▁".repeat(empty))?;
+            }
+            BarStyle::Ascii => {
+                write!(writer, "[{}{}]", "#".repeat(filled), ".".repeat(empty))?;
+            }
+        }
+        if self.percentage {
+            write!(writer, " {:.0}%", self.fraction * 100.0)?;
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
+    }
+}
+
+impl MarkdownWritable for Bar {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+//endregion
+
+//region Embed
+/// Obsidian embed `![[note#heading]]`, which transcludes another note or its part
+///
+/// The target is written unescaped, so it cannot contain `[`, `]`, `|`, `#`, `^` or line breaks.
+pub struct Embed<'a> {
+    file: Cow<'a, str>,
+    section: Option<(EmbedSection, Cow<'a, str>)>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EmbedSection {
+    Heading,
+    Block,
+}
+
+impl<'a> Embed<'a> {
+    /// Creates an embed of the whole `file`
+    pub fn new<F: Into<Cow<'a, str>>>(file: F) -> Self {
+        let file = file.into();
+        assert_embed_target(&file, "^#");
+        Self {
+            file,
+            section: None,
+        }
+    }
+
+    /// Embeds only the section under `heading`
+    pub fn heading<H: Into<Cow<'a, str>>>(mut self, heading: H) -> Self {
+        let heading = heading.into();
+        assert_embed_target(&heading, "");
+        self.section = Some((EmbedSection::Heading, heading));
+        self
+    }
+
+    /// Embeds only the block with identifier `id`, written as `^id`
+    pub fn block<I: Into<Cow<'a, str>>>(mut self, id: I) -> Self {
+        let id = id.into();
+        assert!(
+            !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'),
+            "Block identifier may contain only alphanumeric characters and '-'."
+        );
+        self.section = Some((EmbedSection::Block, id));
+        self
+    }
+
+    fn target(&self) -> String {
+        match &self.section {
+            None => self.file.to_string(),
+            Some((EmbedSection::Heading, heading)) => format!("{}#{}", self.file, heading),
+            Some((EmbedSection::Block, id)) => format!("{}#^{}", self.file, id),
+        }
+    }
+}
+
+impl MarkdownWritable for &'_ Embed<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner, line_prefix, ..
+        } = *context;
+        write!(writer, "![[{}]]", self.target())?;
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.target().as_str().count_max_streak(char, carry)
+    }
+}
+
+impl MarkdownWritable for Embed<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+fn assert_embed_target(target: &str, forbidden: &str) {
+    assert!(
+        !target.is_empty()
+            && !target
+                .chars()
+                .any(|c| "[]|\n".contains(c) || forbidden.contains(c)),
+        "Embed target cannot be empty or contain brackets, '|' or line breaks."
+    );
+}
+//endregion
+
+//region Logseq
+/// Task keyword at the start of a [LogseqBlock](struct.LogseqBlock.html)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TaskKeyword {
+    /// `TODO`
+    Todo,
+    /// `DOING`
+    Doing,
+    /// `DONE`
+    Done,
+}
+
+impl TaskKeyword {
+    fn as_str(self) -> &'static str {
+        match self {
+            TaskKeyword::Todo => "TODO",
+            TaskKeyword::Doing => "DOING",
+            TaskKeyword::Done => "DONE",
+        }
+    }
+}
+
+/// Logseq block with an optional task keyword and `key:: value` block properties
+///
+/// Logseq pages are outlines, so blocks are meant to be items of a [List](struct.List.html),
+/// properties are written on the lines following the content.
+/// The keyword and properties are written unescaped.
+pub struct LogseqBlock<'a> {
+    task: Option<TaskKeyword>,
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+    properties: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+impl<'a> LogseqBlock<'a> {
+    /// Creates an empty block
+    pub fn new() -> Self {
+        Self {
+            task: None,
+            children: Vec::new(),
+            properties: Vec::new(),
+        }
+    }
+
+    /// Marks the block as a task, e.g. `TODO Buy milk`
+    pub fn task(mut self, keyword: TaskKeyword) -> Self {
+        self.task = Some(keyword);
+        self
+    }
+
+    /// Appends an element to the content of the block
+    pub fn append<T: 'a + InlineElement>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+
+    /// Adds a `key:: value` block property
+    ///
+    /// # Panics
+    /// If `key` is empty or contains whitespace or `:`, or `value` contains a line break
+    pub fn property<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        let (key, value) = (key.into(), value.into());
+        assert!(
+            !key.is_empty() && !key.contains(|c: char| c.is_whitespace() || c == ':'),
+            "Property key cannot be empty or contain whitespace or ':'."
+        );
+        assert!(
+            !value.contains('\n'),
+            "Property value cannot contain line breaks."
+        );
+        self.properties.push((key, value));
+        self
+    }
+}
+
+impl Default for LogseqBlock<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownWritable for &'_ LogseqBlock<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        if let Some(task) = self.task {
+            writer.write_all(task.as_str().as_bytes())?;
+            if !self.children.is_empty() {
+                writer.write_all(b" ")?;
+            }
+        }
+        for child in &self.children {
+            child.write_to(writer, &context.child(true, escape, line_prefix, options))?;
+        }
+        for (key, value) in &self.properties {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+            write!(writer, "{}:: {}", key, value)?;
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in &self.children {
+            count = count.max(child.count_max_streak(char, 0).0);
+        }
+        (count, 0)
+    }
+}
+
+impl MarkdownWritable for LogseqBlock<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+//endregion
+
+//region Toc
+/// Table of contents, a nested list of links to headings
+///
+/// Created by [Document::toc](struct.Document.html#method.toc)
+/// or written by [Markdown::write_toc](struct.Markdown.html#method.write_toc).
+#[derive(Clone)]
+pub struct Toc {
+    entries: Vec<(usize, String, Option<String>)>,
+}
+
+impl MarkdownWritable for &'_ Toc {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        let mut slugs: BTreeMap<String, usize> = BTreeMap::new();
+        // Levels of the entries enclosing the current one
+        let mut parents: Vec<usize> = Vec::new();
+        for (level, text, id) in &self.entries {
+            // Without attribute support, headings with an identifier still get a generated anchor
+            let slug = match id {
+                Some(_) if options.flavor.heading_attributes() => None,
+                _ => {
+                    let slug = slugify(&plain_text(text));
+                    Some(numbered(slugs.entry(slug.clone()).or_insert(0), &slug))
+                }
+            };
+            let slug = id.clone().or(slug).unwrap_or_default();
+            while parents.last().is_some_and(|parent| parent >= level) {
+                parents.pop();
+            }
+            for _ in 0..parents.len() {
+                writer.write_all(b"  ")?;
+            }
+            parents.push(*level);
+            write!(writer, "- [{}](#{})", text, slug)?;
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, _char: u8, _carry: usize) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+impl MarkdownWritable for Toc {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+/// Strips escaping and code span delimiters from markdown `text` written by this crate
+fn plain_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_code = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if !in_code => {
+                if let Some(next) = chars.next() {
+                    plain.push(next);
+                }
+            }
+            '`' => {
+                while chars.peek() == Some(&'`') {
+                    chars.next();
+                }
+                if in_code {
+                    if plain.ends_with(' ') {
+                        plain.pop();
+                    }
+                } else if chars.peek() == Some(&' ') {
+                    chars.next();
+                }
+                in_code = !in_code;
+            }
+            _ => plain.push(c),
+        }
+    }
+    plain
+}
+//endregion
+
+//region Statistics
+/// Summary of a document written as a `Field | Value` table
+///
+/// Created by [Document::statistics](struct.Document.html#method.statistics),
+/// the figures are counted in the rendered markdown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// Number of headings
+    pub sections: usize,
+    /// Number of words, including words in code blocks
+    pub words: usize,
+    /// Number of tables
+    pub tables: usize,
+    /// Number of links, excluding images
+    pub links: usize,
+}
+
+impl Statistics {
+    /// Counts words, tables and links in `markdown` written by this crate
+    fn count(markdown: &str, sections: usize) -> Self {
+        let mut statistics = Statistics {
+            sections,
+            ..Statistics::default()
+        };
+        // Opening fence of the current code block, closed by a run at least as long
+        let mut fence: Option<&str> = None;
+        for line in markdown.lines() {
+            let trimmed = line.trim_start();
+            match fence {
+                Some(marker)
+                    if trimmed.starts_with(marker)
+                        && trimmed
+                            .trim_end()
+                            .bytes()
+                            .all(|c| c == marker.as_bytes()[0]) =>
+                {
+                    fence = None
+                }
+                Some(_) => {}
+                None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                    let marker = trimmed.as_bytes()[0];
+                    let len = trimmed.bytes().take_while(|c| *c == marker).count();
+                    fence = Some(&trimmed[..len]);
+                    continue;
+                }
+                None => {
+                    if is_table_delimiter(trimmed) {
+                        statistics.tables += 1;
+                        continue;
+                    }
+                    statistics.links += count_links(line);
+                }
+            }
+            statistics.words += line
+                .split_whitespace()
+                .filter(|word| word.chars().any(char::is_alphanumeric))
+                .count();
+        }
+        statistics
+    }
+}
+
+/// Returns `true` for the line separating the header of a pipe table or a grid table
+fn is_table_delimiter(line: &str) -> bool {
+    let line = line.trim_end();
+    (line.starts_with('|') && line.contains('-') && line.chars().all(|c| "|:- ".contains(c)))
+        || (line.starts_with("+=") && line.chars().all(|c| "+=:".contains(c)))
+}
+
+/// Counts links `[text](address)`, `[text][label]` and `<address>` on a line
+fn count_links(line: &str) -> usize {
+    let bytes = line.as_bytes();
+    let mut count = 0;
+    let mut open = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'[' => open = Some(i),
+            b']' if matches!(bytes.get(i + 1), Some(b'(') | Some(b'[')) => {
+                if let Some(start) = open.take() {
+                    if start == 0 || bytes[start - 1] != b'!' {
+                        count += 1;
+                    }
+                }
+            }
+            b'<' if line[i + 1..].starts_with("http") => count += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    count
+}
+
+impl ToMarkdown for Statistics {
+    fn to_markdown(&self) -> FieldList {
+        FieldList::table()
+            .field("Sections", self.sections.to_string())
+            .field("Words", self.words.to_string())
+            .field("Tables", self.tables.to_string())
+            .field("Links", self.links.to_string())
+    }
+}
+
+impl MarkdownWritable for &'_ Statistics {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        self.to_markdown().write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, _char: u8, _carry: usize) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+impl MarkdownWritable for Statistics {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+//endregion
+
+//region DocumentDiff
+/// Change of a single section found by [DocumentDiff](struct.DocumentDiff.html)
+///
+/// Sections start at headings, `title` is the plain text of the heading
+/// or `None` for content before the first heading.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SectionChange {
+    /// Section present only in the new document, with its new content
+    Added {
+        title: Option<String>,
+        content: String,
+    },
+    /// Section present only in the old document, with its old content
+    Removed {
+        title: Option<String>,
+        content: String,
+    },
+    /// Section present in both documents with different content
+    Changed {
+        title: Option<String>,
+        old: String,
+        new: String,
+    },
+}
+
+/// Summary of sections changed between two rendered documents
+///
+/// Every change is written as a bold label and the section title
+/// followed by the new content (old content for removed sections) in a block quote.
+pub struct DocumentDiff {
+    changes: Vec<SectionChange>,
+}
+
+impl DocumentDiff {
+    /// Compares two rendered markdown documents
+    ///
+    /// Sections are matched by their titles, repeated titles are matched in order.
+    pub fn new(old: &str, new: &str) -> Self {
+        let mut old = split_sections(old)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        let mut changes = Vec::new();
+        for (title, content) in split_sections(new) {
+            let matching = old
+                .iter_mut()
+                .find(|section| matches!(section, Some((t, _)) if *t == title));
+            match matching.and_then(Option::take) {
+                Some((_, old_content)) if old_content == content => {}
+                Some((_, old_content)) => changes.push(SectionChange::Changed {
+                    title,
+                    old: old_content,
+                    new: content,
+                }),
+                None => changes.push(SectionChange::Added { title, content }),
+            }
+        }
+        changes.extend(
+            old.into_iter()
+                .flatten()
+                .map(|(title, content)| SectionChange::Removed { title, content }),
+        );
+        Self { changes }
+    }
+
+    /// Renders two documents and compares the output, see [new](#method.new)
+    ///
+    /// # Returns
+    /// [DocumentDiff](struct.DocumentDiff.html) or `std::io::Error` if an element failed to render
+    pub fn between(old: &Document, new: &Document) -> Result<Self, io::Error> {
+        let old = old.render(Vec::new())?;
+        let new = new.render(Vec::new())?;
+        Ok(Self::new(
+            &String::from_utf8_lossy(&old),
+            &String::from_utf8_lossy(&new),
+        ))
+    }
+
+    /// Returns the changed sections, removed sections are last
+    pub fn changes(&self) -> &[SectionChange] {
+        &self.changes
+    }
+
+    /// Returns whether the documents have the same sections
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Splits rendered markdown at ATX headings outside of code fences
+fn split_sections(markdown: &str) -> Vec<(Option<String>, String)> {
+    let mut sections = vec![(None, String::new())];
+    let mut fence: Option<&str> = None;
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if let Some(open) = fence {
+            if trimmed.starts_with(open)
+                && trimmed
+                    .trim_start_matches(open.as_bytes()[0] as char)
+                    .is_empty()
+            {
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let c = trimmed.as_bytes()[0] as char;
+            fence = Some(&trimmed[..trimmed.len() - trimmed.trim_start_matches(c).len()]);
+        } else {
+            let level = trimmed.bytes().take_while(|&b| b == b'#').count();
+            if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+                let title = plain_text(trimmed[level..].trim());
+                sections.push((Some(title), String::new()));
+                continue;
+            }
+        }
+        sections.last_mut().unwrap().1.push_str(line);
+    }
+    for section in &mut sections {
+        section.1 = section.1.trim_matches('\n').to_string();
+    }
+    if sections[0].1.is_empty() {
+        sections.remove(0);
+    }
+    sections
+}
+
+impl MarkdownWritable for &'_ DocumentDiff {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        for change in &self.changes {
+            let (label, title, content) = match change {
+                SectionChange::Added { title, content } => ("Added", title, content),
+                SectionChange::Removed { title, content } => ("Removed", title, content),
+                SectionChange::Changed { title, new, .. } => ("Changed", title, new),
+            };
+            write!(writer, "**{}:** ", label)?;
+            match title {
+                Some(title) => title
+                    .as_str()
+                    .write_to(writer, &context.child(true, escape, line_prefix, options))?,
+                None => writer.write_all(b"*Before the first heading*")?,
+            }
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+            if !content.is_empty() {
+                Blockquote::new()
+                    .append(Raw::new(content.as_str()))
+                    .write_to(writer, &context.child(false, escape, line_prefix, options))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, _char: u8, _carry: usize) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+impl MarkdownWritable for DocumentDiff {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+//endregion
+
+//region CodeBlock
+/// Fenced code block
+///
+/// The fence is made longer than any streak of backticks in the code,
+/// so the code is written without escaping.
+pub struct CodeBlock<'a> {
+    code: Cow<'a, str>,
+    language: Option<Cow<'a, str>>,
+    chunk: Option<Chunk<'a>>,
+}
+
+struct Chunk<'a> {
+    label: Option<Cow<'a, str>>,
+    options: Vec<(Cow<'a, str>, ChunkOption)>,
+}
+
+/// Value of an R Markdown / Quarto chunk option, written in R syntax
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChunkOption {
+    /// `TRUE` or `FALSE`
+    Bool(bool),
+    /// Integer value, e.g. `3`
+    Integer(i64),
+    /// Decimal value, e.g. `7.5`
+    Number(f64),
+    /// Quoted string, e.g. `"center"`
+    Text(String),
+}
+
+impl From<bool> for ChunkOption {
+    fn from(value: bool) -> Self {
+        ChunkOption::Bool(value)
+    }
+}
+
+impl From<i32> for ChunkOption {
+    fn from(value: i32) -> Self {
+        ChunkOption::Integer(value.into())
+    }
+}
+
+impl From<i64> for ChunkOption {
+    fn from(value: i64) -> Self {
+        ChunkOption::Integer(value)
+    }
+}
+
+impl From<f64> for ChunkOption {
+    fn from(value: f64) -> Self {
+        ChunkOption::Number(value)
+    }
+}
+
+impl From<&str> for ChunkOption {
+    fn from(value: &str) -> Self {
+        ChunkOption::Text(value.to_string())
+    }
+}
+
+impl From<String> for ChunkOption {
+    fn from(value: String) -> Self {
+        ChunkOption::Text(value)
+    }
+}
+
+impl fmt::Display for ChunkOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkOption::Bool(true) => write!(f, "TRUE"),
+            ChunkOption::Bool(false) => write!(f, "FALSE"),
+            ChunkOption::Integer(value) => write!(f, "{}", value),
+            ChunkOption::Number(value) => write!(f, "{}", value),
+            ChunkOption::Text(value) => {
+                write!(
+                    f,
+                    "\"{}\"",
+                    value.replace('\\', "\\\\").replace('"', "\\\"")
+                )
+            }
+        }
+    }
+}
+
+impl<'a> CodeBlock<'a> {
+    /// Creates a code block without a language
+    pub fn new<C: Into<Cow<'a, str>>>(code: C) -> Self {
+        Self {
+            code: code.into(),
+            language: None,
+            chunk: None,
+        }
+    }
+
+    /// Creates a Mermaid diagram rendered by GitHub, GitLab and others, the source is written as is
+    pub fn mermaid<C: Into<Cow<'a, str>>>(source: C) -> Self {
+        Self::new(source).language("mermaid")
+    }
+
+    /// Creates a PlantUML diagram, the source is written as is
+    pub fn plantuml<C: Into<Cow<'a, str>>>(source: C) -> Self {
+        Self::new(source).language("plantuml")
+    }
+
+    /// Creates an executable R Markdown / Quarto chunk, e.g. ```` ```{r label, echo=FALSE} ````
+    ///
+    /// # Arguments
+    /// * `engine` - Language engine of the chunk, e.g. `r` or `python`
+    /// * `code` - Source code of the chunk
+    pub fn chunk<E, C>(engine: E, code: C) -> Self
+    where
+        E: Into<Cow<'a, str>>,
+        C: Into<Cow<'a, str>>,
+    {
+        Self {
+            code: code.into(),
+            language: Some(engine.into()),
+            chunk: Some(Chunk {
+                label: None,
+                options: Vec::new(),
+            }),
+        }
+    }
+
+    /// Sets the language used for syntax highlighting or the chunk engine
+    pub fn language<L: Into<Cow<'a, str>>>(mut self, language: L) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets the label of the chunk
+    ///
+    /// # Panics
+    /// Panics if the block was not created by [chunk](#method.chunk)
+    pub fn label<L: Into<Cow<'a, str>>>(mut self, label: L) -> Self {
+        self.chunk_mut().label = Some(label.into());
+        self
+    }
+
+    /// Adds a `key=value` option of the chunk, the option is written unescaped
+    ///
+    /// # Panics
+    /// Panics if the block was not created by [chunk](#method.chunk)
+    pub fn option<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<Cow<'a, str>>,
+        V: Into<ChunkOption>,
+    {
+        self.chunk_mut().options.push((key.into(), value.into()));
+        self
+    }
+
+    fn chunk_mut(&mut self) -> &mut Chunk<'a> {
+        self.chunk
+            .as_mut()
+            .expect("Only chunks can have a label and options.")
+    }
+}
+
+impl MarkdownWritable for &'_ CodeBlock<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let (count, _) = self.count_max_streak(b'`', 0);
+        let fence = "`".repeat(count.max(2) + 1);
+        writer.write_all(fence.as_bytes())?;
+        match (&self.chunk, &self.language) {
+            (Some(chunk), Some(engine)) => {
+                write!(writer, "{{{}", engine)?;
+                if let Some(label) = &chunk.label {
+                    write!(writer, " {}", label)?;
+                }
+                for (i, (key, value)) in chunk.options.iter().enumerate() {
+                    let separator = if i == 0 && chunk.label.is_none() {
+                        " "
+                    } else {
+                        ", "
+                    };
+                    write!(writer, "{}{}={}", separator, key, value)?;
+                }
+                writer.write_all(b"}")?;
+            }
+            (None, Some(language)) => writer.write_all(language.as_bytes())?,
+            _ => {}
+        }
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+        let code = options.sanitize(Cow::Borrowed(&self.code));
+        let code = code.strip_suffix('\n').unwrap_or(&code);
+        if !code.is_empty() {
+            write_line_prefixed(writer, code.as_bytes(), line_prefix)?;
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        writer.write_all(fence.as_bytes())?;
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let (count, carry) = self.code.count_max_streak(char, 0);
+        (count.max(carry), 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        if self.chunk.is_some() {
+            return pandoc::raw(self, false, options);
+        }
+        let classes: Vec<&str> = self.language.as_deref().into_iter().collect();
+        let code = self.code.strip_suffix('\n').unwrap_or(&self.code);
+        vec![serde_json::json!({"t": "CodeBlock", "c": [pandoc::attr(&classes), code]})]
+    }
+}
+
+impl MarkdownWritable for CodeBlock<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+//endregion
+
+//region Blockquote
+/// Block quote containing arbitrary blocks, e.g. paragraphs, lists or code blocks
+///
+/// Every line written by the children is prefixed with `> `.
+pub struct Blockquote<'a> {
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+    attribution: Vec<Box<dyn 'a + MarkdownWritable>>,
+}
+
+impl<'a> Blockquote<'a> {
+    /// Creates an empty block quote
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            attribution: Vec::new(),
+        }
+    }
+
+    /// Appends a block element to the block quote
+    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+
+    /// Appends an inline element to the attribution, e.g. the author or a [Citation](struct.Citation.html)
+    ///
+    /// The attribution is written as the last paragraph of the quote, `> — Author`.
+    pub fn attribution<T: 'a + InlineElement>(mut self, element: T) -> Self {
+        self.attribution.push(Box::new(element));
+        self
+    }
+}
+
+impl Default for Blockquote<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownWritable for &'_ Blockquote<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        write_prefixed_blocks(
+            writer,
+            b"> ",
+            b"",
+            &self.children,
+            context,
+            Some(Nesting::Quote),
+        )?;
+        if !self.attribution.is_empty() {
+            let prefix = [line_prefix.unwrap_or_default(), b"> "].concat();
+            write_line_prefixed(writer, ">\n> — ".as_bytes(), line_prefix)?;
+            for element in &self.attribution {
+                element.write_to(writer, &context.child(true, escape, Some(&prefix), options))?;
+            }
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in self.children.iter().chain(&self.attribution) {
+            let (c, cr) = child.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        self.children
+            .iter()
+            .flat_map(|child| child.headings(options))
+            .collect()
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        let mut blocks: Vec<serde_json::Value> = self
+            .children
+            .iter()
+            .flat_map(|child| child.pandoc_blocks(options))
+            .collect();
+        if !self.attribution.is_empty() {
+            let mut inlines = vec![
+                serde_json::json!({"t": "Str", "c": "—"}),
+                serde_json::json!({"t": "Space"}),
+            ];
+            for element in &self.attribution {
+                inlines.extend(element.pandoc_inlines(options));
+            }
+            blocks.push(serde_json::json!({"t": "Para", "c": inlines}));
+        }
+        vec![serde_json::json!({"t": "BlockQuote", "c": blocks})]
+    }
+}
+
+impl MarkdownWritable for Blockquote<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
+}
+//endregion
+
+//region Citation
+/// Title of a cited work, e.g. a book or an interview, written as `<cite>title</cite>`
+///
+/// Telegram and Discord do not render HTML, so the title is written in italics there.
+pub struct Citation<'a> {
+    title: Cow<'a, str>,
+}
+
+impl<'a> Citation<'a> {
+    /// Creates a citation of the work titled `title`
+    pub fn new<T: Into<Cow<'a, str>>>(title: T) -> Self {
+        Self {
+            title: title.into(),
+        }
+    }
+}
+
+impl MarkdownWritable for &'_ Citation<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        if matches!(options.flavor, Flavor::Telegram | Flavor::Discord) {
+            options.warn(WarningKind::Degraded, "citation written as italic text");
+            return RichText::new(self.title.as_ref())
+                .italic()
+                .write_to(writer, context);
+        }
+        let title = escape_html(&options.transform(&self.title)).replace('\n', " ");
+        write!(writer, "<cite>{}</cite>", title)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        RichText::new(self.title.as_ref())
+            .italic()
+            .pandoc_inlines(options)
+    }
+}
+
+impl MarkdownWritable for Citation<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
+    }
+}
+//endregion
+
+//region Alert
+/// Kind of an [Alert](struct.Alert.html)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AlertKind {
+    /// `[!NOTE]`
+    Note,
+    /// `[!TIP]`
+    Tip,
+    /// `[!IMPORTANT]`
+    Important,
+    /// `[!WARNING]`
+    Warning,
+    /// `[!CAUTION]`
+    Caution,
+}
+
+/// GitHub alert, a block quote highlighted according to its kind
+pub struct Alert<'a> {
+    kind: AlertKind,
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+}
+
+impl<'a> Alert<'a> {
+    /// Creates an empty alert of `kind`
+    pub fn new(kind: AlertKind) -> Self {
+        Self {
+            kind,
+            children: Vec::new(),
+        }
+    }
+
+    /// Appends a block element to the alert
+    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+
+    /// Creates a green [Tip](enum.AlertKind.html#variant.Tip) with the value of `Ok`,
+    /// or a red [Caution](enum.AlertKind.html#variant.Caution) with the error
+    /// followed by a list of its sources
+    #[cfg(feature = "std")]
+    pub fn from_result<T: fmt::Display, E: std::error::Error>(result: &Result<T, E>) -> Self {
+        let error = match result {
+            Ok(value) => {
+                return Self::new(AlertKind::Tip).append(Paragraph::new().append(value.to_string()))
+            }
+            Err(error) => error,
+        };
+        let alert =
+            Self::new(AlertKind::Caution).append(Paragraph::new().append(error.to_string()));
+        let mut sources = Vec::new();
+        let mut source = error.source();
+        while let Some(error) = source {
+            sources.push(error.to_string());
+            source = error.source();
+        }
+        if sources.is_empty() {
+            return alert;
+        }
+        alert.append(List::from_iter(sources))
+    }
+}
+
+impl MarkdownWritable for &'_ Alert<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner, line_prefix, ..
+        } = *context;
+        let header: &[u8] = match self.kind {
+            AlertKind::Note => b"[!NOTE]\n",
+            AlertKind::Tip => b"[!TIP]\n",
+            AlertKind::Important => b"[!IMPORTANT]\n",
+            AlertKind::Warning => b"[!WARNING]\n",
+            AlertKind::Caution => b"[!CAUTION]\n",
+        };
+        write_prefixed_blocks(
+            writer,
+            b"> ",
+            header,
+            &self.children,
+            context,
+            Some(Nesting::Quote),
+        )?;
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in &self.children {
+            let (c, cr) = child.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        self.children
+            .iter()
+            .flat_map(|child| child.headings(options))
+            .collect()
+    }
+}
+
+impl MarkdownWritable for Alert<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
+}
+//endregion
+
+//region Admonition
+/// MkDocs admonition `!!! note "Title"` with an indented body
+pub struct Admonition<'a> {
+    kind: Cow<'a, str>,
+    title: Option<Cow<'a, str>>,
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+}
+
+impl<'a> Admonition<'a> {
+    /// Creates an empty admonition
+    ///
+    /// # Arguments
+    /// * `kind` - Type of the admonition, e.g. `note`, `tip`, `warning` or a custom type
+    pub fn new<K: Into<Cow<'a, str>>>(kind: K) -> Self {
+        Self {
+            kind: kind.into(),
+            title: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Sets the title written instead of the type, an empty title hides the title bar
+    pub fn title<T: Into<Cow<'a, str>>>(mut self, title: T) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Appends a block element to the admonition
+    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+}
+
+impl MarkdownWritable for &'_ Admonition<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        write!(writer, "!!! {}", self.kind)?;
+        if let Some(title) = &self.title {
+            write!(
+                writer,
+                " \"{}\"",
+                options.transform(title).replace('"', "&quot;")
+            )?;
+        }
+        write_line_prefixed(writer, b"\n", line_prefix)?;
+        if !self.children.is_empty() {
+            write_prefixed_blocks(
+                writer,
+                b"    ",
+                b"",
+                &self.children,
+                context,
+                Some(Nesting::Admonition),
+            )?;
+        }
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
+
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in &self.children {
+            let (c, cr) = child.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        self.children
+            .iter()
+            .flat_map(|child| child.headings(options))
+            .collect()
+    }
+}
+
+impl MarkdownWritable for Admonition<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
+}
+//endregion
+
+//region Collapsible
+/// Collapsible section `<details><summary>…</summary>…</details>`
+///
+/// The content is separated by blank lines, so GitHub renders it as markdown.
+pub struct Collapsible<'a> {
+    summary: Cow<'a, str>,
+    open: bool,
+    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+}
+
+impl<'a> Collapsible<'a> {
+    /// Creates an empty collapsible section
+    ///
+    /// # Arguments
+    /// * `summary` - Text which is always visible, it is written as HTML
+    pub fn new<S: Into<Cow<'a, str>>>(summary: S) -> Self {
+        Self {
+            summary: summary.into(),
+            open: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// Sets whether the section is expanded initially
+    pub fn open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+
+    /// Appends a block element to the section
+    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
+        self.children.push(Box::new(element));
+        self
+    }
+}
+
+impl MarkdownWritable for &'_ Collapsible<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let header = format!(
+            "<details{}>\n<summary>{}</summary>\n\n",
+            if self.open { " open" } else { "" },
+            escape_html(&options.transform(&self.summary))
+        );
+        write_prefixed_blocks(
+            writer,
+            b"",
+            header.as_bytes(),
+            &self.children,
+            context,
+            Some(Nesting::Collapsible),
+        )?;
+        write_line_prefixed(writer, b"\n</details>\n", line_prefix)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
+    }
 
-    /// Converts `self` to **bold** [RichText](struct.RichText.html)
-    fn bold(self) -> RichText<'a>;
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for child in &self.children {
+            let (c, cr) = child.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
+    }
 
-    /// Converts `self` to *italic* [RichText](struct.RichText.html)
-    fn italic(self) -> RichText<'a>;
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        self.children
+            .iter()
+            .flat_map(|child| child.headings(options))
+            .collect()
+    }
+}
 
-    /// Converts `self` to `code` [RichText](struct.RichText.html)
-    fn code(self) -> RichText<'a>;
+impl MarkdownWritable for Collapsible<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
 
-    /// Converts `self` to [Quote](struct.Quote.html)
-    fn quote(self) -> Quote<'a>;
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
 }
+//endregion
 
-//region Paragraph
-/// Markdown paragraph
-pub struct Paragraph<'a> {
-    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+//region Faq
+/// List of questions with answers
+///
+/// Every question is written as a [Collapsible](struct.Collapsible.html) section,
+/// or as a heading followed by the answer in flavors not rendering HTML (Telegram and Discord).
+pub struct Faq<'a> {
+    level: usize,
+    entries: Vec<Collapsible<'a>>,
 }
 
-impl<'a> Paragraph<'a> {
-    /// Creates an empty paragraph
+impl<'a> Faq<'a> {
+    /// Creates an empty list, questions written as headings are of level 3
     pub fn new() -> Self {
         Self {
-            children: Vec::new(),
+            level: 3,
+            entries: Vec::new(),
         }
     }
 
-    /// Appends an element to the paragraph
-    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
-        self.children.push(Box::new(element));
+    /// Sets the level of questions written as headings
+    ///
+    /// # Panics
+    /// Panics if `level` is not in range 1-6
+    pub fn heading_level(mut self, level: usize) -> Self {
+        assert!(level > 0 && level <= 6, "Heading level must be range 1-6.");
+        self.level = level;
+        self
+    }
+
+    /// Appends a question with a block element answering it
+    pub fn entry<Q, A>(mut self, question: Q, answer: A) -> Self
+    where
+        Q: Into<Cow<'a, str>>,
+        A: 'a + MarkdownWritable,
+    {
+        self.entries.push(Collapsible::new(question).append(answer));
         self
     }
+
+    /// Returns `true` if questions are written as headings
+    fn as_headings(options: &Options) -> bool {
+        matches!(options.flavor, Flavor::Telegram | Flavor::Discord)
+    }
 }
 
-impl MarkdownWritable for &'_ Paragraph<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        for child in &self.children {
-            child.write_to(writer, true, escape, line_prefix)?;
+impl Default for Faq<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownWritable for &'_ Faq<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        if Faq::as_headings(options) && !self.entries.is_empty() {
+            options.warn(WarningKind::Degraded, "FAQ written as headings");
         }
-        if !inner {
-            write_line_prefixed(writer, b"\n\n", line_prefix)?;
+        for (i, entry) in self.entries.iter().enumerate() {
+            if Faq::as_headings(options) {
+                Heading::new(self.level)
+                    .append(entry.summary.as_ref())
+                    .write_to(writer, &context.child(false, escape, line_prefix, options))?;
+                for answer in &entry.children {
+                    answer.write_to(writer, &context.child(false, escape, line_prefix, options))?;
+                }
+                continue;
+            }
+            let last = i + 1 == self.entries.len();
+            entry.write_to(
+                writer,
+                &context.child(inner && last, escape, line_prefix, options),
+            )?;
         }
         Ok(())
     }
 
-    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
-        let mut carry = carry;
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
         let mut count = 0;
-        for child in &self.children {
-            let (c, cr) = child.count_max_streak(char, carry);
-            count += c;
-            carry = cr;
+        for entry in &self.entries {
+            let (c, cr) = entry.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
         }
-        count += carry;
         (count, 0)
     }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        let mut headings = Vec::new();
+        for entry in &self.entries {
+            if Faq::as_headings(options) {
+                headings.extend(
+                    Heading::new(self.level)
+                        .append(entry.summary.as_ref())
+                        .headings(options),
+                );
+            }
+            headings.extend(entry.headings(options));
+        }
+        headings
+    }
 }
 
-impl MarkdownWritable for Paragraph<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        (&self).write_to(writer, inner, escape, line_prefix)
+impl MarkdownWritable for Faq<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
     fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
         (&self).count_max_streak(char, carry)
     }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
 }
 //endregion
 
-//region Heading
-/// Markdown heading
-pub struct Heading<'a> {
+//region Html
+/// Raw HTML block, e.g. a table with merged cells or a `<video>`
+///
+/// The HTML is written verbatim and followed by a blank line. Blank lines inside the HTML
+/// are removed, as they would end the HTML block, except in `<pre>`, `<script>`,
+/// `<style>` and `<textarea>` blocks. Markdown [appended](#method.append) to an
+/// [element](#method.element) is separated from the tags by blank lines, so it is rendered.
+pub struct Html<'a> {
+    html: Cow<'a, str>,
+    close: Option<Cow<'a, str>>,
     children: Vec<Box<dyn 'a + MarkdownWritable>>,
-    level: usize,
 }
 
-impl<'a> Heading<'a> {
-    /// Creates an empty heading
-    ///
-    /// # Arguments
-    /// * `level` - Heading level (1-6)
-    pub fn new(level: usize) -> Self {
-        assert!(level > 0 && level <= 6, "Heading level must be range 1-6.");
+impl<'a> Html<'a> {
+    /// Creates an HTML block
+    pub fn new<S: Into<Cow<'a, str>>>(html: S) -> Self {
         Self {
+            html: html.into(),
+            close: None,
             children: Vec::new(),
-            level,
         }
     }
 
-    /// Appends an element to the heading
+    /// Creates an HTML element with markdown content, e.g. `<div align="center">` and `</div>`
+    pub fn element<S: Into<Cow<'a, str>>, C: Into<Cow<'a, str>>>(open: S, close: C) -> Self {
+        Self {
+            html: open.into(),
+            close: Some(close.into()),
+            children: Vec::new(),
+        }
+    }
+
+    /// Appends a block element written between the tags of an [element](#method.element)
     pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
         self.children.push(Box::new(element));
         self
     }
 }
 
-impl MarkdownWritable for &'_ Heading<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        _escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        assert!(!inner, "Inner headings are forbidden.");
-        let mut prefix = Vec::new();
-        prefix.resize(self.level, b'#');
-        prefix.push(b' ');
-        writer.write_all(&prefix)?;
-        for child in &self.children {
-            child.write_to(writer, true, Normal, line_prefix)?;
+/// Returns `html` without blank lines and indentation of the first line,
+/// which would end the HTML block or turn it into a code block
+fn html_block(html: &str) -> String {
+    let html = html.trim_start().trim_end_matches(['\n', '\r', ' ', '\t']);
+    let lower = html.to_ascii_lowercase();
+    let verbatim = ["<pre", "<script", "<style", "<textarea"]
+        .iter()
+        .any(|tag| lower.starts_with(tag));
+    if verbatim {
+        return html.to_string();
+    }
+    let lines: Vec<&str> = html
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    lines.join("\n")
+}
+
+impl MarkdownWritable for &'_ Html<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner, line_prefix, ..
+        } = *context;
+        let html = html_block(&self.html);
+        match &self.close {
+            Some(close) if !self.children.is_empty() => {
+                write_prefixed_blocks(
+                    writer,
+                    b"",
+                    format!("{}\n\n", html).as_bytes(),
+                    &self.children,
+                    context,
+                    None,
+                )?;
+                write_line_prefixed(writer, b"\n", line_prefix)?;
+                write_line_prefixed(writer, html_block(close).as_bytes(), line_prefix)?;
+            }
+            Some(close) => {
+                write_line_prefixed(writer, html.as_bytes(), line_prefix)?;
+                write_line_prefixed(writer, b"\n", line_prefix)?;
+                write_line_prefixed(writer, html_block(close).as_bytes(), line_prefix)?;
+            }
+            None => write_line_prefixed(writer, html.as_bytes(), line_prefix)?,
         }
         write_line_prefixed(writer, b"\n", line_prefix)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
         Ok(())
     }
 
     fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
-        let mut carry = 0;
         let mut count = 0;
         for child in &self.children {
-            let (c, cr) = child.count_max_streak(char, carry);
-            count += c;
-            carry = cr;
+            let (c, cr) = child.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
         }
-        (count, carry)
+        (count, 0)
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        self.children
+            .iter()
+            .flat_map(|child| child.headings(options))
+            .collect()
     }
 }
 
-impl MarkdownWritable for Heading<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        (&self).write_to(writer, inner, escape, line_prefix)
+impl MarkdownWritable for Html<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
     fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
         (&self).count_max_streak(char, carry)
     }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        (&self).headings(options)
+    }
 }
 //endregion
 
-//region Link
-/// Markdown link
-pub struct Link<'a> {
-    children: Vec<Box<dyn 'a + MarkdownWritable>>,
-    address: &'a str,
+//region Footer
+/// Footer noting the tool which generated the document, e.g. *Generated by tool 1.0 on 2024-05-01*
+///
+/// The footer is written as an italic line, or as an HTML comment hidden from readers
+/// with [comment](#method.comment).
+pub struct Footer<'a> {
+    tool: Cow<'a, str>,
+    version: Option<Cow<'a, str>>,
+    timestamp: Option<Cow<'a, str>>,
+    template: Option<Cow<'a, str>>,
+    comment: bool,
 }
 
-impl<'a> Link<'a> {
-    /// Creates an empty link, which leads to `address`
-    pub fn new(address: &'a str) -> Self {
+impl<'a> Footer<'a> {
+    /// Creates a footer naming `tool`
+    pub fn new<T: Into<Cow<'a, str>>>(tool: T) -> Self {
         Self {
-            children: Vec::new(),
-            address,
+            tool: tool.into(),
+            version: None,
+            timestamp: None,
+            template: None,
+            comment: false,
         }
     }
 
-    /// Appends an element to the link's text
-    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
-        self.children.push(Box::new(element));
+    /// Sets version of the tool
+    pub fn version<T: Into<Cow<'a, str>>>(mut self, version: T) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// Sets time of generation, already formatted as it should be written
+    pub fn timestamp<T: Into<Cow<'a, str>>>(mut self, timestamp: T) -> Self {
+        self.timestamp = Some(timestamp.into());
+        self
+    }
+
+    /// Sets text of the footer, e.g. `"Erzeugt von {tool} {version} am {timestamp}"`
+    ///
+    /// Placeholders `{tool}`, `{version}` and `{timestamp}` are replaced by the values,
+    /// missing values by an empty string.
+    /// Defaults to `"Generated by {tool} {version} on {timestamp}"` without the missing parts.
+    pub fn template<T: Into<Cow<'a, str>>>(mut self, template: T) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    /// Writes the footer as an HTML comment, or italic on flavors without HTML
+    pub fn comment(mut self) -> Self {
+        self.comment = true;
         self
     }
 }
 
-impl MarkdownWritable for &'_ Link<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        writer.write_all(b"[")?;
-        for child in &self.children {
-            child.write_to(writer, true, escape, line_prefix)?;
+impl Footer<'_> {
+    /// Returns text of the footer with placeholders replaced
+    fn text(&self) -> String {
+        let template = match &self.template {
+            Some(template) => template.to_string(),
+            None => {
+                let mut template = String::from("Generated by {tool}");
+                if self.version.is_some() {
+                    template.push_str(" {version}");
+                }
+                if self.timestamp.is_some() {
+                    template.push_str(" on {timestamp}");
+                }
+                template
+            }
+        };
+        template
+            .replace("{tool}", &self.tool)
+            .replace("{version}", self.version.as_deref().unwrap_or_default())
+            .replace("{timestamp}", self.timestamp.as_deref().unwrap_or_default())
+    }
+
+    /// Returns `true` if the footer is written as an HTML comment
+    fn is_comment(&self, options: &Options) -> bool {
+        self.comment && !matches!(options.flavor, Flavor::Telegram | Flavor::Discord)
+    }
+}
+
+impl MarkdownWritable for &'_ Footer<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let mut text = self.text();
+        if self.is_comment(options) {
+            // `--` may not appear inside of a comment
+            while text.contains("--") {
+                text = text.replace("--", "- -");
+            }
+            write!(writer, "<!-- {} -->", text.replace('\n', " "))?;
+        } else {
+            if self.comment {
+                options.warn(
+                    WarningKind::Degraded,
+                    "footer comment written as italic text",
+                );
+            }
+            RichText::new(text)
+                .italic()
+                .write_to(writer, &context.child(true, escape, line_prefix, options))?;
         }
-        writer.write_all(b"](")?;
-        self.address.write_to(writer, true, escape, line_prefix)?;
-        writer.write_all(b")")?;
         if !inner {
-            write_line_prefixed(writer, b"\n", line_prefix)?;
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
         }
         Ok(())
     }
 
     fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
-        let (mut addr, addr_cr) = self.address.count_max_streak(char, 0);
-        addr += addr_cr;
-        let mut carry = 0;
-        let mut count = 0;
-        for child in &self.children {
-            let (c, cr) = child.count_max_streak(char, carry);
-            count += c;
-            carry = cr;
+        (self.text().as_str().count_max_streak(char, 0).0, 0)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        if self.is_comment(options) {
+            return pandoc::raw(self, false, options);
         }
-        count += carry;
-        return if count > addr { (count, 0) } else { (addr, 0) };
+        let inlines = RichText::new(self.text()).italic().pandoc_inlines(options);
+        vec![serde_json::json!({"t": "Para", "c": inlines})]
     }
 }
 
-impl MarkdownWritable for Link<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        (&self).write_to(writer, inner, escape, line_prefix)
+impl MarkdownWritable for Footer<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
     fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
         (&self).count_max_streak(char, carry)
     }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
+    }
 }
+//endregion
 
-impl<'a> AsMarkdown<'a> for &'a Link<'a> {
-    fn paragraph(self) -> Paragraph<'a> {
-        Paragraph::new().append(self)
+//region FrontMatter
+/// YAML front matter written at the beginning of a document
+///
+/// Keys keep their order, so existing front matter can be parsed, updated and written back
+/// without reordering or losing hand-edited fields.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrontMatter {
+    entries: Vec<(String, FrontMatterValue)>,
+}
+
+/// Value of a [FrontMatter](struct.FrontMatter.html) key
+#[derive(Clone, Debug, PartialEq)]
+pub enum FrontMatterValue {
+    /// Scalar written on the same line as the key, stored as YAML
+    Scalar(String),
+    /// List of scalars written as `- item` lines, stored as YAML
+    List(Vec<String>),
+    /// Any other value kept verbatim including indentation
+    ///
+    /// A nested mapping starts on the line after the key, while an unindented first line,
+    /// e.g. the `|` or `>` header of a block scalar, is written on the line of the key.
+    Block(String),
+}
+
+impl FrontMatter {
+    /// Creates empty front matter
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn heading(self, level: usize) -> Heading<'a> {
-        Heading::new(level).append(self)
+    /// Parses front matter at the beginning of `document`
+    ///
+    /// Only top-level keys are interpreted, nested values and block scalars are kept as
+    /// [Block](enum.FrontMatterValue.html#variant.Block). Top-level comments are skipped.
+    ///
+    /// # Returns
+    /// Front matter and the rest of the document or `None` if the document does not start with `---`
+    pub fn parse(document: &str) -> Option<(FrontMatter, &str)> {
+        let mut lines = document.split_inclusive('\n');
+        if lines.next()?.trim_end() != "---" {
+            return None;
+        }
+        let mut front_matter = FrontMatter::new();
+        let mut consumed = document.find('\n')? + 1;
+        for line in lines {
+            consumed += line.len();
+            let content = line.trim_end_matches(['\r', '\n']);
+            if content == "---" || content == "..." {
+                return Some((front_matter, &document[consumed..]));
+            }
+            if content.trim().is_empty() {
+                // Blank lines are part of block scalars and nested values
+                if let Some((_, value @ FrontMatterValue::Block(_))) =
+                    front_matter.entries.last_mut()
+                {
+                    if *value != FrontMatterValue::Block(String::new()) {
+                        value.push_line("");
+                    }
+                }
+                continue;
+            }
+            if content.starts_with('#') {
+                continue;
+            }
+            let nested = content.starts_with(' ') || content.starts_with('-');
+            match front_matter.entries.last_mut() {
+                Some((_, value)) if nested => value.push_line(content),
+                _ => {
+                    let (key, value) = content.split_once(':')?;
+                    let value = value.trim();
+                    let value = if value.is_empty() {
+                        FrontMatterValue::Block(String::new())
+                    } else {
+                        FrontMatterValue::Scalar(value.to_string())
+                    };
+                    front_matter.entries.push((key.trim().to_string(), value));
+                }
+            }
+        }
+        None
     }
 
-    fn link_to(self, _address: &'a str) -> Link<'a> {
-        panic!("Link cannot contain another link.");
+    /// Returns the value of `key`
+    pub fn get(&self, key: &str) -> Option<&FrontMatterValue> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
     }
 
-    fn bold(self) -> RichText<'a> {
-        panic!("Cannot change link's body. Please use 'x.as_bold().as_link_to(...);'");
+    /// Sets `key` to a string, which is quoted if needed
+    ///
+    /// Existing keys keep their position, new keys are added at the end.
+    pub fn set<T: ToString>(&mut self, key: &str, value: T) {
+        self.set_value(
+            key,
+            FrontMatterValue::Scalar(yaml_scalar(&value.to_string())),
+        );
     }
 
-    fn italic(self) -> RichText<'a> {
-        panic!("Cannot change link's body. Please use 'x.as_italic().as_link_to(...);'");
+    /// Sets `key` to `value` without any conversion
+    pub fn set_value(&mut self, key: &str, value: FrontMatterValue) {
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.entries.push((key.to_string(), value)),
+        }
     }
 
-    fn code(self) -> RichText<'a> {
-        panic!("Cannot change link's body. Please use 'x.as_code().as_link_to(...);'");
+    /// Appends a string to the list under `key`, unless the list already contains it
+    ///
+    /// A scalar value is converted to a list containing it, other values are left unchanged.
+    pub fn append<T: ToString>(&mut self, key: &str, item: T) {
+        let item = yaml_scalar(&item.to_string());
+        let value = match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, value)) => value,
+            None => {
+                self.entries
+                    .push((key.to_string(), FrontMatterValue::List(Vec::new())));
+                &mut self.entries.last_mut().unwrap().1
+            }
+        };
+        match value {
+            FrontMatterValue::Scalar(scalar) => {
+                *value = FrontMatterValue::List(vec![scalar.clone()])
+            }
+            FrontMatterValue::Block(block) if block.is_empty() => {
+                *value = FrontMatterValue::List(Vec::new())
+            }
+            _ => {}
+        }
+        if let FrontMatterValue::List(items) = value {
+            if !items.contains(&item) {
+                items.push(item);
+            }
+        }
+    }
+
+    /// Removes `key`
+    ///
+    /// # Returns
+    /// Removed value or `None` if the key was not present
+    pub fn remove(&mut self, key: &str) -> Option<FrontMatterValue> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Sets all keys of `other`, keys already present keep their position
+    pub fn merge(&mut self, other: FrontMatter) {
+        for (key, value) in other.entries {
+            self.set_value(&key, value);
+        }
+    }
+}
+
+impl FrontMatterValue {
+    fn push_line(&mut self, line: &str) {
+        if let Some(item) = line.trim_start().strip_prefix("- ") {
+            match self {
+                FrontMatterValue::List(items) => {
+                    items.push(item.trim().to_string());
+                    return;
+                }
+                FrontMatterValue::Block(block) if block.is_empty() => {
+                    *self = FrontMatterValue::List(vec![item.trim().to_string()]);
+                    return;
+                }
+                _ => {}
+            }
+        }
+        let mut block = match self {
+            FrontMatterValue::List(items) => items
+                .iter()
+                .map(|item| format!("- {}\n", item))
+                .collect::<String>(),
+            FrontMatterValue::Scalar(scalar) => format!("{}\n", scalar),
+            FrontMatterValue::Block(block) => core::mem::take(block),
+        };
+        block.push_str(line);
+        block.push('\n');
+        *self = FrontMatterValue::Block(block);
+    }
+}
+
+impl MarkdownWritable for &'_ FrontMatter {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        if options.key_order() == KeyOrder::Sorted {
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        let mut yaml = String::from("---\n");
+        for (key, value) in entries {
+            match value {
+                FrontMatterValue::Scalar(scalar) => {
+                    yaml.push_str(&format!("{}: {}\n", key, scalar))
+                }
+                FrontMatterValue::List(items) if items.is_empty() => {
+                    yaml.push_str(&format!("{}: []\n", key))
+                }
+                FrontMatterValue::List(items) => {
+                    yaml.push_str(&format!("{}:\n", key));
+                    for item in items {
+                        yaml.push_str(&format!("  - {}\n", item));
+                    }
+                }
+                FrontMatterValue::Block(block) => {
+                    let separator = match block.chars().next() {
+                        Some(c) if !c.is_whitespace() && c != '-' => " ",
+                        _ => "\n",
+                    };
+                    yaml.push_str(&format!("{}:{}{}", key, separator, block));
+                    if !block.is_empty() && !block.ends_with('\n') {
+                        yaml.push('\n');
+                    }
+                }
+            }
+        }
+        yaml.push_str("---\n");
+        write_line_prefixed(writer, yaml.as_bytes(), line_prefix)
     }
 
-    fn quote(self) -> Quote<'a> {
-        Quote::new().append(self)
+    fn count_max_streak(&self, _char: u8, _carry: usize) -> (usize, usize) {
+        (0, 0)
     }
 }
 
-impl<'a> AsMarkdown<'a> for Link<'a> {
-    fn paragraph(self) -> Paragraph<'a> {
-        Paragraph::new().append(self)
+impl MarkdownWritable for FrontMatter {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
-    fn heading(self, level: usize) -> Heading<'a> {
-        Heading::new(level).append(self)
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
     }
+}
 
-    fn link_to(self, _address: &'a str) -> Link<'a> {
-        panic!("Link cannot contain another link.");
+/// Writes `text` as a YAML scalar, quoting it when it would be read differently
+fn yaml_scalar(text: &str) -> String {
+    let plain = !text.is_empty()
+        && !text.starts_with(|c: char| " -?:,[]{}#&*!|>'\"%@`".contains(c))
+        && !text.ends_with(' ')
+        && !text.contains(": ")
+        && !text.contains(" #")
+        && !text.contains('\n');
+    if plain {
+        text.to_string()
+    } else {
+        format!(
+            "\"{}\"",
+            text.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        )
     }
+}
+//endregion
 
-    fn bold(self) -> RichText<'a> {
-        panic!("Cannot change link's body. Please use 'x.as_bold().as_link_to(...);'");
-    }
+//region FieldList
+/// Trait for types which can be written as a [FieldList](struct.FieldList.html)
+///
+/// With the `derive` feature it can be derived by `#[derive(ToMarkdown)]`.
+pub trait ToMarkdown {
+    /// Converts fields of `self` to a [FieldList](struct.FieldList.html)
+    fn to_markdown(&self) -> FieldList;
+}
 
-    fn italic(self) -> RichText<'a> {
-        panic!("Cannot change link's body. Please use 'x.as_italic().as_link_to(...);'");
+/// Names and values of fields written as a two-column table or a definition list
+pub struct FieldList {
+    list: bool,
+    fields: Vec<(String, String, bool)>,
+}
+
+impl FieldList {
+    /// Creates an empty field list written as a `Field | Value` table
+    pub fn table() -> Self {
+        Self {
+            list: false,
+            fields: Vec::new(),
+        }
     }
 
-    fn code(self) -> RichText<'a> {
-        panic!("Cannot change link's body. Please use 'x.as_code().as_link_to(...);'");
+    /// Creates an empty field list written as a definition list
+    pub fn definition_list() -> Self {
+        Self {
+            list: true,
+            fields: Vec::new(),
+        }
     }
 
-    fn quote(self) -> Quote<'a> {
-        Quote::new().append(self)
+    /// Adds a field with the value written as text
+    pub fn field<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.fields.push((name.into(), value.into(), false));
+        self
     }
-}
-//endregion
 
-//region RichText
-/// Text styled with **bold**, *italic* or `code`
-#[derive(Copy, Clone)]
-pub struct RichText<'a> {
-    bold: bool,
-    italic: bool,
-    code: bool,
-    text: &'a str,
-}
+    /// Adds a field with the value written as inline code
+    pub fn code_field<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.fields.push((name.into(), value.into(), true));
+        self
+    }
 
-impl<'a> RichText<'a> {
-    fn new(text: &'a str) -> Self {
-        Self {
-            bold: false,
-            italic: false,
-            code: false,
-            text,
-        }
+    fn value(value: &str, code: bool) -> RichText<'_> {
+        let mut text = RichText::new(value);
+        text.code = code;
+        text
     }
 }
 
-impl MarkdownWritable for &'_ RichText<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        mut escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        let mut symbol = Vec::new();
-        if self.bold {
-            symbol.extend_from_slice(b"**");
+impl MarkdownWritable for &'_ FieldList {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        if options.key_order() == KeyOrder::Sorted {
+            fields.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
         }
-        if self.italic {
-            symbol.push(b'*');
+        if !self.list {
+            let mut table = Table::new().header(vec!["Field", "Value"]);
+            for (name, value, code) in &fields {
+                table = table.row(vec![
+                    RichText::new(name.as_str()),
+                    FieldList::value(value, *code),
+                ]);
+            }
+            return table.write_to(writer, context);
         }
-        if self.code {
-            let (mut ticks_needed, carry) = self.text.count_max_streak(b'`', 0);
-            ticks_needed += 1 + carry;
-            symbol.extend(vec![b'`'; ticks_needed]);
-            symbol.push(b' ');
-            escape = InlineCode;
+        // Lines of a value continue the definition, so they are indented like it
+        let mut prefix = Vec::new();
+        if let Some(line_prefix) = line_prefix {
+            prefix.extend_from_slice(line_prefix);
         }
-
-        writer.write_all(&symbol)?;
-        self.text.write_to(writer, true, escape, line_prefix)?;
-        symbol.reverse();
-        writer.write_all(&symbol)?;
-
-        if !inner {
+        prefix.extend_from_slice(b"    ");
+        for (name, value, code) in fields {
+            name.as_str()
+                .write_to(writer, &context.child(true, escape, line_prefix, options))?;
+            write_line_prefixed(writer, b"\n:   ", line_prefix)?;
+            FieldList::value(value, *code)
+                .write_to(writer, &context.child(true, escape, Some(&prefix), options))?;
             write_line_prefixed(writer, b"\n\n", line_prefix)?;
         }
         Ok(())
     }
 
     fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
-        let (res, cr) = self.text.count_max_streak(char, 0);
-        (res + cr, 0)
+        let mut count = 0;
+        for (name, value, _) in &self.fields {
+            for text in &[name, value] {
+                let (c, cr) = text.as_str().count_max_streak(char, 0);
+                count = count.max(c).max(cr);
+            }
+        }
+        (count, 0)
     }
 }
 
-impl MarkdownWritable for RichText<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        (&self).write_to(writer, inner, escape, line_prefix)
+impl MarkdownWritable for FieldList {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
     fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
         (&self).count_max_streak(char, carry)
     }
 }
+//endregion
 
-impl<'a> AsMarkdown<'a> for &'a RichText<'a> {
-    fn paragraph(self) -> Paragraph<'a> {
-        Paragraph::new().append(self)
+//region Glossary
+/// Set of terms, which are linked to their definitions
+///
+/// Only the first occurrence of each term in a section is linked.
+pub struct Glossary<'a> {
+    terms: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    linked: Vec<bool>,
+}
+
+impl<'a> Glossary<'a> {
+    /// Creates an empty glossary
+    pub fn new() -> Self {
+        Self {
+            terms: Vec::new(),
+            linked: Vec::new(),
+        }
     }
 
-    fn heading(self, level: usize) -> Heading<'a> {
-        Heading::new(level).append(self)
+    /// Registers a term
+    ///
+    /// # Arguments
+    /// * `term` - Term to search for
+    /// * `address` - Address which will the term lead to
+    pub fn term<T, A>(mut self, term: T, address: A) -> Self
+    where
+        T: Into<Cow<'a, str>>,
+        A: Into<Cow<'a, str>>,
+    {
+        let term = term.into();
+        assert!(!term.is_empty(), "Glossary term cannot be empty.");
+        self.terms.push((term, address.into()));
+        self.linked.push(false);
+        self
     }
 
-    fn link_to(self, address: &'a str) -> Link<'a> {
-        Link::new(address).append(self)
+    /// Starts a new section, so every term will be linked again on its next occurrence
+    pub fn section(&mut self) {
+        for linked in &mut self.linked {
+            *linked = false;
+        }
     }
 
-    fn bold(self) -> RichText<'a> {
-        let mut clone = *self;
-        clone.bold = true;
-        *self
+    /// Links the first occurrence of each term in `text`, which was not linked yet in the current section
+    ///
+    /// # Returns
+    /// [Paragraph](struct.Paragraph.html) containing `text` with the terms converted to [Links](struct.Link.html)
+    pub fn link_terms(&mut self, text: &'a str) -> Paragraph<'a> {
+        let mut paragraph = Paragraph::new();
+        let mut rest = text;
+        while let Some((index, at)) = self.find_next(rest) {
+            let (term, address) = &self.terms[index];
+            let end = at + term.len();
+            self.linked[index] = true;
+            if at > 0 {
+                paragraph = paragraph.append(&rest[..at]);
+            }
+            paragraph = paragraph.append(Link::new(address.clone()).append(&rest[at..end]));
+            rest = &rest[end..];
+        }
+        if !rest.is_empty() {
+            paragraph = paragraph.append(rest);
+        }
+        paragraph
     }
 
-    fn italic(self) -> RichText<'a> {
-        let mut clone = *self;
-        clone.italic = true;
-        *self
+    /// Finds the earliest occurrence of a term, which was not linked yet
+    ///
+    /// # Returns
+    /// `(index, position)` of the term, longer terms take precedence at the same position
+    fn find_next(&self, text: &str) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize)> = None;
+        for (index, (term, _)) in self.terms.iter().enumerate() {
+            if self.linked[index] {
+                continue;
+            }
+            if let Some(at) = find_word(text, term) {
+                best = match best {
+                    Some((best_index, best_at))
+                        if best_at < at
+                            || (best_at == at && self.terms[best_index].0.len() >= term.len()) =>
+                    {
+                        Some((best_index, best_at))
+                    }
+                    _ => Some((index, at)),
+                };
+            }
+        }
+        best
     }
+}
 
-    fn code(self) -> RichText<'a> {
-        let mut clone = *self;
-        clone.code = true;
-        *self
+impl Default for Glossary<'_> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn quote(self) -> Quote<'a> {
-        Quote::new().append(self)
+/// Finds the first occurrence of `word` in `text`, which is not a part of another word
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(at) = text[from..].find(word) {
+        let at = from + at;
+        let end = at + word.len();
+        let before = text[..at].chars().next_back();
+        let after = text[end..].chars().next();
+        if !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric) {
+            return Some(at);
+        }
+        from = at + text[at..].chars().next().map_or(1, char::len_utf8);
     }
+    None
 }
+//endregion
 
-impl<'a> AsMarkdown<'a> for RichText<'a> {
-    fn paragraph(self) -> Paragraph<'a> {
-        Paragraph::new().append(self)
+//region Index
+/// Alphabetized back-of-book index
+///
+/// Occurrences of terms are marked with [IndexMarkers](struct.IndexMarker.html)
+/// and the index itself is written as a bulleted list linking back to them.
+pub struct Index<'a> {
+    entries: Vec<Cow<'a, str>>,
+}
+
+impl<'a> Index<'a> {
+    /// Creates an empty index
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
     }
 
-    fn heading(self, level: usize) -> Heading<'a> {
-        Heading::new(level).append(self)
+    /// Marks an occurrence of `term`
+    ///
+    /// # Returns
+    /// [IndexMarker](struct.IndexMarker.html), which has to be written at the location of the occurrence
+    pub fn mark<T: Into<Cow<'a, str>>>(&mut self, term: T) -> IndexMarker {
+        self.entries.push(term.into());
+        IndexMarker {
+            id: self.entries.len(),
+        }
     }
+}
 
-    fn link_to(self, address: &'a str) -> Link<'a> {
-        Link::new(address).append(self)
+impl Default for Index<'_> {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn bold(mut self) -> RichText<'a> {
-        self.bold = true;
-        self
+impl MarkdownWritable for &'_ Index<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let mut terms = self.entries.clone();
+        terms.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()).then(a.cmp(b)));
+        terms.dedup();
+
+        let addresses: Vec<String> = (1..=self.entries.len())
+            .map(|id| format!("#index-{}", id))
+            .collect();
+        let numbers: Vec<String> = (1..=self.entries.len()).map(|n| n.to_string()).collect();
+
+        let mut list = List::new(false);
+        for term in terms {
+            let mut item = Paragraph::new().append(term.clone()).append(": ");
+            let occurrences = self.entries.iter().enumerate().filter(|(_, t)| **t == term);
+            for (n, (i, _)) in occurrences.enumerate() {
+                if n > 0 {
+                    item = item.append(", ");
+                }
+                item = item.append(Link::new(&addresses[i]).append(numbers[n].as_str()));
+            }
+            list = list.item(item);
+        }
+        list.write_to(writer, context)
     }
 
-    fn italic(mut self) -> RichText<'a> {
-        self.italic = true;
-        self
+    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
+        let mut count = 0;
+        for term in &self.entries {
+            let (c, cr) = term.count_max_streak(char, 0);
+            count = count.max(c).max(cr);
+        }
+        (count, 0)
     }
+}
 
-    fn code(mut self) -> RichText<'a> {
-        self.code = true;
-        self
+impl MarkdownWritable for Index<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
-    fn quote(self) -> Quote<'a> {
-        Quote::new().append(self)
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
     }
 }
-//endregion
 
-//region List
-/// Bulleted or numbered list
-pub struct List<'a> {
-    title: Vec<Box<dyn 'a + MarkdownWritable>>,
-    items: Vec<Box<dyn 'a + MarkdownWritable>>,
-    numbered: bool,
+/// Invisible anchor marking an occurrence of an [Index](struct.Index.html) term
+pub struct IndexMarker {
+    id: usize,
 }
 
-impl<'a> List<'a> {
-    /// Creates an empty list
-    /// # Arguments
-    /// * `numbered` - `true` for numbered list, `false` for bulleted list
-    pub fn new(numbered: bool) -> Self {
-        Self {
-            items: Vec::new(),
-            title: Vec::new(),
-            numbered,
+impl MarkdownWritable for &'_ IndexMarker {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner, line_prefix, ..
+        } = *context;
+        write!(writer, "<a id=\"index-{}\"></a>", self.id)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
         }
+        Ok(())
     }
 
-    /// Append an item to the list title
-    pub fn title<T: 'a + MarkdownWritable>(mut self, item: T) -> Self {
-        self.title.push(Box::new(item));
-        self
+    fn count_max_streak(&self, _char: u8, carry: usize) -> (usize, usize) {
+        (carry, 0)
     }
+}
 
-    /// Adds an item to the list
-    pub fn item<T: 'a + MarkdownWritable>(mut self, item: T) -> Self {
-        self.items.push(Box::new(item));
-        self
+impl MarkdownWritable for IndexMarker {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
     }
 }
+//endregion
 
-impl MarkdownWritable for &'_ List<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        _inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        for it in &self.title {
-            it.write_to(writer, true, escape, line_prefix)?;
+//region Math
+/// Inline LaTeX math `$...$`, written without escaping except for `$` signs
+///
+/// A `$` not escaped by a backslash would end the math, so it is written as `\$`.
+/// Empty math is not written at all.
+/// Flavors without math support, Telegram and Discord, show the source as inline code.
+#[derive(Clone, Debug)]
+pub struct MathInline<'a> {
+    tex: Cow<'a, str>,
+}
+
+impl<'a> MathInline<'a> {
+    /// Creates inline math from LaTeX source without the delimiters
+    pub fn new<T: Into<Cow<'a, str>>>(tex: T) -> Self {
+        Self { tex: tex.into() }
+    }
+}
+
+/// Display LaTeX math written between `$$` lines, without escaping
+///
+/// Math containing a `$$` line, which would end the block early, is written as
+/// a fenced code block with the `math` language like GFM supports. Empty math is not written at all.
+/// Flavors without math support, Telegram and Discord, show the source as a code block.
+#[derive(Clone, Debug)]
+pub struct MathBlock<'a> {
+    tex: Cow<'a, str>,
+}
+
+impl<'a> MathBlock<'a> {
+    /// Creates display math from LaTeX source without the delimiters
+    pub fn new<T: Into<Cow<'a, str>>>(tex: T) -> Self {
+        Self { tex: tex.into() }
+    }
+}
+
+impl MarkdownWritable for &'_ MathInline<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let tex = self.tex.trim();
+        if tex.is_empty() {
+            return options.empty("empty math");
         }
-        let mut prefix = Vec::new();
-        if line_prefix.is_some() {
-            prefix.extend_from_slice(line_prefix.unwrap());
+        if matches!(options.flavor, Flavor::Telegram | Flavor::Discord) {
+            options.warn(WarningKind::Degraded, "math written as inline code");
+            return RichText::new(tex).code().write_to(writer, context);
         }
-        prefix.extend_from_slice(b"   ");
-
-        for it in &self.items {
-            if self.numbered {
-                write_line_prefixed(writer, b"\n1. ", Some(&prefix))?;
-            } else {
-                write_line_prefixed(writer, b"\n* ", Some(&prefix))?;
+        // Inline math is not recognized if it starts or ends with a space or spans lines
+        let mut math = String::with_capacity(tex.len() + 2);
+        let mut backslashes = 0;
+        for c in tex.chars() {
+            match c {
+                '$' if backslashes % 2 == 0 => math.push_str("\\$"),
+                '\n' => math.push(' '),
+                _ => math.push(c),
             }
-
-            it.write_to(writer, true, escape, Some(&prefix))?;
+            backslashes = if c == '\\' { backslashes + 1 } else { 0 };
+        }
+        write!(writer, "${}$", math)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n\n", line_prefix)?;
         }
         Ok(())
     }
 
-    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
-        let mut count = 0;
-        for child in &self.items {
-            let (c, _) = child.count_max_streak(char, 0);
-            if c > count {
-                count = c;
-            }
-        }
-        (count, 0)
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.tex.as_ref().count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, _options: &Options) -> Vec<serde_json::Value> {
+        vec![serde_json::json!({"t": "Math", "c": [{"t": "InlineMath"}, self.tex.trim()]})]
     }
 }
 
-impl<'a> MarkdownWritable for List<'a> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        (&self).write_to(writer, inner, escape, line_prefix)
+impl MarkdownWritable for MathInline<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
     fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
         (&self).count_max_streak(char, carry)
     }
-}
 
-impl<'a> AsMarkdown<'a> for List<'a> {
-    fn paragraph(self) -> Paragraph<'a> {
-        Paragraph::new().append(self)
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_inlines(options)
     }
+}
 
-    fn heading(self, _level: usize) -> Heading<'a> {
-        panic!("Cannot make a Heading from List");
+impl MarkdownWritable for &'_ MathBlock<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        let tex = self.tex.trim_matches('\n');
+        if tex.trim().is_empty() {
+            return options.empty("empty math");
+        }
+        if matches!(options.flavor, Flavor::Telegram | Flavor::Discord) {
+            options.warn(WarningKind::Degraded, "math written as a code block");
+            return CodeBlock::new(tex)
+                .language("latex")
+                .write_to(writer, context);
+        }
+        if tex.lines().any(|line| line.trim_start().starts_with("$$")) {
+            options.warn(
+                WarningKind::Degraded,
+                "math containing a $$ line written as a code block",
+            );
+            return CodeBlock::new(tex)
+                .language("math")
+                .write_to(writer, context);
+        }
+        write_line_prefixed(writer, b"$$\n", line_prefix)?;
+        write_line_prefixed(writer, tex.as_bytes(), line_prefix)?;
+        write_line_prefixed(writer, b"\n$$\n", line_prefix)?;
+        if !inner {
+            write_line_prefixed(writer, b"\n", line_prefix)?;
+        }
+        Ok(())
     }
 
-    fn link_to(self, _address: &'a str) -> Link<'a> {
-        panic!("Cannot make a Link from List");
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.tex.as_ref().count_max_streak(char, carry)
     }
 
-    fn bold(self) -> RichText<'a> {
-        panic!("Cannot make a List bold");
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, _options: &Options) -> Vec<serde_json::Value> {
+        let math = serde_json::json!({"t": "Math", "c": [{"t": "DisplayMath"}, self.tex.trim()]});
+        vec![serde_json::json!({"t": "Para", "c": [math]})]
     }
-
-    fn italic(self) -> RichText<'a> {
-        panic!("Cannot make a List italic");
+}
+
+impl MarkdownWritable for MathBlock<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
-    fn code(self) -> RichText<'a> {
-        panic!("Cannot make a List code");
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
     }
 
-    fn quote(self) -> Quote<'a> {
-        Quote::new().append(self)
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        (&self).pandoc_blocks(options)
     }
 }
 //endregion
 
-//region Quote
-/// A quote block
-pub struct Quote<'a> {
-    children: Vec<Box<dyn 'a + MarkdownWritable>>,
+//region Raw
+/// Text written verbatim without escaping, e.g. prebuilt markdown or HTML
+///
+/// The text is not checked, so it can break the surrounding markdown.
+/// Written as a block, it is followed by an empty line.
+#[derive(Clone, Debug)]
+pub struct Raw<'a> {
+    text: Cow<'a, str>,
 }
 
-impl<'a> Quote<'a> {
-    /// Creates an empty quote block
-    fn new() -> Self {
-        Self {
-            children: Vec::new(),
-        }
-    }
-
-    /// Appends an element to the quote block
-    pub fn append<T: 'a + MarkdownWritable>(mut self, element: T) -> Self {
-        self.children.push(Box::new(element));
-        self
+impl<'a> Raw<'a> {
+    /// Creates a raw text element
+    pub fn new<T: Into<Cow<'a, str>>>(text: T) -> Self {
+        Self { text: text.into() }
     }
 }
 
-impl MarkdownWritable for &'_ Quote<'_> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        let mut prefix = Vec::new();
-        if line_prefix.is_some() {
-            prefix.extend_from_slice(line_prefix.unwrap());
-        }
-        prefix.extend_from_slice(b">");
-        if !inner {
-            write_line_prefixed(writer, b"\n", line_prefix)?;
-        }
-        writer.write_all(b">")?;
-        for child in &self.children {
-            child.write_to(writer, true, escape, Some(&prefix))?;
-        }
+impl MarkdownWritable for &'_ Raw<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner, line_prefix, ..
+        } = *context;
+        write_line_prefixed(writer, self.text.as_bytes(), line_prefix)?;
         if !inner {
             write_line_prefixed(writer, b"\n\n", line_prefix)?;
         }
-
         Ok(())
     }
 
-    fn count_max_streak(&self, char: u8, _carry: usize) -> (usize, usize) {
-        let mut count = 0;
-        for child in &self.children {
-            let (c, _) = child.count_max_streak(char, 0);
-            if c > count {
-                count = c;
-            }
-        }
-        (count, 0)
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.text.as_ref().count_max_streak(char, carry)
     }
 }
-impl<'a> MarkdownWritable for Quote<'a> {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
-        (&self).write_to(writer, inner, escape, line_prefix)
+
+impl MarkdownWritable for Raw<'_> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        (&self).write_to(writer, context)
     }
 
     fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
@@ -731,19 +9133,41 @@ impl<'a> MarkdownWritable for Quote<'a> {
 
 //region String and &str
 impl MarkdownWritable for &str {
-    fn write_to(
-        &self,
-        writer: &mut dyn Write,
-        inner: bool,
-        escape: Escaping,
-        line_prefix: Option<&[u8]>,
-    ) -> Result<(), Error> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext {
+            inner,
+            escape,
+            line_prefix,
+            options,
+            ..
+        } = *context;
+        if self.is_empty() && !inner {
+            return options.empty("empty text");
+        }
         match escape {
             Normal => {
-                write_escaped(writer, self.as_bytes(), b"\\`*_{}[]()#+-.!", line_prefix)?;
+                let text = options.sanitize(options.transform(self));
+                let text = options.whitespace(options.tabs(text, Some(context.line))?, !inner)?;
+                if options.smart_escaping && options.flavor != Flavor::Telegram {
+                    write_smart_escaped(
+                        writer,
+                        text.as_bytes(),
+                        context.line,
+                        options.flavor,
+                        line_prefix,
+                    )?;
+                } else {
+                    write_escaped(
+                        writer,
+                        text.as_bytes(),
+                        options.flavor.escape(),
+                        line_prefix,
+                    )?;
+                }
             }
             InlineCode => {
-                writer.write_all(self.as_bytes())?;
+                let text = options.sanitize(Cow::Borrowed(self));
+                writer.write_all(options.tabs(text, None)?.as_bytes())?;
             }
         }
         if !inner {
@@ -775,6 +9199,101 @@ impl MarkdownWritable for &str {
         }
         (max, current)
     }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        pandoc::text(&options.transform(self))
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        vec![serde_json::json!({"t": "Para", "c": self.pandoc_inlines(options)})]
+    }
+}
+
+impl MarkdownWritable for String {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        self.as_str().write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.as_str().count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        self.as_str().pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        self.as_str().pandoc_blocks(options)
+    }
+}
+
+impl<T: MarkdownWritable> MarkdownWritable for Option<T> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        let RenderContext { options, .. } = *context;
+        match self {
+            Some(value) => value.write_to(writer, context),
+            None if options.null_placeholder().is_empty() => Ok(()),
+            None => options.null_placeholder().write_to(writer, context),
+        }
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        match self {
+            Some(value) => value.count_max_streak(char, carry),
+            None => (0, carry),
+        }
+    }
+
+    fn headings(&self, options: &Options) -> Vec<(usize, String, Option<String>)> {
+        match self {
+            Some(value) => value.headings(options),
+            None => Vec::new(),
+        }
+    }
+
+    fn preferred_alignment(&self) -> Option<Alignment> {
+        self.as_ref()?.preferred_alignment()
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        match self {
+            Some(value) => value.pandoc_inlines(options),
+            None => options.null_placeholder().pandoc_inlines(options),
+        }
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        match self {
+            Some(value) => value.pandoc_blocks(options),
+            None => options.null_placeholder().pandoc_blocks(options),
+        }
+    }
+}
+
+impl MarkdownWritable for Cow<'_, str> {
+    fn write_to(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+        self.as_ref().write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        self.as_ref().count_max_streak(char, carry)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_inlines(&self, options: &Options) -> Vec<serde_json::Value> {
+        self.as_ref().pandoc_inlines(options)
+    }
+
+    #[cfg(feature = "pandoc")]
+    fn pandoc_blocks(&self, options: &Options) -> Vec<serde_json::Value> {
+        self.as_ref().pandoc_blocks(options)
+    }
 }
 
 impl<'a> AsMarkdown<'a> for &'a String {
@@ -786,7 +9305,7 @@ impl<'a> AsMarkdown<'a> for &'a String {
         self.as_str().heading(level)
     }
 
-    fn link_to(self, address: &'a str) -> Link<'a> {
+    fn link_to<A: Into<Cow<'a, str>>>(self, address: A) -> Link<'a> {
         self.as_str().link_to(address)
     }
 
@@ -805,6 +9324,10 @@ impl<'a> AsMarkdown<'a> for &'a String {
     fn quote(self) -> Quote<'a> {
         self.as_str().quote()
     }
+
+    fn image_to<A: Into<Cow<'a, str>>>(self, address: A) -> Image<'a> {
+        self.as_str().image_to(address)
+    }
 }
 
 impl<'a> AsMarkdown<'a> for &'a str {
@@ -816,7 +9339,7 @@ impl<'a> AsMarkdown<'a> for &'a str {
         Heading::new(level).append(self)
     }
 
-    fn link_to(self, address: &'a str) -> Link<'a> {
+    fn link_to<A: Into<Cow<'a, str>>>(self, address: A) -> Link<'a> {
         Link::new(address).append(self)
     }
 
@@ -835,10 +9358,65 @@ impl<'a> AsMarkdown<'a> for &'a str {
     fn quote(self) -> Quote<'a> {
         Quote::new().append(self)
     }
+
+    fn image_to<A: Into<Cow<'a, str>>>(self, address: A) -> Image<'a> {
+        Image::new(self, address)
+    }
 }
 //endregion
 
-fn write_escaped<W: Write + ?Sized>(
+/// Characters escaped with a backslash in [Escaping::Normal](enum.Escaping.html#variant.Normal) mode
+const NORMAL_ESCAPE: &[u8] = b"\\`*_{}[]()#+-.!";
+
+/// Writes a link destination
+///
+/// Telegram requires backslash escapes, elsewhere characters which would end the destination
+/// are percent-encoded and the rest of the address is left as is.
+fn write_address<W: Write + ?Sized>(
+    writer: &mut W,
+    address: &str,
+    flavor: Flavor,
+    line_prefix: Option<&[u8]>,
+) -> Result<(), Error> {
+    if flavor == Flavor::Telegram {
+        return write_escaped(
+            writer,
+            address.as_bytes(),
+            flavor.address_escape(),
+            line_prefix,
+        );
+    }
+    let mut depth: isize = 0;
+    let balanced = address.chars().all(|c| {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        depth >= 0
+    }) && depth == 0;
+    let mut encoded = String::with_capacity(address.len());
+    for c in address.chars() {
+        let encode = c.is_whitespace()
+            || c.is_control()
+            || c == '<'
+            || c == '>'
+            || (!balanced && (c == '(' || c == ')'));
+        if encode {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            encoded.push(c);
+        }
+    }
+    write_line_prefixed(writer, encoded.as_bytes(), line_prefix)
+}
+
+/// Writes `data` with every byte contained in `escape` preceded by a backslash,
+/// e.g. `EscapeContext::Text(flavor).escaped()`, and `line_prefix` after every line break
+pub fn write_escaped<W: Write + ?Sized>(
     writer: &mut W,
     mut data: &[u8],
     escape: &[u8],
@@ -848,7 +9426,7 @@ fn write_escaped<W: Write + ?Sized>(
         let slice_at = data.iter().position(|x| escape.contains(x));
         match slice_at {
             Option::None => {
-                write_line_prefixed(writer, &data, line_prefix)?;
+                write_line_prefixed(writer, data, line_prefix)?;
                 return Ok(());
             }
             Some(slice_at) => {
@@ -861,7 +9439,390 @@ fn write_escaped<W: Write + ?Sized>(
     }
 }
 
-fn write_line_prefixed<W: Write + ?Sized>(
+/// Scans inline code once, returning the longest run of backticks before the last one,
+/// the trailing run of backticks and `true` if the code contains no tabs or control characters
+///
+/// Bytes starting C1 control characters are treated as control characters together with
+/// the rest of the Latin-1 supplement, which only makes such code take the slower path.
+fn scan_code(data: &[u8]) -> (usize, usize, bool) {
+    let mut max = 0;
+    let mut current = 0;
+    let mut plain = true;
+    for &c in data {
+        if c == b'`' {
+            current += 1;
+            continue;
+        }
+        max = max.max(current);
+        current = 0;
+        if c < 0x20 || c == 0x7f || c == 0xc2 {
+            plain = false;
+        }
+    }
+    (max, current, plain)
+}
+
+/// Writes text escaping only characters which would start markdown syntax at their position
+///
+/// `preceding` is the text already written on the line `data` starts on.
+fn write_smart_escaped<W: Write + ?Sized>(
+    writer: &mut W,
+    data: &[u8],
+    preceding: &[u8],
+    flavor: Flavor,
+    line_prefix: Option<&[u8]>,
+) -> Result<(), Error> {
+    let mut written = 0;
+    let mut line_start = 0;
+    for (i, &c) in data.iter().enumerate() {
+        if c == b'\n' {
+            line_start = i + 1;
+            continue;
+        }
+        let before = || match line_start {
+            0 if !preceding.is_empty() => Cow::Owned([preceding, &data[..i]].concat()),
+            _ => Cow::Borrowed(&data[line_start..i]),
+        };
+        let after = &data[i + 1..];
+        let rest = &after[..after
+            .iter()
+            .position(|x| *x == b'\n')
+            .unwrap_or(after.len())];
+        let at_line_start = || {
+            let before = before();
+            let indent = before.iter().take_while(|x| **x == b' ').count();
+            indent == before.len() && indent < 4
+        };
+        let ends_marker = rest.first().map_or(true, |x| x.is_ascii_whitespace());
+        let escape = match c {
+            b'\\' | b'`' | b'*' | b'[' | b']' => true,
+            b'~' => flavor != Flavor::CommonMark,
+            b'|' => flavor == Flavor::Discord,
+            b'_' => {
+                let word = |x: Option<&u8>| x.is_some_and(|x| x.is_ascii_alphanumeric());
+                let previous = match i {
+                    0 => preceding.last(),
+                    _ => data.get(i - 1),
+                };
+                !(word(previous) && word(data.get(i + 1)))
+            }
+            b'#' | b'>' => at_line_start(),
+            // List items, thematic breaks and setext heading underlines
+            b'-' | b'+' | b'=' => {
+                (c != b'=' && ends_marker || rest.iter().all(|x| *x == c || *x == b' '))
+                    && at_line_start()
+            }
+            // Ordered list items
+            b'.' | b')' => {
+                let before = before();
+                let indent = before.iter().take_while(|x| **x == b' ').count();
+                let number = &before[indent..];
+                indent < 4
+                    && ends_marker
+                    && !number.is_empty()
+                    && number.len() < 10
+                    && number.iter().all(u8::is_ascii_digit)
+            }
+            _ => false,
+        };
+        if escape {
+            write_line_prefixed(writer, &data[written..i], line_prefix)?;
+            writer.write_all(b"\\")?;
+            written = i;
+        }
+    }
+    write_line_prefixed(writer, &data[written..], line_prefix)
+}
+
+/// Writer recording the start of the current line for [write_smart_escaped]
+///
+/// Line prefixes written after line breaks are not recorded.
+struct LineTracker<'a, W: Write + ?Sized> {
+    writer: &'a mut W,
+    line_prefix: &'a [u8],
+    /// Bytes of the line prefix still to be skipped
+    skip: usize,
+    /// Start of the current line, long enough to tell whether it is blank or an ordered list marker
+    line: Vec<u8>,
+}
+
+impl<'a, W: Write + ?Sized> LineTracker<'a, W> {
+    const RECORDED: usize = 16;
+
+    fn new(writer: &'a mut W, line: &[u8], line_prefix: Option<&'a [u8]>) -> Self {
+        Self {
+            writer,
+            line_prefix: line_prefix.unwrap_or_default(),
+            skip: 0,
+            line: line[..line.len().min(Self::RECORDED)].to_vec(),
+        }
+    }
+}
+
+impl<W: Write + ?Sized> Write for LineTracker<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let written = self.writer.write(buf)?;
+        for &c in &buf[..written] {
+            if c == b'\n' {
+                self.line.clear();
+                self.skip = self.line_prefix.len();
+            } else if self.skip > 0 {
+                self.skip -= 1;
+            } else if self.line.len() < Self::RECORDED {
+                self.line.push(c);
+            }
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()
+    }
+}
+
+/// Skips the rest of an ANSI escape sequence after `ESC`
+///
+/// Handles CSI sequences, e.g. colors `ESC [ 31 m`, OSC sequences, e.g. hyperlinks,
+/// ended by `BEL` or `ESC \\`, and two-character sequences.
+fn skip_escape_sequence<I: Iterator<Item = char>>(chars: &mut core::iter::Peekable<I>) {
+    match chars.next() {
+        Some('[') => {
+            for c in chars.by_ref() {
+                if ('\u{40}'..='\u{7e}').contains(&c) {
+                    break;
+                }
+            }
+        }
+        Some(']') => {
+            while let Some(c) = chars.next() {
+                if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                    break;
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces spaces which markdown would drop or collapse by non-breaking spaces
+///
+/// These are spaces at the start and end of lines, all but the first space of a run
+/// and, if `edges` is set, spaces at the start and end of `text`.
+fn preserve_whitespace(text: &str, edges: bool, flavor: Flavor) -> String {
+    // Telegram does not support HTML entities
+    let nbsp = if flavor == Flavor::Telegram {
+        "\u{a0}"
+    } else {
+        "&nbsp;"
+    };
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut preserved = String::with_capacity(text.len());
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            preserved.push('\n');
+        }
+        let content = line.trim_start_matches(' ');
+        let leading = line.len() - content.len();
+        let content = content.trim_end_matches(' ');
+        let trailing = line.len() - leading - content.len();
+        // A run of spaces inside a line keeps its first space
+        let run = |count: usize, edge: bool| match count {
+            0 => String::new(),
+            _ if edge => nbsp.repeat(count),
+            _ => format!(" {}", nbsp.repeat(count - 1)),
+        };
+        preserved.push_str(&run(leading, i > 0 || edges));
+        let mut previous = '\0';
+        for c in content.chars() {
+            if c == ' ' && previous == ' ' {
+                preserved.push_str(nbsp);
+            } else {
+                preserved.push(c);
+            }
+            previous = c;
+        }
+        preserved.push_str(&run(trailing, i + 1 < lines.len() || edges));
+    }
+    preserved
+}
+
+/// Returns the number of columns `text` takes in a monospace font
+///
+/// Wide characters are counted as two columns with the `unicode-width` feature,
+/// otherwise every character is counted as one column.
+fn display_width(text: &str) -> usize {
+    #[cfg(feature = "unicode-width")]
+    {
+        unicode_width::UnicodeWidthStr::width(text)
+    }
+    #[cfg(not(feature = "unicode-width"))]
+    {
+        text.chars().count()
+    }
+}
+
+/// Wraps lines of rendered inline `text` to `width` characters at spaces
+///
+/// Spaces inside code spans and inline links, `[text](address)`, are not broken
+/// and existing line breaks are kept. Lines are not broken before words which would
+/// start a block at the start of a line, e.g. `1.` or `>`, so these may exceed `width`.
+fn wrap(text: &str, width: usize) -> String {
+    let mut wrapped = String::with_capacity(text.len());
+    let mut previous: Option<&str> = None;
+    for line in text.split('\n') {
+        if let Some(previous) = previous {
+            // Keep trailing spaces of a hard line break
+            if previous.ends_with("  ") {
+                wrapped.push_str("  ");
+            }
+            wrapped.push('\n');
+        }
+        previous = Some(line);
+        let mut column = 0;
+        for word in unbreakable_words(line) {
+            let length = display_width(word);
+            if column > 0 && column + 1 + length > width && !starts_block(word) {
+                wrapped.push('\n');
+                column = 0;
+            } else if column > 0 {
+                wrapped.push(' ');
+                column += 1;
+            }
+            wrapped.push_str(word);
+            column += length;
+        }
+    }
+    wrapped
+}
+
+/// Returns whether `word` would start a block if it was at the start of a line
+fn starts_block(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    let marker = bytes.iter().take_while(|c| c.is_ascii_digit()).count();
+    match bytes {
+        [b'>', ..] => true,
+        [b'#', ..] => bytes.len() <= 6 && bytes.iter().all(|c| *c == b'#'),
+        [b'-' | b'+' | b'*' | b'=' | b'_', ..] => bytes.iter().all(|c| *c == bytes[0]),
+        // Code fences and HTML blocks
+        [b'`', b'`', b'`', ..] | [b'~', b'~', b'~', ..] => true,
+        [b'<', c, ..] => c.is_ascii_alphabetic() || b"/!?".contains(c),
+        _ => {
+            (1..10).contains(&marker)
+                && marker + 1 == bytes.len()
+                && (bytes[marker] == b'.' || bytes[marker] == b')')
+        }
+    }
+}
+
+/// Splits a line at spaces outside of code spans and inline links
+fn unbreakable_words(line: &str) -> Vec<&str> {
+    let bytes = line.as_bytes();
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    // Start of the text of an unescaped `[`, which may begin an inline link
+    let mut bracket = None;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'`' => {
+                let ticks = bytes[i..].iter().take_while(|c| **c == b'`').count();
+                let fence = &line[i..i + ticks];
+                i += ticks;
+                if let Some(end) = line[i..].find(fence) {
+                    i += end + ticks;
+                }
+                continue;
+            }
+            b'[' if bracket.is_none() => bracket = Some(i),
+            b']' if bracket.is_some() => {
+                if bytes.get(i + 1) == Some(&b'(') {
+                    if let Some(end) = line[i..].find(')') {
+                        i += end;
+                    }
+                }
+                bracket = None;
+            }
+            b' ' if bracket.is_none() || !closes_inline_link(&bytes[i..]) => {
+                if i > start {
+                    words.push(&line[start..i]);
+                }
+                start = i + 1;
+                bracket = None;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if start < bytes.len() {
+        words.push(&line[start..]);
+    }
+    words
+}
+
+/// Returns whether the first unescaped `]` in `text` is followed by `(`
+fn closes_inline_link(text: &[u8]) -> bool {
+    let mut escaped = false;
+    for (i, c) in text.iter().enumerate() {
+        match c {
+            b']' if !escaped => return text.get(i + 1) == Some(&b'('),
+            b'\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    false
+}
+
+/// Writes `[^label]: body` lines followed by an empty line, nothing if there are no footnotes
+///
+/// GitHub comments get a paragraph `<sup id="fn-label">label</sup> body` per footnote,
+/// the target of the references written by [FootnoteRef](struct.FootnoteRef.html).
+/// Telegram and Discord get a numbered list, numbered by the labels.
+fn write_footnote_definitions<W: Write + ?Sized>(
+    writer: &mut W,
+    footnotes: &[(usize, Vec<u8>)],
+    flavor: Flavor,
+) -> Result<(), Error> {
+    if footnotes.is_empty() {
+        return Ok(());
+    }
+    let marker = |label: usize| match flavor {
+        Flavor::CommonMark | Flavor::Gfm => format!("[^{}]: ", label),
+        Flavor::GfmComment => format!("<sup id=\"fn-{0}\">{0}</sup> ", label),
+        Flavor::Telegram => format!("{}\\. ", label),
+        Flavor::Discord => format!("{}. ", label),
+    };
+    for (label, body) in footnotes {
+        writer.write_all(marker(*label).as_bytes())?;
+        writer.write_all(body)?;
+        writer.write_all(b"\n")?;
+        if flavor == Flavor::GfmComment {
+            writer.write_all(b"\n")?;
+        }
+    }
+    if flavor == Flavor::GfmComment {
+        return Ok(());
+    }
+    writer.write_all(b"\n")
+}
+
+/// Returns `number` written in superscript digits, e.g. `¹²`
+fn superscript(number: usize) -> String {
+    number
+        .to_string()
+        .chars()
+        .map(|digit| match digit {
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            _ => char::from_u32(0x2070 + digit.to_digit(10).unwrap_or(0)).unwrap_or(digit),
+        })
+        .collect()
+}
+
+/// Writes `data` with `line_prefix` after every line break,
+/// e.g. `> ` inside of a [Blockquote](struct.Blockquote.html)
+pub fn write_line_prefixed<W: Write + ?Sized>(
     writer: &mut W,
     mut data: &[u8],
     line_prefix: Option<&[u8]>,
@@ -874,7 +9835,7 @@ fn write_line_prefixed<W: Write + ?Sized>(
             let slice_at = data.iter().position(|x| *x == b'\n');
             match slice_at {
                 Option::None => {
-                    writer.write_all(&data)?;
+                    writer.write_all(data)?;
                     break;
                 }
                 Some(slice_at) => {
@@ -888,3 +9849,149 @@ fn write_line_prefixed<W: Write + ?Sized>(
 
     Ok(())
 }
+
+/// Returns `data` without trailing spaces, e.g. a line prefix written on an empty line
+fn trim_end_spaces(data: &[u8]) -> &[u8] {
+    let end = data.iter().rposition(|c| *c != b' ').map_or(0, |i| i + 1);
+    &data[..end]
+}
+
+/// Writes `header` and block `children` with every line prefixed by `prefix`, ending with a line break
+///
+/// `children` are written inside of `nesting` if the prefix makes up a container, e.g. a block quote
+fn write_prefixed_blocks(
+    writer: &mut dyn Write,
+    prefix: &[u8],
+    header: &[u8],
+    children: &[Box<dyn '_ + MarkdownWritable>],
+    context: &RenderContext<'_>,
+    nesting: Option<Nesting>,
+) -> Result<(), Error> {
+    let line_prefix = context.line_prefix;
+    let mut prefixed = LinePrefixWriter::new(writer, prefix, line_prefix);
+    prefixed.write_all(header)?;
+    let child = RenderContext {
+        nesting,
+        ..context.child(false, context.escape, None, context.options)
+    };
+    for element in children {
+        element.write_to(&mut prefixed, &child)?;
+        // Line breaks ending a block and starting the next one separate the same two blocks
+        prefixed.end_block();
+    }
+    if !prefixed.started {
+        prefixed.writer.write_all(trim_end_spaces(prefix))?;
+    }
+    write_line_prefixed(prefixed.writer, b"\n", line_prefix)
+}
+
+/// Writer adapter prefixing every line with `prefix`
+///
+/// Line breaks are held back until more text follows, so no prefix is left dangling
+/// after the last line and leading and trailing blank lines are dropped.
+struct LinePrefixWriter<'w> {
+    writer: &'w mut dyn Write,
+    prefix: &'w [u8],
+    line_prefix: Option<&'w [u8]>,
+    started: bool,
+    pending_lines: usize,
+    /// Line breaks held back at the end of the previous block
+    block_end_lines: usize,
+}
+
+impl<'w> LinePrefixWriter<'w> {
+    fn new(writer: &'w mut dyn Write, prefix: &'w [u8], line_prefix: Option<&'w [u8]>) -> Self {
+        Self {
+            writer,
+            prefix,
+            line_prefix,
+            started: false,
+            pending_lines: 0,
+            block_end_lines: 0,
+        }
+    }
+
+    /// Ends a block, line breaks starting the next block are merged with the held back ones
+    fn end_block(&mut self) {
+        self.block_end_lines = self.block_end_lines.max(self.pending_lines);
+        self.pending_lines = 0;
+    }
+
+    fn start_line(&mut self) -> Result<(), Error> {
+        self.pending_lines = self.pending_lines.max(self.block_end_lines);
+        self.block_end_lines = 0;
+        if self.started {
+            write_line_prefixed(self.writer, b"\n", self.line_prefix)?;
+            for _ in 1..self.pending_lines {
+                self.writer.write_all(trim_end_spaces(self.prefix))?;
+                write_line_prefixed(self.writer, b"\n", self.line_prefix)?;
+            }
+        }
+        self.started = true;
+        self.pending_lines = 0;
+        self.writer.write_all(self.prefix)
+    }
+}
+
+impl Write for LinePrefixWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let mut rest = buf;
+        while let Some(&first) = rest.first() {
+            if first == b'\n' {
+                self.pending_lines += 1;
+                rest = &rest[1..];
+                continue;
+            }
+            if !self.started || self.pending_lines > 0 || self.block_end_lines > 0 {
+                self.start_line()?;
+            }
+            let end = rest.iter().position(|b| *b == b'\n').unwrap_or(rest.len());
+            self.writer.write_all(&rest[..end])?;
+            rest = &rest[end..];
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()
+    }
+}
+
+/// Escapes characters with a special meaning in HTML
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Writer adapter counting written bytes
+struct CountingWriter<'w> {
+    writer: &'w mut dyn Write,
+    count: usize,
+}
+
+impl<'w> CountingWriter<'w> {
+    fn new(writer: &'w mut dyn Write) -> Self {
+        Self { writer, count: 0 }
+    }
+}
+
+impl Write for CountingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let written = self.writer.write(buf)?;
+        self.count += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush()
+    }
+}
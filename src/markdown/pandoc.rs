@@ -0,0 +1,235 @@
+//! Backend writing elements as Pandoc JSON AST instead of markdown text
+//!
+//! Available with the `pandoc` feature.
+//! The output can be read by `pandoc --from json`.
+
+use super::{
+    Alignment, Attributes, Escaping, MarkdownWritable, Options, RenderContext, TextRole,
+    WarningKind,
+};
+use serde_json::{json, Value};
+use std::io;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Version of the Pandoc AST the output conforms to
+pub const API_VERSION: [u32; 3] = [1, 23, 1];
+
+/// Struct for generating Pandoc JSON AST
+///
+/// Elements are collected by [write](#method.write)
+/// and the whole document is written by [finish](#method.finish).
+pub struct Pandoc<W: Write> {
+    writer: W,
+    options: Options,
+    blocks: Vec<Value>,
+}
+
+impl<W: Write> Pandoc<W> {
+    /// Creates a new [Pandoc](struct.Pandoc.html) struct
+    ///
+    /// # Arguments
+    /// * `writer` - Destination for JSON data
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            options: Options::new(),
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Sets a transform applied to text with `role`,
+    /// see [Markdown::with_transform](../struct.Markdown.html#method.with_transform)
+    pub fn with_transform<F: 'static + Fn(&str) -> String + Send + Sync>(
+        mut self,
+        role: TextRole,
+        transform: F,
+    ) -> Self {
+        self.options.set_transform(role, Arc::new(transform));
+        self
+    }
+
+    /// Sets a callback rewriting every link and image address,
+    /// see [Markdown::with_link_rewriter](../struct.Markdown.html#method.with_link_rewriter)
+    pub fn with_link_rewriter<F: 'static + Fn(&str) -> String + Send + Sync>(
+        mut self,
+        rewriter: F,
+    ) -> Self {
+        self.options.set_link_rewriter(Arc::new(rewriter));
+        self
+    }
+
+    /// Adds a [MarkdownWritable](../trait.MarkdownWritable.html) to the document
+    pub fn write<T: MarkdownWritable>(&mut self, element: T) {
+        self.blocks.extend(element.pandoc_blocks(&self.options));
+    }
+
+    /// Writes the document and returns the underlying `writer`
+    ///
+    /// # Returns
+    /// `writer` or `std::io::Error` if an error occurred during writing
+    pub fn finish(mut self) -> Result<W, io::Error> {
+        let document = json!({
+            "pandoc-api-version": API_VERSION,
+            "meta": {},
+            "blocks": self.blocks,
+        });
+        serde_json::to_writer(&mut self.writer, &document)?;
+        Ok(self.writer)
+    }
+}
+
+/// Attributes `["", classes, []]` without an identifier and key-value pairs
+pub(super) fn attr(classes: &[&str]) -> Value {
+    json!(["", classes, []])
+}
+
+/// Converts `attributes` to `[id, classes, pairs]`
+pub(super) fn attributes(attributes: &Attributes) -> Value {
+    let pairs: Vec<[&str; 2]> = attributes
+        .pairs
+        .iter()
+        .map(|(key, value)| [key.as_ref(), value.as_ref()])
+        .collect();
+    json!([attributes.get_id().unwrap_or(""), attributes.classes, pairs])
+}
+
+/// Splits `text` into `Str`, `Space` and `SoftBreak` inlines
+pub(super) fn text(text: &str) -> Vec<Value> {
+    let mut inlines = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !word.is_empty() {
+                inlines.push(json!({"t": "Str", "c": word}));
+                word = String::new();
+            }
+            let inline = if c == '\n' {
+                json!({"t": "SoftBreak"})
+            } else {
+                json!({"t": "Space"})
+            };
+            match inlines.last() {
+                Some(last) if last["t"] == "Space" && c != '\n' => {}
+                Some(last) if last["t"] == "Space" => *inlines.last_mut().unwrap() = inline,
+                _ => inlines.push(inline),
+            }
+        } else {
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        inlines.push(json!({"t": "Str", "c": word}));
+    }
+    inlines
+}
+
+/// Writes `element` as markdown wrapped in a `RawInline` or `RawBlock`
+pub(super) fn raw<T: MarkdownWritable + ?Sized>(
+    element: &T,
+    inner: bool,
+    options: &Options,
+) -> Vec<Value> {
+    let mut markdown = Vec::new();
+    if let Err(error) = element.write_to(
+        &mut markdown,
+        &RenderContext::new(inner, Escaping::Normal, None, options),
+    ) {
+        options.warn(
+            WarningKind::Degraded,
+            format!("element left out of the Pandoc AST: {}", error),
+        );
+        return Vec::new();
+    }
+    let markdown = String::from_utf8_lossy(&markdown);
+    let markdown = markdown.trim_end_matches('\n');
+    if markdown.is_empty() {
+        return Vec::new();
+    }
+    let kind = if inner { "RawInline" } else { "RawBlock" };
+    vec![json!({"t": kind, "c": ["markdown", markdown]})]
+}
+
+/// Turns paragraphs of a tight list item or of a table cell into `Plain` blocks
+pub(super) fn plain(blocks: Vec<Value>) -> Vec<Value> {
+    blocks
+        .into_iter()
+        .map(|mut block| {
+            if block["t"] == "Para" {
+                block["t"] = json!("Plain");
+            }
+            block
+        })
+        .collect()
+}
+
+/// Prepends a `☐` or `☒` checkbox to the blocks of a task list item like Pandoc does
+pub(super) fn task(checked: bool, mut blocks: Vec<Value>) -> Vec<Value> {
+    let checkbox = [
+        json!({"t": "Str", "c": if checked { "☒" } else { "☐" }}),
+        json!({"t": "Space"}),
+    ];
+    match blocks.first_mut() {
+        Some(block) if block["t"] == "Plain" => {
+            if let Some(inlines) = block["c"].as_array_mut() {
+                inlines.splice(0..0, checkbox);
+            }
+        }
+        _ => blocks.insert(0, json!({"t": "Plain", "c": checkbox})),
+    }
+    blocks
+}
+
+/// Creates a `BulletList` or an `OrderedList` numbered from 1 of `items`
+pub(super) fn list(numbered: bool, items: Vec<Vec<Value>>) -> Value {
+    if numbered {
+        json!({"t": "OrderedList", "c": [[1, {"t": "Decimal"}, {"t": "Period"}], items]})
+    } else {
+        json!({"t": "BulletList", "c": items})
+    }
+}
+
+/// Creates a `Table` without a caption from blocks of the header and data cells
+///
+/// Rows are filled with empty cells up to the number of `alignments`.
+pub(super) fn table(
+    alignments: &[Alignment],
+    header: Vec<Vec<Value>>,
+    rows: Vec<Vec<Vec<Value>>>,
+) -> Value {
+    let columns = alignments.len();
+    let row = |mut cells: Vec<Vec<Value>>| {
+        cells.resize(columns, Vec::new());
+        let cells: Vec<Value> = cells
+            .into_iter()
+            .map(|blocks| json!([attr(&[]), {"t": "AlignDefault"}, 1, 1, blocks]))
+            .collect();
+        json!([attr(&[]), cells])
+    };
+    let specs: Vec<Value> = alignments
+        .iter()
+        .map(|alignment| {
+            let alignment = match alignment {
+                Alignment::None => "AlignDefault",
+                Alignment::Left => "AlignLeft",
+                Alignment::Center => "AlignCenter",
+                Alignment::Right => "AlignRight",
+            };
+            json!([{"t": alignment}, {"t": "ColWidthDefault"}])
+        })
+        .collect();
+    let head: Vec<Value> = if header.is_empty() {
+        Vec::new()
+    } else {
+        vec![row(header)]
+    };
+    let body: Vec<Value> = rows.into_iter().map(row).collect();
+    json!({"t": "Table", "c": [
+        attr(&[]),
+        [null, []],
+        specs,
+        [attr(&[]), head],
+        [[attr(&[]), 0, [], body]],
+        [attr(&[]), []],
+    ]})
+}
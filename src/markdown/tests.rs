@@ -1,5 +1,38 @@
 use super::Markdown;
-use crate::markdown::{AsMarkdown, List};
+use crate::markdown::io::{Error, ErrorKind, Write};
+use crate::markdown::{
+    needs_escape, slugify, title_case, Admonition, Alert, AlertKind, Alignment, AnchorNamespace,
+    AsMarkdown, Bar, BarStyle, BlockElement, Blockquote, Bullet, CaseStyle, Change, Citation,
+    CodeBlock, Collapsible, ColumnLengthError, ColumnTable, ControlChars, CustomElement, Directive,
+    Div, Document, DocumentDiff, Embed, Emphasis, EmphasisStyle, EscapeContext, Escaping,
+    ExampleList, ExampleRef, Fanout, Faq, FieldList, Flavor, FmtWriter, Footer, FrontMatter,
+    FrontMatterValue, Glossary, Heading, HeadingCase, HeadingPath, Html, Image, Index,
+    InlineElement, KeyOrder, LineBreak, LineBreakStyle, Link, List, LogseqBlock, LongLinks,
+    MarkdownPool, MarkdownWritable, MathBlock, MathInline, Nesting, Options, Paragraph, Quote, Raw,
+    RenderContext, RichText, SectionChange, SourceMapEntry, Span, Statistics, Summary,
+    SummaryTable, TabPolicy, Table, TableStyle, TaskKeyword, TextRole, Warning, WarningKind,
+    WhitespacePolicy, WithAttributes,
+};
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Element which always fails to render, e.g. a custom element with invalid content
+struct Failing;
+
+impl CustomElement for Failing {
+    fn write(&self, _writer: &mut dyn Write, _context: &RenderContext<'_>) -> Result<(), Error> {
+        Err(Error::new(ErrorKind::InvalidData, "failing element"))
+    }
+}
+
+impl InlineElement for Failing {}
+
+impl BlockElement for Failing {}
 
 //region Heading
 #[test]
@@ -25,12 +58,19 @@ fn headings() {
 }
 
 #[test]
-#[should_panic]
-fn panic_on_inner_heading() {
-    let mut md = Markdown::new(Vec::new());
-
-    md.write("h1".paragraph().append("this should panic".heading(1)))
-        .unwrap();
+fn error_on_inner_heading() {
+    // Appending a heading to a paragraph or writing it by `write_inner` does not compile,
+    // a heading written as inner directly fails
+    let options = Options::new();
+    let context = RenderContext::new(true, Escaping::Normal, None, &options);
+    assert_eq!(
+        "inner heading"
+            .heading(1)
+            .write_to(&mut Vec::new(), &context)
+            .unwrap_err()
+            .kind(),
+        ErrorKind::InvalidInput
+    );
 }
 
 #[test]
@@ -42,6 +82,291 @@ fn heading_append() {
         "# h1appended\n"
     );
 }
+
+#[test]
+fn heading_clamped() {
+    let mut md = Markdown::new(Vec::new());
+    md.write("root".heading_clamped(0)).unwrap();
+    md.write("deep".heading_clamped(9)).unwrap();
+    md.write(Heading::clamped(7).append("deeper")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "# root\n###### deep\n###### deeper\n"
+    );
+}
+
+#[test]
+fn heading_suffix() {
+    let heading = Heading::new(2)
+        .append("GET /users")
+        .suffix("deprecated".code());
+    assert_eq!(heading.anchor(), "get-users");
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(&heading).unwrap();
+    md.write(Link::to_heading(&heading).append("Users"))
+        .unwrap();
+    md.write_toc().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "<a id=\"get-users\"></a>\n## GET /users ` deprecated `\n\
+         [Users](#get-users)\n\
+         - [GET /users](#get-users)\n\n"
+    );
+}
+
+#[test]
+fn heading_id() {
+    let heading = Heading::new(2)
+        .append("Getting started")
+        .id("setup")
+        .class("unnumbered");
+    assert_eq!(heading.anchor(), "setup");
+    assert_eq!(heading.attributes().unwrap().get_id(), Some("setup"));
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(&heading).unwrap();
+    md.write(Link::to_heading(&heading).append("Setup"))
+        .unwrap();
+    md.write(Heading::new(3).append("API").suffix("v2").id("api"))
+        .unwrap();
+    md.write(Heading::new(3).append("Plain")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "## Getting started {#setup .unnumbered}\n\
+         [Setup](#setup)\n\
+         ### API v2 {#api}\n\
+         ### Plain\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Telegram);
+    md.write(&heading).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "*Getting started*\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Gfm);
+    md.write(&heading).unwrap();
+    md.write(Heading::new(2).append("Getting started")).unwrap();
+    md.write_toc().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "<a id=\"setup\"></a>\n\
+         ## Getting started\n\
+         ## Getting started\n\
+         - [Getting started](#setup)\n\
+         - [Getting started](#getting-started-1)\n\n"
+    );
+}
+
+#[test]
+fn heading_numbers() {
+    let mut md = Markdown::new(Vec::new()).with_heading_numbers();
+    md.write(Heading::new(1).append("Preface").unnumbered())
+        .unwrap();
+    md.write(Heading::new(1).append("Scope")).unwrap();
+    md.write(Heading::new(2).append("Terms")).unwrap();
+    md.write(Blockquote::new().append(Heading::new(3).append("Notation")))
+        .unwrap();
+    md.write(Heading::new(1).append("Requirements")).unwrap();
+    md.write(Heading::new(2).append("Syntax")).unwrap();
+    md.reset_heading_numbers();
+    md.write(Heading::new(1).append("Appendix")).unwrap();
+    md.write_toc().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "# Preface\n\
+         # 1\\. Scope {#scope}\n\
+         ## 1\\.1 Terms {#terms}\n\
+         > ### 1\\.1\\.1 Notation {#notation}\n\n\
+         # 2\\. Requirements {#requirements}\n\
+         ## 2\\.1 Syntax {#syntax}\n\
+         # 1\\. Appendix {#appendix}\n\
+         - [Preface](#preface)\n\
+         - [1\\. Scope](#scope)\n\
+         \x20 - [1\\.1 Terms](#terms)\n\
+         \x20   - [1\\.1\\.1 Notation](#notation)\n\
+         - [2\\. Requirements](#requirements)\n\
+         \x20 - [2\\.1 Syntax](#syntax)\n\
+         - [1\\. Appendix](#appendix)\n\n"
+    );
+
+    // Links to headings use the anchor without the number
+    let usage = Heading::new(2).append("Usage");
+    let mut md = Markdown::new(Vec::new())
+        .with_flavor(Flavor::Gfm)
+        .with_heading_numbers();
+    md.write(&usage).unwrap();
+    md.write(Link::to_heading(&usage).append("usage")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "<a id=\"usage\"></a>\n## 1\\. Usage\n[usage](#usage)\n"
+    );
+
+    assert!(Heading::new(2)
+        .append(Failing)
+        .headings(&Options::new())
+        .is_empty());
+
+    let mut document = Document::new();
+    document.push(Heading::new(2).append("Usage"));
+    document.push(Heading::new(3).append("Options"));
+    document.set_heading_numbers(true);
+    document.insert(0, document.toc().unwrap());
+    document.push(Failing);
+    assert!(document.toc().is_err());
+    document.remove(document.len() - 1);
+    assert_eq!(
+        document
+            .render(Vec::new())
+            .map(String::from_utf8)
+            .unwrap()
+            .unwrap(),
+        "- [1\\. Usage](#usage)\n\
+         \x20 - [1\\.1 Options](#options)\n\n\
+         ## 1\\. Usage {#usage}\n\
+         ### 1\\.1 Options {#options}\n"
+    );
+}
+
+#[test]
+fn sections() {
+    fn details(md: &mut Markdown<Vec<u8>>) -> Result<(), Error> {
+        md.section("Details", |md| md.write("Text".paragraph()))
+    }
+
+    let mut md = Markdown::new(Vec::new());
+    md.section("Report", |md| {
+        details(md)?;
+        md.section("Summary", |md| {
+            assert_eq!(md.section_level(), 3);
+            details(md)
+        })
+    })
+    .unwrap();
+    details(&mut md).unwrap();
+    md.begin_section("Appendix").unwrap();
+    md.begin_section("Data").unwrap();
+    md.end_section();
+    md.end_section();
+    assert_eq!(md.section_level(), 1);
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "# Report\n\
+         ## Details\n\
+         Text\n\n\
+         ## Summary\n\
+         ### Details\n\
+         Text\n\n\
+         # Details\n\
+         Text\n\n\
+         # Appendix\n\
+         ## Data\n"
+    );
+}
+
+#[test]
+fn write_nested() {
+    let mut fragment = Document::new();
+    fragment.push("Findings".heading(1));
+    fragment.push("Text".paragraph());
+    fragment.push("Details".heading(6));
+
+    let mut md = Markdown::new(Vec::new());
+    md.section("Report", |md| {
+        let offset = md.section_level() - 1;
+        md.write_nested(&fragment, offset)
+    })
+    .unwrap();
+    md.write_toc().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "# Report\n\
+         ## Findings\n\
+         Text\n\n\
+         ###### Details\n\
+         - [Report](#report)\n\
+         \x20 - [Findings](#findings)\n\
+         \x20   - [Details](#details)\n\n"
+    );
+}
+
+#[test]
+fn toc_rewrites_links_once() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    let mut md = Markdown::new(Vec::new()).with_link_rewriter(move |address| {
+        counted.fetch_add(1, Ordering::Relaxed);
+        address.to_uppercase()
+    });
+    md.write(Heading::new(1).append("Docs".link_to("a.html")))
+        .unwrap();
+    md.write_toc().unwrap();
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn heading_path() {
+    use std::sync::{Arc, Mutex};
+
+    let links = Arc::new(Mutex::new(Vec::new()));
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let md = Markdown::new(Vec::new());
+    let path = md.heading_path();
+    let (collected, collected_warnings) = (links.clone(), warnings.clone());
+    let mut md = md
+        .with_link_rewriter(move |address| {
+            collected
+                .lock()
+                .unwrap()
+                .push(format!("{} in {}", address, path));
+            address.to_string()
+        })
+        .with_warning_handler(move |warning: &Warning| {
+            collected_warnings.lock().unwrap().push(warning.to_string())
+        });
+    md.write("Install".heading(1)).unwrap();
+    md.write("Linux".heading(2)).unwrap();
+    md.write("Arch".heading(3)).unwrap();
+    md.write(Link::new("arch.md").append("wiki")).unwrap();
+    md.write("Windows".heading(2)).unwrap();
+    md.write(Link::new("win.md")).unwrap();
+    assert_eq!(md.heading_path().titles(), ["Install", "Windows"]);
+    assert_eq!(
+        *links.lock().unwrap(),
+        [
+            "arch.md in Install > Linux > Arch",
+            "win.md in Install > Windows"
+        ]
+    );
+    assert_eq!(
+        *warnings.lock().unwrap(),
+        ["empty link text in Install > Windows"]
+    );
+
+    // Numbers are not part of the path and heading links are rewritten once
+    let rewritten = Arc::new(Mutex::new(0));
+    let counter = rewritten.clone();
+    let mut md = Markdown::new(Vec::new())
+        .with_heading_numbers()
+        .with_link_rewriter(move |address| {
+            *counter.lock().unwrap() += 1;
+            address.to_string()
+        });
+    md.write(
+        Heading::new(1)
+            .append("See ")
+            .append("docs".link_to("docs.md")),
+    )
+    .unwrap();
+    md.write("Setup".heading(2)).unwrap();
+    assert_eq!(md.heading_path().titles()[1], "Setup");
+    assert_eq!(*rewritten.lock().unwrap(), 1);
+}
 //endregion
 
 //region Paragraph
@@ -74,6 +399,105 @@ fn paragraph_append() {
         \n"
     );
 }
+
+#[test]
+fn paragraph_from_sentences() {
+    let mut md = Markdown::new(Vec::new());
+
+    md.write(Paragraph::from_sentences(vec![
+        "Build finished ",
+        "",
+        ", with  3 warnings",
+        "(see",
+        "log)",
+        ".",
+        " Tests passed.",
+        ".",
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Build finished, with 3 warnings \\(see log\\)\\. Tests passed\\.\n\
+        \n"
+    );
+}
+
+#[test]
+fn inline_change() {
+    let changes = || {
+        Paragraph::new()
+            .append("timeout: ")
+            .append(Change::new("30", "60"))
+            .append(", retries: ")
+            .append(Change::added("3"))
+            .append(", proxy: ")
+            .append(Change::removed("none"))
+            .append(", port: ")
+            .append(Change::new("80", "80"))
+    };
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Gfm);
+    md.write(changes()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "timeout: ~~30~~ **60**, retries: **3**, proxy: ~~none~~, port: 80\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(changes()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "timeout: <del>30</del> <ins>60</ins>, retries: <ins>3</ins>, proxy: <del>none</del>, port: 80\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Telegram);
+    md.write(Change::new("a", "b")).unwrap();
+    assert_eq!(String::from_utf8(md.into_inner()).unwrap(), "~a~ *b*\n\n");
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Gfm);
+    md.write(Change::new("", "enabled")).unwrap();
+    md.write(Change::new(" 30", "60 ")).unwrap();
+    md.write(Change::new("x", "  ")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "**enabled**\n\n~~30~~ **60**\n\n~~x~~\n\n"
+    );
+}
+
+#[test]
+fn line_breaks() {
+    let address = || {
+        "Jane Doe"
+            .paragraph()
+            .line_break()
+            .append("Main Street 1")
+            .append(LineBreak)
+            .append("Springfield")
+    };
+    let mut md = Markdown::new(Vec::new());
+    md.write(address()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Jane Doe\\\nMain Street 1\\\nSpringfield\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new())
+        .with_line_break_style(LineBreakStyle::Spaces)
+        .with_line_width(10, LongLinks::OwnLine);
+    md.write(address()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Jane Doe  \nMain\nStreet 1  \nSpringfield\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Discord);
+    md.write(address()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Jane Doe\nMain Street 1\nSpringfield\n\n"
+    );
+}
 //endregion
 
 //region String
@@ -107,7 +531,7 @@ fn link() {
     md.write("test link".link_to("https://test.url")).unwrap();
     assert_eq!(
         String::from_utf8(md.into_inner()).unwrap(),
-        "[test link](https://test\\.url)\n"
+        "[test link](https://test.url)\n"
     );
 }
 
@@ -118,7 +542,22 @@ fn link_escaping() {
         .unwrap();
     assert_eq!(
         String::from_utf8(md.into_inner()).unwrap(),
-        "[\\[\\]\\[\\]test \\[\\] link\\[\\]\\[\\]](https://test\\(\\)\\.url\\(\\))\n"
+        "[\\[\\]\\[\\]test \\[\\] link\\[\\]\\[\\]](https://test().url())\n"
+    );
+}
+
+#[test]
+fn link_address_encoding() {
+    let mut md = Markdown::new(Vec::new());
+    md.write("spaces".link_to("docs/My File.md")).unwrap();
+    md.write("parens".link_to("https://example.com/a_(b"))
+        .unwrap();
+    md.write("angles".link_to("x<y>.html")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "[spaces](docs/My%20File.md)\n\
+         [parens](https://example.com/a_%28b)\n\
+         [angles](x%3Cy%3E.html)\n"
     );
 }
 
@@ -134,7 +573,7 @@ fn link_append() {
     .unwrap();
     assert_eq!(
         String::from_utf8(md.into_inner()).unwrap(),
-        "[test link appended](https://test\\.url)\n"
+        "[test link appended](https://test.url)\n"
     );
 }
 //endregion
@@ -150,6 +589,20 @@ fn code() {
     );
 }
 
+#[test]
+fn code_ticks() {
+    let mut md = Markdown::new(Vec::new());
+    md.write("a``b`".code()).unwrap();
+    let mut md = Markdown::new(md.into_inner()).with_tab_policy(TabPolicy::Expand(2));
+    md.write("``\t`".code()).unwrap();
+    let mut md = Markdown::new(md.into_inner()).with_control_chars(ControlChars::Strip);
+    md.write("x\u{1b}[1`".code()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "```` a``b` ````\n\n```` ``  ` ````\n\n` x `\n\n"
+    );
+}
+
 #[test]
 fn bold() {
     let mut md = Markdown::new(Vec::new());
@@ -232,6 +685,46 @@ fn list() {
         "\n   1. item 1\n   1. **bold**\n   1. nested list\n      * **bold***italic*"
     );
 }
+
+#[test]
+fn list_from_iterator() {
+    let names = ["alpha", "beta"];
+    let mut bulleted: List = names.iter().map(|name| name.bold()).collect();
+    bulleted.extend(vec!["gamma"]);
+    let numbered = List::from_items(true, names.iter().copied());
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(bulleted).unwrap();
+    md.write(numbered).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n   * **alpha**\n   * **beta**\n   * gamma\n   1. alpha\n   1. beta"
+    );
+}
+
+#[test]
+fn list_style() {
+    let list = || {
+        List::new(false).item("item").item(
+            List::new(false)
+                .title("nested")
+                .item(List::new(true).title("numbered").item("deep")),
+        )
+    };
+    let mut md = Markdown::new(Vec::new()).with_list_style(Bullet::Dash, 2);
+    md.write(list()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n- item\n- nested\n  - numbered\n    1. deep"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_list_style(Bullet::Plus, 4);
+    md.write(list()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n+ item\n+ nested\n    + numbered\n        1. deep"
+    );
+}
 //endregion
 
 //region Quote
@@ -257,7 +750,32 @@ fn quote() {
     .unwrap();
     assert_eq!(
         String::from_utf8(md.into_inner()).unwrap(),
-        "\n>**bold quote**\n\n\n>` code quote `\n\n\n>test [link](sample\\.url)\n\n\n>quoted list\n>   1. item\n>   1. nested quoted list\n>      * >**bold item quote**\n>      * [test](sample\\.url)\n\n"
+        "\n>**bold quote**\n\n\n>` code quote `\n\n\n>test [link](sample.url)\n\n\n>quoted list\n>   1. item\n>   1. nested quoted list\n>      * >**bold item quote**\n>      * [test](sample.url)\n\n"
+    );
+}
+//endregion
+
+//region Glossary
+#[test]
+fn glossary() {
+    let mut glossary = Glossary::new()
+        .term("crate", "https://crates.io")
+        .term("Rust crate", "https://rust-lang.org")
+        .term("lib", "#lib");
+    let mut md = Markdown::new(Vec::new());
+    md.write(glossary.link_terms("A Rust crate is a crate, library is not a lib."))
+        .unwrap();
+    md.write(glossary.link_terms("Another lib.")).unwrap();
+    glossary.section();
+    md.write(glossary.link_terms("crates and crate")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "A [Rust crate](https://rust-lang.org) is a [crate](https://crates.io), library is not a [lib](#lib)\\.\n\
+        \n\
+        Another lib\\.\n\
+        \n\
+        crates and [crate](https://crates.io)\n\
+        \n"
     );
 }
 //endregion
@@ -270,7 +788,7 @@ fn link_as_heading() {
         .unwrap();
     assert_eq!(
         String::from_utf8(md.into_inner()).unwrap(),
-        "## [test link](https://test\\.url)\n"
+        "## [test link](https://test.url)\n"
     );
 }
 
@@ -312,3 +830,3189 @@ fn unicode() {
     );
 }
 //endregion
+
+//region Index
+#[test]
+fn index() {
+    let mut index = Index::new();
+    let mut md = Markdown::new(Vec::new());
+    md.write("Rust".paragraph().append(index.mark("Rust")))
+        .unwrap();
+    md.write("crate".paragraph().append(index.mark("crate")))
+        .unwrap();
+    md.write("Rust again".paragraph().append(index.mark("Rust")))
+        .unwrap();
+    md.write(&index).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Rust<a id=\"index-1\"></a>\n\
+        \n\
+        crate<a id=\"index-2\"></a>\n\
+        \n\
+        Rust again<a id=\"index-3\"></a>\n\
+        \n\
+        \n   * crate: [1](#index-2)\n   * Rust: [1](#index-1), [2](#index-3)"
+    );
+}
+//endregion
+
+//region Table
+#[test]
+fn table() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Table::new()
+            .header(vec!["name", "value"])
+            .alignment(vec![Alignment::Left, Alignment::Right])
+            .row(vec!["a|b".bold(), "1".code()])
+            .row(vec!["c".bold()]),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| name | value |\n\
+        | :--- | ---: |\n\
+        | **a\\|b** | ` 1 ` |\n\
+        | **c** |  |\n\
+        \n"
+    );
+}
+
+#[test]
+fn table_in_list() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(List::new(false).item(Table::new().header(vec!["a"]).row(vec!["b"])))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n   * | a |\n   | --- |\n   | b |\n   "
+    );
+}
+
+#[test]
+fn table_limit() {
+    let rows = (1..=1236).map(|i| vec![i.to_string()]);
+    let table = Table::new().header(vec!["n"]).limit(2).rows(rows);
+    let mut md = Markdown::new(Vec::new());
+    md.write(&table).unwrap();
+    md.write(
+        Table::new()
+            .header(vec!["n"])
+            .rows(vec![vec!["1"], vec!["2"]])
+            .limit(1)
+            .more_link("full.csv"),
+    )
+    .unwrap();
+    md.write(
+        Table::new()
+            .header(vec!["n"])
+            .rows(vec![vec!["1"], vec!["2"], vec!["3"]])
+            .limit(1)
+            .omitted_template("… und {count} weitere Zeilen"),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| n |\n| --- |\n| 1 |\n| 2 |\n\n*… and 1,234 more rows*\n\n\
+         | n |\n| --- |\n| 1 |\n\n[*… and 1 more row*](full.csv)\n\n\
+         | n |\n| --- |\n| 1 |\n\n*… und 2 weitere Zeilen*\n\n"
+    );
+}
+
+#[test]
+fn warning_handler() {
+    use std::sync::{Arc, Mutex};
+
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let collected = warnings.clone();
+    let document_id = "report-7";
+    let mut md = Markdown::new(Vec::new())
+        .with_flavor(Flavor::Telegram)
+        .with_control_chars(ControlChars::Strip)
+        .with_warning_handler(move |warning: &Warning| {
+            collected
+                .lock()
+                .unwrap()
+                .push((document_id, warning.kind, warning.message.clone()))
+        });
+    md.write("Title".heading(2)).unwrap();
+    md.write("bell\u{7}").unwrap();
+    md.write("").unwrap();
+    md.write(
+        Table::new()
+            .header(vec!["n"])
+            .rows(vec![vec!["1"], vec!["2"]])
+            .limit(1),
+    )
+    .unwrap();
+    md.write(Footer::new("gen").comment()).unwrap();
+    assert_eq!(
+        *warnings.lock().unwrap(),
+        [
+            (
+                "report-7",
+                WarningKind::Degraded,
+                "heading level 2 written as bold text".to_string()
+            ),
+            (
+                "report-7",
+                WarningKind::LossyEscaping,
+                "control characters removed".to_string()
+            ),
+            ("report-7", WarningKind::Empty, "empty text".to_string()),
+            (
+                "report-7",
+                WarningKind::Degraded,
+                "table written as a code block".to_string()
+            ),
+            (
+                "report-7",
+                WarningKind::Truncated,
+                "1 table row over the limit omitted".to_string()
+            ),
+            (
+                "report-7",
+                WarningKind::Degraded,
+                "footer comment written as italic text".to_string()
+            ),
+        ]
+    );
+
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let collected = warnings.clone();
+    let mut md = Markdown::new(Vec::new())
+        .with_flavor(Flavor::Discord)
+        .with_control_chars(ControlChars::Strip)
+        .with_warning_handler(move |warning: &Warning| {
+            collected.lock().unwrap().push(warning.message.clone())
+        });
+    md.write("Bell\u{7}".heading(2)).unwrap();
+    md.write(MathInline::new("x^2")).unwrap();
+    md.write(MathBlock::new("x^2")).unwrap();
+    md.write(Citation::new("Dune")).unwrap();
+    md.write(Faq::new().entry("Why?", "Because.")).unwrap();
+    md.write(
+        Table::new()
+            .header(["user"])
+            .row(["jane"])
+            .row_anchors(0, "user-"),
+    )
+    .unwrap();
+    let note = md.footnote("Note").unwrap();
+    md.write(note).unwrap();
+    assert_eq!(
+        *warnings.lock().unwrap(),
+        [
+            "control characters removed",
+            "math written as inline code",
+            "math written as a code block",
+            "citation written as italic text",
+            "FAQ written as headings",
+            "row anchors dropped",
+            "footnote written as a superscript",
+        ]
+    );
+}
+
+#[test]
+fn table_writer() {
+    let mut md = Markdown::new(Vec::new()).with_source_map();
+    let mut table = md
+        .begin_table(vec!["Id", "Name"])
+        .unwrap()
+        .alignment(vec![Alignment::Right]);
+    for id in 1..=3 {
+        table
+            .row(vec![id.to_string(), format!("item {}", id)])
+            .unwrap();
+    }
+    table.row(vec!["4"]).unwrap();
+    table.row(vec!["5", "five", "ignored"]).unwrap();
+    table.finish().unwrap();
+    md.begin_table(vec!["Empty"]).unwrap().finish().unwrap();
+    assert_eq!(
+        md.begin_table(Vec::<&str>::new()).err().unwrap().kind(),
+        ErrorKind::InvalidInput
+    );
+    let output = "| Id | Name |\n| ---: | --- |\n| 1 | item 1 |\n| 2 | item 2 |\n| 3 | item 3 |\n\
+         | 4 |  |\n| 5 | five |\n\n";
+    assert_eq!(md.source_map()[0].range, 0..output.len());
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        format!("{}| Empty |\n| --- |\n\n", output)
+    );
+}
+
+#[test]
+fn table_row_anchors() {
+    let table = || {
+        Table::new()
+            .header(vec!["User", "Role"])
+            .row(vec!["Jane Doe", "admin"])
+            .row(vec!["", "guest"])
+            .row(vec!["Jane Doe", "*owner*"])
+            .row_anchors(0, "user-")
+    };
+    let mut md = Markdown::new(Vec::new());
+    md.write(table()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| User | Role |\n\
+         | --- | --- |\n\
+         | <a id=\"user-jane-doe\"></a>Jane Doe | admin |\n\
+         |  | guest |\n\
+         | <a id=\"user-jane-doe-1\"></a>Jane Doe | \\*owner\\* |\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Discord);
+    md.write(table().row_anchors(1, "")).unwrap();
+    assert!(!String::from_utf8(md.into_inner())
+        .unwrap()
+        .contains("<a id"));
+}
+
+#[test]
+fn table_aligned() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Table::new()
+            .header(vec!["Name", "Count", "State"])
+            .alignment(vec![Alignment::Left, Alignment::Right, Alignment::Center])
+            .row(vec!["a|b", "1", "ok"])
+            .row(vec!["c", "1024", "failed"])
+            .aligned(),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| Name | Count | State  |\n\
+         | :--- | ----: | :----: |\n\
+         | a\\|b |     1 |   ok   |\n\
+         | c    |  1024 | failed |\n\n"
+    );
+
+    #[cfg(feature = "unicode-width")]
+    {
+        let mut md = Markdown::new(Vec::new());
+        md.write(
+            Table::new()
+                .header(vec!["名前", "x"])
+                .row(vec!["ab", "日本"])
+                .aligned(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(md.into_inner()).unwrap(),
+            "| 名前 | x    |\n| ---- | ---- |\n| ab   | 日本 |\n\n"
+        );
+    }
+}
+
+#[cfg(feature = "csv")]
+#[test]
+fn table_from_csv() {
+    let csv = "name,status,notes\n\
+               build,ok,\"a|b\"\n\
+               \"lint *all*\",failed,\"two\nlines\"\n\
+               docs,skipped\n";
+    let mut md = Markdown::new(Vec::new());
+    md.write(Table::from_csv_reader(csv.as_bytes()).unwrap())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| name | status | notes |\n| --- | --- | --- |\n\
+         | build | ok | a\\|b |\n\
+         | lint \\*all\\* | failed | two<br>lines |\n\
+         | docs | skipped |  |\n\n"
+    );
+    assert!(Table::from_csv_reader(&b"a\n\xff"[..]).is_err());
+}
+
+#[test]
+fn column_table() {
+    let table = ColumnTable::new()
+        .column("Day", vec!["Mon", "Tue"])
+        .column("Visits", vec![12.to_string(), 7.to_string()])
+        .build()
+        .unwrap();
+    let mut md = Markdown::new(Vec::new());
+    md.write(table).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| Day | Visits |\n| --- | --- |\n| Mon | 12 |\n| Tue | 7 |\n\n"
+    );
+
+    let error = ColumnTable::new()
+        .column("Day", vec!["Mon", "Tue"])
+        .column("Visits", vec!["12"])
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        error,
+        ColumnLengthError {
+            column: 1,
+            expected: 2,
+            found: 1,
+        }
+    );
+    assert_eq!(error.to_string(), "column 1 has 1 values, expected 2");
+}
+//endregion
+
+//region ExampleList
+#[test]
+fn example_list() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        ExampleList::new()
+            .labeled_item("good", "A good example.")
+            .item("Multi\nline"),
+    )
+    .unwrap();
+    md.write(
+        "As shown in "
+            .paragraph()
+            .append(ExampleRef::new("good"))
+            .append("."),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "(@good) A good example\\.\n\
+        (@) Multi\n    line\n\
+        \n\
+        As shown in (@good)\\.\n\
+        \n"
+    );
+}
+
+#[test]
+#[should_panic]
+fn panic_on_invalid_example_label() {
+    ExampleRef::new("not valid");
+}
+//endregion
+
+//region Div and Span
+#[test]
+fn div() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Div::new()
+            .id("note")
+            .class("warning")
+            .attribute("title", "Say \"hi\"")
+            .append("text")
+            .append(Div::new().append("nested")),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        ":::: {#note .warning title=\"Say \\\"hi\\\"\"}\n\
+        text\n\
+        \n\
+        ::: {}\n\
+        nested\n\
+        \n\
+        \n\
+        :::\n\
+        \n\
+        \n\
+        ::::\n\
+        \n"
+    );
+}
+
+#[test]
+fn span() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        "This is "
+            .paragraph()
+            .append(Span::new().class("smallcaps").append("small".bold())),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "This is [**small**]{.smallcaps}\n\n"
+    );
+}
+
+#[test]
+fn span_group() {
+    fn status<'a>(name: &'a str) -> Span<'a> {
+        Span::new()
+            .append("✔".bold())
+            .append(" ")
+            .append(name)
+            .append(" ")
+            .append("log".link_to("log.html"))
+    }
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(List::new(false).item(status("build"))).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n   * **✔** build [log](log.html)"
+    );
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(Table::new().header(vec!["Job"]).row(vec![status("lint")]))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| Job |\n| --- |\n| **✔** lint [log](log.html) |\n\n"
+    );
+}
+
+#[test]
+fn tuples() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(("Status: ", "ok".bold())).unwrap();
+    md.write(
+        "See "
+            .paragraph()
+            .append(("the ", "log".link_to("log.html"), ".")),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Status: **ok**\n\nSee the [log](log.html)\\.\n\n"
+    );
+}
+//endregion
+
+#[test]
+fn grid_table() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Table::new()
+            .style(TableStyle::Grid)
+            .header(vec!["name", "value"])
+            .alignment(vec![Alignment::Left, Alignment::Center])
+            .row(vec!["multi\nline", "1"])
+            .block_row(vec![List::new(false).item("a").item("b")]),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "+--------+-------+\n\
+        | name   | value |\n\
+        +:=======+:=====:+\n\
+        | multi  | 1     |\n\
+        | line   |       |\n\
+        +--------+-------+\n\
+        |    * a |       |\n\
+        |    * b |       |\n\
+        +--------+-------+\n\
+        \n"
+    );
+}
+
+//region Image
+#[test]
+fn image() {
+    let mut md = Markdown::new(Vec::new());
+    md.write("logo".image_to("logo.png")).unwrap();
+    md.write(
+        "See "
+            .paragraph()
+            .append(Image::new("chart", "chart.svg").title("A \"chart\""))
+            .append(" or ")
+            .append("Rust".image_to("rust.png").link_to("https://rust-lang.org")),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "![logo](logo.png)\n\
+        \n\
+        See ![chart](chart.svg \"A \\\"chart\\\"\") or [![Rust](rust.png)](https://rust-lang.org)\n\
+        \n"
+    );
+}
+//endregion
+
+//region Directive
+#[test]
+fn directives() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        "Press "
+            .paragraph()
+            .append(Directive::text("kbd").label("Ctrl"))
+            .append("."),
+    )
+    .unwrap();
+    md.write(
+        Directive::leaf("youtube")
+            .label("Video")
+            .attribute("v", "123"),
+    )
+    .unwrap();
+    md.write(
+        Directive::container("note")
+            .class("big")
+            .append("inside".paragraph()),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Press :kbd[Ctrl]\\.\n\
+        \n\
+        ::youtube[Video]{v=\"123\"}\n\
+        \n\
+        :::note{.big}\n\
+        inside\n\
+        \n\
+        \n\
+        :::\n\
+        \n"
+    );
+}
+
+#[test]
+fn nested_directives() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Directive::container("tabs")
+            .append(Directive::container("tab").append("one".paragraph()))
+            .append(Directive::container("tab").label("Two")),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "::::tabs\n\
+        :::tab\n\
+        one\n\
+        \n\
+        \n\
+        :::\n\
+        \n\
+        :::tab[Two]\n\
+        \n\
+        :::\n\
+        \n\
+        \n\
+        ::::\n\
+        \n"
+    );
+}
+
+#[test]
+#[should_panic]
+fn panic_on_block_in_leaf_directive() {
+    Directive::leaf("leaf").append("block");
+}
+//endregion
+
+//region Task list
+#[test]
+fn task_list() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        List::new(false)
+            .item_checked(true, "done")
+            .item_checked(false, "todo")
+            .item(
+                List::new(true)
+                    .title("nested")
+                    .item_checked(false, "subtask"),
+            ),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n   * [x] done\n   * [ ] todo\n   * nested\n      1. [ ] subtask"
+    );
+}
+//endregion
+
+//region Transform
+#[test]
+fn transforms() {
+    let mut md = Markdown::new(Vec::new())
+        .with_transform(TextRole::Heading, title_case)
+        .with_transform(TextRole::TableHeader, str::to_uppercase);
+    md.write("the lord of the rings".heading(1)).unwrap();
+    md.write("plain text of the document".paragraph()).unwrap();
+    md.write(Table::new().header(vec!["name"]).row(vec!["value"]))
+        .unwrap();
+    md.write("code in heading".code().heading(2)).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "# The Lord of the Rings\n\
+        plain text of the document\n\
+        \n\
+        | NAME |\n\
+        | --- |\n\
+        | value |\n\
+        \n\
+        ## ` code in heading `\n"
+    );
+}
+
+#[test]
+fn text_transform_does_not_change_addresses() {
+    let mut md =
+        Markdown::new(Vec::new()).with_transform(TextRole::Text, |text| text.replace('a', "b"));
+    md.write("a".link_to("a")).unwrap();
+    assert_eq!(String::from_utf8(md.into_inner()).unwrap(), "[b](a)\n");
+}
+//endregion
+
+//region Footnote
+#[test]
+fn footnotes() {
+    let mut md = Markdown::new(Vec::new());
+    let first = md.footnote("First note.").unwrap();
+    let second = md.footnote("Multi\nline".bold()).unwrap();
+    md.write(
+        "Text"
+            .paragraph()
+            .append(first)
+            .append(" and more")
+            .append(second),
+    )
+    .unwrap();
+    md.write_footnotes().unwrap();
+    let third = md.footnote("Third").unwrap();
+    md.write(third).unwrap();
+    md.write_footnotes().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Text[^1] and more[^2]\n\
+        \n\
+        [^1]: First note\\.\n\
+        [^2]: **Multi\n    line**\n\
+        \n\
+        [^3]\n\
+        \n\
+        [^3]: Third\n\
+        \n"
+    );
+}
+
+#[test]
+fn footnotes_without_native_support() {
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::GfmComment);
+    let first = md.footnote("First note.").unwrap();
+    let second = md.footnote("Second").unwrap();
+    md.write("Text".paragraph().append(first).append(second))
+        .unwrap();
+    md.write_footnotes().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Text<sup><a href=\"#fn-1\">1</a></sup><sup><a href=\"#fn-2\">2</a></sup>\n\
+        \n\
+        <sup id=\"fn-1\">1</sup> First note\\.\n\
+        \n\
+        <sup id=\"fn-2\">2</sup> Second\n\
+        \n"
+    );
+    assert!(Flavor::Gfm.native_footnotes());
+    assert!(!Flavor::GfmComment.native_footnotes());
+    assert!(!Flavor::Telegram.native_footnotes());
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Discord);
+    let notes: Vec<_> = (1..=12)
+        .map(|i| md.footnote(format!("Note {}", i)).unwrap())
+        .collect();
+    md.write("Text".paragraph().append(notes[11])).unwrap();
+    md.write_footnotes().unwrap();
+    let output = String::from_utf8(md.into_inner()).unwrap();
+    assert!(output.starts_with("Text¹²\n\n1. Note 1\n2. Note 2\n"));
+    assert!(output.ends_with("12. Note 12\n\n"));
+}
+//endregion
+
+//region Summary
+#[test]
+fn summary() {
+    let summary = Summary::from_values((1..=20).map(f64::from)).unwrap();
+    assert_eq!(summary.count, 20);
+    assert_eq!(summary.min, 1.0);
+    assert_eq!(summary.max, 20.0);
+    assert_eq!(summary.mean, 10.5);
+    assert_eq!(summary.median, 10.5);
+    assert_eq!(summary.p95, 19.0);
+    assert!(Summary::from_values(Vec::<f64>::new()).is_none());
+}
+
+#[test]
+fn summary_table() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        SummaryTable::new()
+            .precision(1)
+            .series("latency", vec![3u32, 1, 2])
+            .series("empty", Vec::<u32>::new()),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| series | count | min | max | mean | median | p95 |\n\
+        | :--- | ---: | ---: | ---: | ---: | ---: | ---: |\n\
+        | latency | 3 | 1\\.0 | 3\\.0 | 2\\.0 | 2\\.0 | 3\\.0 |\n\
+        | empty | 0 |  |  |  |  |  |\n\
+        \n"
+    );
+}
+//endregion
+
+//region Bar
+#[test]
+fn bars() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(Bar::new(0.6).width(5)).unwrap();
+    md.write(Table::new().header(vec!["usage"]).row(vec![
+        Bar::from_value(1.0, 4.0).width(4).style(BarStyle::Ascii),
+    ]))
+    .unwrap();
+    md.write(Bar::new(2.0).width(3).percentage(false)).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "▇▇▇▁▁ 60%\n\
+        \n\
+        | usage |\n\
+        | --- |\n\
+        | [#...] 25% |\n\
+        \n\
+        ▇▇▇\n\
+        \n"
+    );
+}
+//endregion
+
+//region Humanize
+#[cfg(feature = "humanize")]
+#[test]
+fn humanize() {
+    use crate::markdown::humanize::{Bytes, Count, HumanDuration};
+    use std::time::Duration;
+
+    assert_eq!(Bytes::new(512).to_string(), "512 B");
+    assert_eq!(Bytes::new(1_400_000_000).to_string(), "1.4 GB");
+    assert_eq!(Bytes::new(999_960).to_string(), "1 MB");
+    assert_eq!(
+        HumanDuration::new(Duration::from_millis(850)).to_string(),
+        "850ms"
+    );
+    assert_eq!(
+        HumanDuration::new(Duration::from_secs(192)).to_string(),
+        "3m 12s"
+    );
+    assert_eq!(
+        HumanDuration::new(Duration::from_secs(7200)).to_string(),
+        "2h"
+    );
+    assert_eq!(Count::new(42).to_string(), "42");
+    assert_eq!(Count::new(1_234_567).to_string(), "1.2M");
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        "Downloaded "
+            .paragraph()
+            .append(Bytes::new(1_400_000_000))
+            .append(" in ")
+            .append(HumanDuration::new(Duration::from_secs(192)).code()),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Downloaded 1\\.4 GB in ` 3m 12s `\n\n"
+    );
+}
+//endregion
+
+//region Owned text
+fn owned_link(name: String) -> Link<'static> {
+    let address = format!("https://example.com/{}", name);
+    Link::new(address).append(RichText::new(name).bold())
+}
+
+#[test]
+fn owned_text() {
+    let link = owned_link("docs".to_string());
+    let image =
+        Image::new(String::from("Logo"), String::from("logo.png")).title(String::from("Our logo"));
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(Paragraph::new().append(link).append(" ").append(image))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "[**docs**](https://example.com/docs) ![Logo](logo.png \"Our logo\")\n\n"
+    );
+}
+//endregion
+
+//region Locale
+#[cfg(feature = "locale")]
+#[test]
+fn locale_numbers() {
+    use crate::markdown::locale::{Locale, Number};
+
+    assert_eq!(Number::new(1234.5).precision(2).to_string(), "1,234.50");
+    assert_eq!(
+        Number::new(-1234567.891)
+            .precision(2)
+            .locale(Locale::DE)
+            .to_string(),
+        "-1.234.567,89"
+    );
+    assert_eq!(Number::new(999).locale(Locale::CH).to_string(), "999");
+    assert_eq!(
+        Number::new(12345)
+            .locale(Locale::new(None, ','))
+            .to_string(),
+        "12345"
+    );
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        SummaryTable::new()
+            .locale(Locale::DE)
+            .series("latency", vec![1200.5, 1500.25]),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| series | count | min | max | mean | median | p95 |\n\
+         | :--- | ---: | ---: | ---: | ---: | ---: | ---: |\n\
+         | latency | 2 | 1\\.200,50 | 1\\.500,25 | 1\\.350,38 | 1\\.350,38 | 1\\.500,25 |\n\n"
+    );
+}
+
+#[cfg(feature = "locale")]
+#[test]
+fn locale_percent_currency() {
+    use crate::markdown::locale::{Currency, Locale, Percent, SymbolPlacement};
+
+    assert_eq!(Percent::new(0.125).precision(1).to_string(), "12.5%");
+    assert_eq!(Percent::new(-0.5).to_string(), "-50%");
+    assert_eq!(Currency::new(1234.5, "$").to_string(), "$1,234.50");
+    assert_eq!(Currency::new(-5, "$").to_string(), "-$5.00");
+    assert_eq!(
+        Currency::new(1234.5, "€")
+            .locale(Locale::DE)
+            .placement(SymbolPlacement::After)
+            .to_string(),
+        "1.234,50\u{a0}€"
+    );
+
+    let table = ColumnTable::new()
+        .column("Item", vec!["Widget", "Total"])
+        .column(
+            "Price",
+            vec![Currency::new(10, "$"), Currency::new(10, "$")],
+        )
+        .column("Margin", vec![Some(Percent::new(0.3)), None])
+        .build()
+        .unwrap()
+        .column_alignment(0, Alignment::Left);
+    let mut md = Markdown::new(Vec::new());
+    md.write(table).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| Item | Price | Margin |\n\
+         | :--- | ---: | ---: |\n\
+         | Widget | $10\\.00 | 30% |\n\
+         | Total | $10\\.00 |  |\n\n"
+    );
+}
+//endregion
+
+//region Heading case
+#[test]
+fn heading_case() {
+    let sentence = HeadingCase::new(CaseStyle::Sentence);
+    assert!(sentence.check("Writing to a file with the API"));
+    assert!(!sentence.check("Writing to a File"));
+    assert_eq!(sentence.fix("Writing To A File"), "Writing to a file");
+
+    let title = HeadingCase::new(CaseStyle::Title).small_words(vec!["to", "a", "with"]);
+    assert!(!title.check("Writing to a file"));
+    assert_eq!(
+        title.fix("writing to a file with the API"),
+        "Writing to a File with The API"
+    );
+
+    let mut md =
+        Markdown::new(Vec::new()).with_transform(TextRole::Heading, move |text| sentence.fix(text));
+    md.write("Usage Of The API".heading(2)).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "## Usage of the API\n"
+    );
+}
+//endregion
+
+//region Document
+#[test]
+fn custom_element() {
+    struct Shortcode<'a> {
+        name: &'a str,
+        text: &'a str,
+    }
+
+    impl CustomElement for Shortcode<'_> {
+        fn write(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+            context.write_raw(writer, format!("{{{{< {} >}}}}", self.name).as_bytes())?;
+            context.write_inner(writer, self.text)?;
+            context.write_raw(writer, format!("{{{{< /{} >}}}}", self.name).as_bytes())?;
+            context.end_block(writer)
+        }
+    }
+
+    impl InlineElement for Shortcode<'_> {}
+    impl InlineElement for &'_ Shortcode<'_> {}
+
+    let shortcode = Shortcode {
+        name: "highlight",
+        text: "a_b\nc",
+    };
+    let mut md = Markdown::new(Vec::new());
+    md.write(&shortcode).unwrap();
+    md.write(Blockquote::new().append("See ".paragraph().append(&shortcode)))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "{{< highlight >}}a\\_b\nc{{< /highlight >}}\n\n\
+         > See {{< highlight >}}a\\_b\n> c{{< /highlight >}}\n\n"
+    );
+}
+
+#[test]
+fn render_context_nesting() {
+    use core::cell::RefCell;
+
+    struct Nested<'a>(&'a RefCell<Vec<(String, usize)>>);
+
+    impl CustomElement for Nested<'_> {
+        fn write(&self, writer: &mut dyn Write, context: &RenderContext<'_>) -> Result<(), Error> {
+            let nesting: Vec<_> = context.nesting().map(|n| format!("{:?}", n)).collect();
+            self.0
+                .borrow_mut()
+                .push((nesting.join(" in "), context.depth(Nesting::List)));
+            context.write_inner(writer, "x")?;
+            context.end_block(writer)
+        }
+    }
+
+    impl InlineElement for Nested<'_> {}
+    impl InlineElement for &'_ Nested<'_> {}
+
+    let log = RefCell::new(Vec::new());
+    let nested = Nested(&log);
+    let mut md = Markdown::new(Vec::new());
+    md.write(&nested).unwrap();
+    md.write(List::new(false).item(List::new(false).title("list").item(&nested)))
+        .unwrap();
+    md.write(Blockquote::new().append(List::new(false).item(&nested)))
+        .unwrap();
+    md.write(Table::new().header(["cell"]).row([&nested]))
+        .unwrap();
+    let footnote = md.footnote(&nested).unwrap();
+    md.write(footnote).unwrap();
+    md.write(Admonition::new("note").append(Collapsible::new("More").append(&nested)))
+        .unwrap();
+    md.write(Div::new().append(Div::new().append(&nested)))
+        .unwrap();
+    md.write(ExampleList::new().item(&nested)).unwrap();
+    let log: Vec<_> = log.into_inner();
+    assert_eq!(
+        log,
+        [
+            ("".to_string(), 0),
+            ("List in List".to_string(), 2),
+            ("List in Quote".to_string(), 1),
+            ("Table".to_string(), 0),
+            ("Footnote".to_string(), 0),
+            ("Collapsible in Admonition".to_string(), 0),
+            // Both divs measure their fences by rendering the children
+            ("".to_string(), 0),
+            ("".to_string(), 0),
+            ("Div in Div".to_string(), 0),
+            ("List".to_string(), 1),
+        ]
+    );
+}
+
+#[test]
+fn fanout() {
+    let mut gfm = Markdown::new(Vec::new()).with_flavor(Flavor::Gfm);
+    let mut telegram = Markdown::new(FmtWriter::new(String::new())).with_flavor(Flavor::Telegram);
+    let mut fanout = Fanout::new().sink(&mut gfm).sink(&mut telegram);
+    fanout.write("Build".heading(2)).unwrap();
+    let note = fanout.footnote("Cached.").unwrap();
+    fanout
+        .write("Passed in 1.5 s".paragraph().append(note))
+        .unwrap();
+    fanout.write_footnotes().unwrap();
+
+    assert_eq!(
+        String::from_utf8(gfm.into_inner()).unwrap(),
+        "## Build\nPassed in 1\\.5 s[^1]\n\n[^1]: Cached\\.\n\n"
+    );
+    assert_eq!(
+        telegram.into_inner().into_inner(),
+        "*Build*\n\nPassed in 1\\.5 s¹\n\n1\\. Cached\\.\n\n"
+    );
+
+    let mut strict = Markdown::new(Vec::new()).with_strict(true);
+    let mut lenient = Markdown::new(Vec::new());
+    lenient.footnote("Direct").unwrap();
+    let mut fanout = Fanout::new().sink(&mut strict).sink(&mut lenient);
+    assert!(fanout.write("".bold()).is_err());
+    assert_eq!(
+        fanout.footnote("Note").err().unwrap().kind(),
+        ErrorKind::InvalidData
+    );
+    assert!(strict.into_inner().is_empty());
+    assert!(lenient.into_inner().is_empty());
+    assert_eq!(
+        Fanout::new().footnote("Note").err().unwrap().kind(),
+        ErrorKind::InvalidInput
+    );
+}
+
+#[test]
+fn markdown_pool() {
+    let pool = MarkdownPool::new(1024, 1).with_options(|md| md.with_flavor(Flavor::Telegram));
+
+    let mut md = pool.take();
+    md.write("Done.".paragraph()).unwrap();
+    let buffer = md.into_inner();
+    assert_eq!(String::from_utf8_lossy(&buffer), "Done\\.\n\n");
+    let address = buffer.as_ptr();
+    pool.recycle(buffer);
+    pool.recycle(Vec::new());
+    assert_eq!(pool.idle(), 1);
+
+    let mut md = pool.take();
+    assert_eq!(pool.idle(), 0);
+    md.write("Next".bold()).unwrap();
+    let buffer = md.into_inner();
+    assert_eq!(buffer.as_ptr(), address);
+    assert_eq!(String::from_utf8(buffer).unwrap(), "*Next*\n\n");
+}
+
+#[test]
+fn document() {
+    let mut doc = Document::new();
+    doc.push("Intro".paragraph());
+    doc.push("Details".paragraph());
+    doc.insert(0, "Title".heading(1));
+    assert_eq!(doc.len(), 3);
+
+    let removed = doc.remove(2);
+    doc.push(Quote::new().append("Quoted"));
+    let mut buffer = Vec::new();
+    removed
+        .write_to(
+            &mut buffer,
+            &RenderContext::new(false, Escaping::Normal, None, &Options::new()),
+        )
+        .unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), "Details\n\n");
+
+    let first = String::from_utf8(doc.render(Vec::new()).unwrap()).unwrap();
+    let second = String::from_utf8(doc.render(Vec::new()).unwrap()).unwrap();
+    assert_eq!(first, "# Title\nIntro\n\n\n>Quoted\n\n");
+    assert_eq!(first, second);
+}
+//endregion
+
+//region Embed
+#[test]
+fn embed() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(Embed::new("Daily notes/2024-01-01")).unwrap();
+    md.write(Embed::new("Project_plan").heading("Goals (Q1)"))
+        .unwrap();
+    md.write(Embed::new("Tasks").block("task-1")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "![[Daily notes/2024-01-01]]\n\n\
+         ![[Project_plan#Goals (Q1)]]\n\n\
+         ![[Tasks#^task-1]]\n\n"
+    );
+}
+
+#[test]
+#[should_panic]
+fn embed_invalid_target() {
+    Embed::new("Note]]");
+}
+//endregion
+
+//region Logseq
+#[test]
+fn logseq_blocks() {
+    let mut md = Markdown::new(Vec::new()).with_list_style(Bullet::Dash, 2);
+    md.write(
+        List::new(false)
+            .item(
+                LogseqBlock::new()
+                    .task(TaskKeyword::Todo)
+                    .append("Buy *milk*")
+                    .property("priority", "[[high]]"),
+            )
+            .item(LogseqBlock::new().task(TaskKeyword::Done))
+            .item(
+                LogseqBlock::new()
+                    .append("Notes")
+                    .property("tags", "home, errands")
+                    .property("id", "a1"),
+            ),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n- TODO Buy \\*milk\\*\n  priority:: [[high]]\
+         \n- DONE\
+         \n- Notes\n  tags:: home, errands\n  id:: a1"
+    );
+}
+
+#[test]
+#[should_panic]
+fn logseq_invalid_property() {
+    LogseqBlock::new().property("due date", "today");
+}
+//endregion
+
+//region Toc
+#[test]
+fn toc_from_document() {
+    let mut doc = Document::new();
+    doc.push("Usage".heading(1));
+    doc.push(Heading::new(2).append("Writing ").append("files".code()));
+    doc.push("Text".paragraph());
+    doc.push(Div::new().append("Notes & tips".heading(3)));
+    doc.push("Usage".heading(2));
+    doc.insert(0, doc.toc().unwrap());
+
+    assert_eq!(
+        String::from_utf8(doc.render(Vec::new()).unwrap()).unwrap(),
+        "- [Usage](#usage)\n\
+         \x20 - [Writing ` files `](#writing-files)\n\
+         \x20   - [Notes & tips](#notes--tips)\n\
+         \x20 - [Usage](#usage-1)\n\
+         \n\
+         # Usage\n\
+         ## Writing ` files `\n\
+         Text\n\n\
+         ::: {}\n\
+         ### Notes & tips\n\
+         \n:::\n\n\
+         ## Usage\n"
+    );
+}
+
+#[test]
+fn document_statistics() {
+    let mut doc = Document::new();
+    doc.push("Results".heading(1));
+    doc.push(
+        "See the "
+            .paragraph()
+            .append("docs".link_to("https://example.com"))
+            .append(" and ")
+            .append("chart".image_to("chart.png")),
+    );
+    doc.push(
+        Table::new()
+            .header(vec!["Name", "Score"])
+            .row(vec!["Alice", "10"]),
+    );
+    doc.push(CodeBlock::new("let [a](b) = c;\n```\n[x](y)").language("rust"));
+    doc.push("Appendix".heading(2));
+
+    let statistics = doc.statistics().unwrap();
+    assert_eq!(
+        statistics,
+        Statistics {
+            sections: 2,
+            words: 15,
+            tables: 1,
+            links: 1,
+        }
+    );
+    doc.insert(1, statistics);
+    let rendered = String::from_utf8(doc.render(Vec::new()).unwrap()).unwrap();
+    assert!(rendered.starts_with(
+        "# Results\n\
+         | Field | Value |\n| --- | --- |\n| Sections | 2 |\n| Words | 15 |\n\
+         | Tables | 1 |\n| Links | 1 |\n\n\
+         See the [docs](https://example.com)"
+    ));
+
+    doc.push(Failing);
+    assert!(doc.statistics().is_err());
+}
+
+#[test]
+fn toc_written_headings() {
+    let mut md = Markdown::new(Vec::new()).with_transform(TextRole::Heading, title_case);
+    md.write("getting started".heading(1)).unwrap();
+    md.write("Some text.".paragraph()).unwrap();
+    md.write_toc().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "# Getting Started\nSome text\\.\n\n- [Getting Started](#getting-started)\n\n"
+    );
+}
+//endregion
+
+//region Anchors
+#[test]
+fn heading_anchors() {
+    assert_eq!(slugify("Hello, World! 2.0"), "hello-world-20");
+    assert_eq!(slugify("Über_alles - now"), "über_alles---now");
+
+    let heading = Heading::new(2)
+        .append("Writing to ")
+        .append("files".code())
+        .emit_anchor();
+    assert_eq!(heading.anchor(), "writing-to-files");
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(&heading).unwrap();
+    md.write(Link::to_heading(&heading).append("See above"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "<a id=\"writing-to-files\"></a>\n## Writing to ` files `\n[See above](#writing-to-files)\n"
+    );
+}
+//endregion
+
+//region CodeBlock
+#[test]
+fn code_blocks() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(CodeBlock::new("let x = 1;\n").language("rust"))
+        .unwrap();
+    md.write(CodeBlock::new("Use ``` to start a *block*"))
+        .unwrap();
+    md.write(
+        CodeBlock::chunk("r", "plot(cars)")
+            .label("cars-plot")
+            .option("echo", false)
+            .option("fig.width", 7.5)
+            .option("fig.align", "center"),
+    )
+    .unwrap();
+    md.write(CodeBlock::chunk("python", "print(1)").option("eval", true))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "```rust\nlet x = 1;\n```\n\n\
+         ````\nUse ``` to start a *block*\n````\n\n\
+         ```{r cars-plot, echo=FALSE, fig.width=7.5, fig.align=\"center\"}\nplot(cars)\n```\n\n\
+         ```{python eval=TRUE}\nprint(1)\n```\n\n"
+    );
+}
+
+#[test]
+fn diagrams() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(CodeBlock::mermaid("graph TD;\n  A-->|`x`|B;\n"))
+        .unwrap();
+    md.write(CodeBlock::plantuml("@startuml\nA -> B: *call*\n@enduml"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "```mermaid\ngraph TD;\n  A-->|`x`|B;\n```\n\n\
+         ```plantuml\n@startuml\nA -> B: *call*\n@enduml\n```\n\n"
+    );
+}
+//endregion
+
+//region Blockquote
+#[test]
+fn blockquote() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Blockquote::new()
+            .append("First paragraph.".paragraph())
+            .append(CodeBlock::new("fn main() {\n\n}").language("rust"))
+            .append(Blockquote::new().append("Nested".paragraph())),
+    )
+    .unwrap();
+    md.write("After".paragraph()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "> First paragraph\\.\n\
+         >\n\
+         > ```rust\n\
+         > fn main() {\n\
+         >\n\
+         > }\n\
+         > ```\n\
+         >\n\
+         > > Nested\n\
+         \n\
+         After\n\n"
+    );
+}
+
+#[test]
+fn blockquote_in_footnote() {
+    let mut md = Markdown::new(Vec::new());
+    let note = md
+        .footnote(
+            Blockquote::new()
+                .append("A".paragraph())
+                .append("B".paragraph()),
+        )
+        .unwrap();
+    md.write("Text".paragraph().append(note)).unwrap();
+    md.write_footnotes().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Text[^1]\n\n[^1]: > A\n    >\n    > B\n    \n\n"
+    );
+}
+
+#[test]
+fn blockquote_attribution() {
+    let quote = || {
+        Blockquote::new()
+            .append("Simple is better.".paragraph())
+            .attribution("Jane Doe, ")
+            .attribution(Citation::new("Notes <2024>"))
+    };
+    let mut md = Markdown::new(Vec::new());
+    md.write(List::new(false).item(quote())).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n   * > Simple is better\\.\
+         \n   >\
+         \n   > — Jane Doe, <cite>Notes &lt;2024&gt;</cite>\
+         \n   "
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Discord);
+    md.write(quote()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "> Simple is better.\n>\n> — Jane Doe, *Notes <2024\\>*\n\n"
+    );
+}
+//endregion
+
+//region Jupyter
+#[cfg(feature = "jupyter")]
+#[test]
+fn jupyter_notebook() {
+    use crate::markdown::jupyter::Notebook;
+
+    let notebook = Notebook::new()
+        .split_at_headings(true)
+        .code_cells(true)
+        .language("python")
+        .append("Analysis".heading(1))
+        .append("Intro".paragraph())
+        .code(CodeBlock::new("import pandas\nprint(1)\n").language("python"))
+        .append("Results".heading(2))
+        .append("Done.".paragraph())
+        .cell_break()
+        .append("Appendix".paragraph());
+    let output = notebook.render(Vec::new()).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json["nbformat"], 4);
+    assert_eq!(json["metadata"]["language_info"]["name"], "python");
+    let cells = json["cells"].as_array().unwrap();
+    let summary: Vec<(&str, Vec<&str>)> = cells
+        .iter()
+        .map(|cell| {
+            (
+                cell["cell_type"].as_str().unwrap(),
+                cell["source"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|line| line.as_str().unwrap())
+                    .collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("markdown", vec!["# Analysis\n", "Intro"]),
+            ("code", vec!["import pandas\n", "print(1)"]),
+            ("markdown", vec!["## Results\n", "Done\\."]),
+            ("markdown", vec!["Appendix"]),
+        ]
+    );
+    assert!(notebook.append(Failing).render(Vec::new()).is_err());
+}
+//endregion
+
+//region Alert
+#[test]
+fn alerts() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(Alert::new(AlertKind::Note).append("Useful information.".paragraph()))
+        .unwrap();
+    md.write(
+        Alert::new(AlertKind::Warning)
+            .append("Check first:".paragraph())
+            .append(List::new(false).item("backups")),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "> [!NOTE]\n> Useful information\\.\n\n\
+         > [!WARNING]\n> Check first:\n>\n>    * backups\n\n"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn alert_from_result() {
+    use std::fmt;
+
+    #[derive(Debug)]
+    struct JobError(Option<std::io::Error>);
+
+    impl fmt::Display for JobError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "job failed")
+        }
+    }
+
+    impl std::error::Error for JobError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.0.as_ref().map(|e| e as _)
+        }
+    }
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(Alert::from_result(&Ok::<_, JobError>("42 rows exported")))
+        .unwrap();
+    md.write(Alert::from_result(&Err::<u32, _>(JobError(None))))
+        .unwrap();
+    md.write(Alert::from_result(&Err::<u32, _>(JobError(Some(
+        std::io::Error::other("disk full"),
+    )))))
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "> [!TIP]\n> 42 rows exported\n\n\
+         > [!CAUTION]\n> job failed\n\n\
+         > [!CAUTION]\n> job failed\n>\n>    * disk full\n\n"
+    );
+}
+//endregion
+
+//region Admonition
+#[test]
+fn admonition() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(Admonition::new("note").append("Useful information.".paragraph()))
+        .unwrap();
+    md.write(
+        Admonition::new("warning")
+            .title("Read \"this\"")
+            .append("First.".paragraph())
+            .append(CodeBlock::new("rm -rf build")),
+    )
+    .unwrap();
+    md.write(Admonition::new("tip").title("")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "!!! note\n    Useful information\\.\n\n\
+         !!! warning \"Read &quot;this&quot;\"\n    First\\.\n\n    ```\n    rm -rf build\n    ```\n\n\
+         !!! tip \"\"\n\n"
+    );
+}
+//endregion
+
+//region Pandoc
+#[cfg(feature = "pandoc")]
+#[test]
+fn pandoc_json() {
+    use crate::markdown::pandoc::Pandoc;
+    use serde_json::json;
+
+    let mut pandoc = Pandoc::new(Vec::new());
+    pandoc.write("Title".heading(1));
+    pandoc.write(
+        "Some *literal* "
+            .paragraph()
+            .append("bold".bold())
+            .append(" ")
+            .append("site".link_to("https://example.com")),
+    );
+    pandoc.write(CodeBlock::new("x <- 1\n").language("r"));
+    pandoc.write(Blockquote::new().append(Table::new().header(vec!["a"])));
+    pandoc.write(
+        Table::new()
+            .header(vec!["x", "y"])
+            .alignment(vec![Alignment::Right])
+            .row(vec!["1"]),
+    );
+    pandoc.write(
+        List::new(true)
+            .item("one")
+            .item(List::new(false).title("two").item_checked(true, "done")),
+    );
+    pandoc.write("cited".quote());
+    pandoc.write(ExampleList::new().item("first"));
+    let output = pandoc.finish().unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(json["pandoc-api-version"], json!([1, 23, 1]));
+    assert_eq!(
+        json["blocks"],
+        json!([
+            {"t": "Header", "c": [1, ["", [], []], [{"t": "Str", "c": "Title"}]]},
+            {"t": "Para", "c": [
+                {"t": "Str", "c": "Some"}, {"t": "Space"},
+                {"t": "Str", "c": "*literal*"}, {"t": "Space"},
+                {"t": "Strong", "c": [{"t": "Str", "c": "bold"}]}, {"t": "Space"},
+                {"t": "Link", "c": [
+                    ["", [], []],
+                    [{"t": "Str", "c": "site"}],
+                    ["https://example.com", ""]
+                ]}
+            ]},
+            {"t": "CodeBlock", "c": [["", ["r"], []], "x <- 1"]},
+            {"t": "BlockQuote", "c": [
+                {"t": "Table", "c": [
+                    ["", [], []],
+                    [null, []],
+                    [[{"t": "AlignDefault"}, {"t": "ColWidthDefault"}]],
+                    [["", [], []], [[["", [], []], [
+                        [["", [], []], {"t": "AlignDefault"}, 1, 1,
+                            [{"t": "Plain", "c": [{"t": "Str", "c": "a"}]}]]
+                    ]]]],
+                    [[["", [], []], 0, [], []]],
+                    [["", [], []], []]
+                ]}
+            ]},
+            {"t": "Table", "c": [
+                ["", [], []],
+                [null, []],
+                [
+                    [{"t": "AlignRight"}, {"t": "ColWidthDefault"}],
+                    [{"t": "AlignDefault"}, {"t": "ColWidthDefault"}]
+                ],
+                [["", [], []], [[["", [], []], [
+                    [["", [], []], {"t": "AlignDefault"}, 1, 1,
+                        [{"t": "Plain", "c": [{"t": "Str", "c": "x"}]}]],
+                    [["", [], []], {"t": "AlignDefault"}, 1, 1,
+                        [{"t": "Plain", "c": [{"t": "Str", "c": "y"}]}]]
+                ]]]],
+                [[["", [], []], 0, [], [[["", [], []], [
+                    [["", [], []], {"t": "AlignDefault"}, 1, 1,
+                        [{"t": "Plain", "c": [{"t": "Str", "c": "1"}]}]],
+                    [["", [], []], {"t": "AlignDefault"}, 1, 1, []]
+                ]]]]],
+                [["", [], []], []]
+            ]},
+            {"t": "OrderedList", "c": [[1, {"t": "Decimal"}, {"t": "Period"}], [
+                [{"t": "Plain", "c": [{"t": "Str", "c": "one"}]}],
+                [
+                    {"t": "Plain", "c": [{"t": "Str", "c": "two"}]},
+                    {"t": "BulletList", "c": [[{"t": "Plain", "c": [
+                        {"t": "Str", "c": "☒"}, {"t": "Space"}, {"t": "Str", "c": "done"}
+                    ]}]]}
+                ]
+            ]]},
+            {"t": "BlockQuote", "c": [{"t": "Para", "c": [{"t": "Str", "c": "cited"}]}]},
+            {"t": "OrderedList", "c": [[1, {"t": "Example"}, {"t": "TwoParens"}], [
+                [{"t": "Plain", "c": [{"t": "Str", "c": "first"}]}]
+            ]]}
+        ])
+    );
+}
+//endregion
+
+//region Collapsible
+#[test]
+fn collapsible() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Collapsible::new("Build log <stderr>")
+            .append("Failed *twice*.".paragraph())
+            .append(CodeBlock::new("error: oops")),
+    )
+    .unwrap();
+    md.write(Collapsible::new("Empty").open(true)).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "<details>\n<summary>Build log &lt;stderr&gt;</summary>\n\n\
+         Failed \\*twice\\*\\.\n\n```\nerror: oops\n```\n\n</details>\n\n\
+         <details open>\n<summary>Empty</summary>\n\n</details>\n\n"
+    );
+}
+//endregion
+
+//region FrontMatter
+#[test]
+fn front_matter_update() {
+    let document = "---\n\
+                    title: Release notes\n\
+                    lastmod: 2024-01-01\n\
+                    tags:\n\
+                    \x20 - rust\n\
+                    author:\n\
+                    \x20 name: Jane\n\
+                    draft:\n\
+                    ---\n\
+                    Body\n";
+    let (mut front_matter, body) = FrontMatter::parse(document).unwrap();
+    assert_eq!(body, "Body\n");
+    assert_eq!(
+        front_matter.get("title"),
+        Some(&FrontMatterValue::Scalar("Release notes".to_string()))
+    );
+
+    front_matter.set("lastmod", "2024-02-01");
+    front_matter.append("tags", "markdown");
+    front_matter.append("tags", "rust");
+    let mut update = FrontMatter::new();
+    update.set("summary", "Fixes: many");
+    update.set("title", "Release notes 1.3");
+    front_matter.merge(update);
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(&front_matter).unwrap();
+    let mut output = String::from_utf8(md.into_inner()).unwrap();
+    output.push_str(body);
+    assert_eq!(
+        output,
+        "---\n\
+         title: Release notes 1.3\n\
+         lastmod: 2024-02-01\n\
+         tags:\n\
+         \x20 - rust\n\
+         \x20 - markdown\n\
+         author:\n\
+         \x20 name: Jane\n\
+         draft:\n\
+         summary: \"Fixes: many\"\n\
+         ---\n\
+         Body\n"
+    );
+    assert!(FrontMatter::parse("# No front matter").is_none());
+}
+
+#[test]
+fn front_matter_round_trip() {
+    fn round_trip(document: &str) -> String {
+        let (front_matter, body) = FrontMatter::parse(document).unwrap();
+        let mut md = Markdown::new(Vec::new());
+        md.write(&front_matter).unwrap();
+        String::from_utf8(md.into_inner()).unwrap() + body
+    }
+
+    let document = "---\n\
+                    description: >\n\
+                    \x20 Folded text\n\
+                    \x20 over lines\n\
+                    \n\
+                    \x20 and a paragraph\n\
+                    script: |-\n\
+                    \x20 cargo build\n\
+                    \x20 cargo test\n\
+                    title: Notes\n\
+                    wrapped: plain\n\
+                    \x20 continued\n\
+                    author:\n\
+                    \x20 # Nested comments are kept\n\
+                    \x20 name: Jane\n\
+                    ---\n\
+                    Body\n";
+    assert_eq!(round_trip(document), document);
+    let (front_matter, _) = FrontMatter::parse(document).unwrap();
+    assert_eq!(
+        front_matter.get("script"),
+        Some(&FrontMatterValue::Block(
+            "|-\n  cargo build\n  cargo test\n".to_string()
+        ))
+    );
+
+    let commented = "---\n\
+                     # Generated: do not edit\n\
+                     title: Notes\n\
+                     #draft: true\n\
+                     ---\n";
+    let (front_matter, _) = FrontMatter::parse(commented).unwrap();
+    assert_eq!(front_matter.get("# Generated"), None);
+    assert_eq!(front_matter.get("#draft"), None);
+    assert_eq!(round_trip(commented), "---\ntitle: Notes\n---\n");
+}
+//endregion
+
+//region Flavor
+#[test]
+fn flavors() {
+    fn render(flavor: Flavor) -> String {
+        let mut md = Markdown::new(Vec::new()).with_flavor(flavor);
+        md.write("Status".heading(4)).unwrap();
+        md.write(
+            "Build #1 = ok."
+                .paragraph()
+                .append(" ")
+                .append("fast".bold())
+                .append(" ")
+                .append("a`b".code())
+                .append(" ")
+                .append("log".link_to("https://ci.example/(1)")),
+        )
+        .unwrap();
+        String::from_utf8(md.into_inner()).unwrap()
+    }
+
+    assert_eq!(Flavor::Discord.max_heading_level(), 3);
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Discord);
+    md.write("Title".heading(3)).unwrap();
+    md.write("Details".heading(5)).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "### Title\n**Details**\n\n"
+    );
+    assert_eq!(
+        render(Flavor::CommonMark),
+        "#### Status\nBuild \\#1 = ok\\. **fast** `` a`b `` [log](https://ci.example/(1))\n\n"
+    );
+    assert_eq!(
+        render(Flavor::Telegram),
+        "*Status*\n\nBuild \\#1 \\= ok\\. *fast* `a\\`b` [log](https://ci.example/(1\\))\n\n"
+    );
+    assert_eq!(
+        render(Flavor::Discord),
+        "**Status**\n\nBuild \\#1 = ok. **fast** `` a`b `` [log](https://ci.example/(1))\n\n"
+    );
+}
+
+#[test]
+fn telegram_lists_and_tables() {
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Telegram);
+    md.write(
+        Table::new()
+            .header(["Name", "Size"])
+            .alignment([Alignment::None, Alignment::Right])
+            .row(["a|b", "1.5"])
+            .row(["`code`", "10"]),
+    )
+    .unwrap();
+    md.write(List::new(false).item("a-b").item_checked(true, "done"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "```\n\
+         Name   | Size\n\
+         ------ | ----\n\
+         a|b    |  1.5\n\
+         \\`code\\` |   10\n\
+         ```\n\n\
+         \n• a\\-b\n• \\[x\\] done"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Telegram);
+    md.write(List::new(true).item("first").item("second"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "\n1\\. first\n2\\. second"
+    );
+}
+//endregion
+
+//region Link rewriting
+#[test]
+fn link_rewriter() {
+    let mut md = Markdown::new(Vec::new()).with_link_rewriter(|address| {
+        if address.contains("://") {
+            address.to_string()
+        } else {
+            format!(
+                "https://cdn.example/{}",
+                address
+                    .strip_suffix(".md")
+                    .map_or(address.to_string(), |a| a.to_string() + ".html")
+            )
+        }
+    });
+    md.write(
+        "See "
+            .paragraph()
+            .append("guide".link_to("guide.md"))
+            .append(" ")
+            .append("logo".image_to("logo.png"))
+            .append(" ")
+            .append("web".link_to("https://example.com")),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "See [guide](https://cdn.example/guide.html) ![logo](https://cdn.example/logo.png) [web](https://example.com)\n\n"
+    );
+}
+//endregion
+
+//region Metadata
+#[test]
+fn element_metadata() {
+    let element = WithAttributes::new("Results".paragraph())
+        .id("results")
+        .class("wide")
+        .attribute("generator", "bench");
+    let attributes = element.attributes().unwrap();
+    assert_eq!(attributes.get_id(), Some("results"));
+    assert!(attributes.has_class("wide"));
+    assert_eq!(attributes.get("generator"), Some("bench"));
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(&element).unwrap();
+    assert_eq!(String::from_utf8(md.into_inner()).unwrap(), "Results\n\n");
+}
+
+#[cfg(feature = "pandoc")]
+#[test]
+fn element_metadata_pandoc() {
+    use crate::markdown::pandoc::Pandoc;
+    use serde_json::json;
+
+    let mut pandoc = Pandoc::new(Vec::new());
+    pandoc.write(WithAttributes::new("Results".paragraph()).class("wide"));
+    pandoc.write(
+        "Total: "
+            .paragraph()
+            .append(WithAttributes::new("42".bold()).attribute("unit", "ms")),
+    );
+    let json: serde_json::Value = serde_json::from_slice(&pandoc.finish().unwrap()).unwrap();
+    assert_eq!(
+        json["blocks"],
+        json!([
+            {"t": "Div", "c": [["", ["wide"], []], [
+                {"t": "Para", "c": [{"t": "Str", "c": "Results"}]}
+            ]]},
+            {"t": "Para", "c": [
+                {"t": "Str", "c": "Total:"}, {"t": "Space"},
+                {"t": "Span", "c": [["", [], [["unit", "ms"]]], [
+                    {"t": "Strong", "c": [{"t": "Str", "c": "42"}]}
+                ]]}
+            ]}
+        ])
+    );
+}
+//endregion
+
+//region Source map
+#[test]
+fn source_map() {
+    let mut md = Markdown::new(Vec::new()).with_source_map();
+    md.write_labeled("title", "Report".heading(1)).unwrap();
+    let note = md.footnote("Measured on CI.").unwrap();
+    md.write_labeled("summary", "All good".paragraph().append(note))
+        .unwrap();
+    md.write_footnotes().unwrap();
+
+    let map = md.source_map().to_vec();
+    let output = String::from_utf8(md.into_inner()).unwrap();
+    assert_eq!(
+        map,
+        vec![
+            SourceMapEntry {
+                label: Some("title".to_string()),
+                range: 0..9,
+            },
+            SourceMapEntry {
+                label: Some("summary".to_string()),
+                range: 9..23,
+            },
+            SourceMapEntry {
+                label: None,
+                range: 23..output.len(),
+            },
+        ]
+    );
+    assert_eq!(&output[map[1].range.clone()], "All good[^1]\n\n");
+}
+//endregion
+
+//region FieldList
+#[cfg(feature = "derive")]
+#[test]
+fn derive_to_markdown() {
+    use crate::markdown::ToMarkdown;
+
+    #[derive(ToMarkdown)]
+    struct Build {
+        #[markdown(rename = "Target")]
+        target: String,
+        #[markdown(code)]
+        commit: &'static str,
+        #[markdown(skip)]
+        #[allow(dead_code)]
+        secret: u32,
+        jobs: u32,
+    }
+
+    #[derive(ToMarkdown)]
+    #[markdown(style = "list")]
+    struct Owner {
+        name: &'static str,
+    }
+
+    let build = Build {
+        target: "x86_64".to_string(),
+        commit: "abc123",
+        secret: 7,
+        jobs: 4,
+    };
+    let mut md = Markdown::new(Vec::new());
+    md.write(build.to_markdown()).unwrap();
+    md.write(Owner { name: "ci" }.to_markdown()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| Field | Value |\n| --- | --- |\n| Target | x86\\_64 |\n| commit | ` abc123 ` |\n| jobs | 4 |\n\nname\n:   ci\n\n"
+    );
+}
+
+#[test]
+fn field_list_multiline() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(FieldList::definition_list().field("notes", "first\nsecond"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "notes\n:   first\n    second\n\n"
+    );
+}
+//endregion
+
+//region Regeneration
+#[test]
+fn regenerate() {
+    let mut doc = Document::new();
+    doc.push("Intro".paragraph());
+    doc.push("Usage".heading(2));
+    doc.push("Run it.".paragraph());
+    doc.push("Changes".heading(2));
+    doc.push("None yet.".paragraph());
+
+    let options = Options::new();
+    let first = doc.regenerate("", &options).unwrap();
+    assert_eq!(first.changed, vec![0, 1, 2]);
+    assert_eq!(
+        first.output,
+        "<!-- markdown-gen section 0 13f5a7e5a8035111 -->\nIntro\n\n<!-- markdown-gen end -->\n\
+         <!-- markdown-gen section 1 d8d228ec03ca351e -->\n## Usage\nRun it\\.\n\n<!-- markdown-gen end -->\n\
+         <!-- markdown-gen section 2 301253649e64ba92 -->\n## Changes\nNone yet\\.\n\n<!-- markdown-gen end -->\n"
+    );
+    assert!(doc
+        .regenerate(&first.output, &options)
+        .unwrap()
+        .is_unchanged());
+
+    // Manual edits of unchanged sections and text outside of sections are kept
+    let edited = format!(
+        "# Handbook\n\n{}\nWritten by hand.\n",
+        first.output.replace("Run it", "Run it carefully")
+    );
+    doc.remove(4);
+    doc.push("Fixed a bug.".paragraph());
+    doc.push("Changes".heading(2));
+    doc.push("None yet.".paragraph());
+    let second = doc.regenerate(&edited, &options).unwrap();
+    assert_eq!(second.changed, vec![2, 3]);
+    assert_eq!(second.removed, 0);
+    assert!(second
+        .output
+        .starts_with("# Handbook\n\n<!-- markdown-gen section 0 "));
+    assert!(second.output.contains("Run it carefully"));
+    assert!(second.output.contains("Fixed a bug"));
+    assert!(second
+        .output
+        .ends_with("None yet\\.\n\n<!-- markdown-gen end -->\n\nWritten by hand.\n"));
+
+    // Sections are matched by their position
+    assert!(doc
+        .regenerate(&second.output, &options)
+        .unwrap()
+        .is_unchanged());
+    let mut repeated = Document::new();
+    repeated.push("Same".heading(2));
+    repeated.push("Same".heading(2));
+    let output = repeated.regenerate("", &options).unwrap().output;
+    assert!(repeated
+        .regenerate(&output, &options)
+        .unwrap()
+        .is_unchanged());
+    doc.remove(6);
+    doc.remove(5);
+    let third = doc.regenerate(&second.output, &options).unwrap();
+    assert_eq!(third.changed, Vec::<usize>::new());
+    assert_eq!(third.removed, 1);
+
+    let mut telegram = Options::new();
+    telegram.set_flavor(Flavor::Telegram);
+    assert!(doc
+        .regenerate("", &telegram)
+        .unwrap()
+        .output
+        .contains("-->\n*Usage*\n"));
+
+    let mut strict = Options::new();
+    strict.set_strict(true);
+    let mut empty = Document::new();
+    empty.push("");
+    assert!(empty.regenerate("", &strict).is_err());
+}
+//endregion
+
+//region Splitting
+#[test]
+fn document_split() {
+    let mut doc = Document::new();
+    let shared = doc.footnote("Shared note");
+    let local = doc.footnote("Only in usage");
+    doc.push(
+        Paragraph::new()
+            .append("Intro")
+            .append(shared)
+            .append(" see ")
+            .append("docs".link_to("https://docs.rs").reference()),
+    );
+    doc.push("Usage".heading(2));
+    doc.push(
+        Paragraph::new()
+            .append("Run")
+            .append(shared)
+            .append(local)
+            .append(" see ")
+            .append("docs".link_to("https://docs.rs").reference()),
+    );
+    doc.push("Changes".heading(2));
+
+    let parts = doc.split().unwrap();
+    assert_eq!(
+        parts,
+        vec![
+            "Intro[^1] see [docs][docs]\n\n\
+             [^1]: Shared note\n\n\
+             [docs]: https://docs.rs\n\n",
+            "## Usage\nRun[^1][^2] see [docs][docs]\n\n\
+             [^1]: Shared note\n\
+             [^2]: Only in usage\n\n\
+             [docs]: https://docs.rs\n\n",
+            "## Changes\n",
+        ]
+    );
+
+    // Rendered as a whole, every definition is written once at the end
+    assert_eq!(
+        String::from_utf8(doc.render(Vec::new()).unwrap()).unwrap(),
+        "Intro[^1] see [docs][docs]\n\n\
+         ## Usage\nRun[^1][^2] see [docs][docs]\n\n\
+         ## Changes\n\
+         [^1]: Shared note\n\
+         [^2]: Only in usage\n\n\
+         [docs]: https://docs.rs\n\n"
+    );
+
+    doc.push(Failing);
+    assert!(doc.split().is_err());
+    assert!(doc.split_files(|i, _| i.to_string()).is_err());
+}
+#[test]
+fn document_split_files() {
+    let mut doc = Document::new();
+    doc.push("Guide".heading(1));
+    doc.push(
+        Paragraph::new()
+            .append("See ")
+            .append("usage".link_to("#usage"))
+            .append(", ")
+            .append("more usage".link_to("#usage-1"))
+            .append(" and ")
+            .append("this".link_to("#guide")),
+    );
+    doc.push("Usage".heading(2));
+    doc.push("Usage".heading(2));
+    doc.push("Back to ".paragraph().append("guide".link_to("#guide")));
+
+    let files = doc
+        .split_files(|i, anchor| {
+            if anchor.is_empty() {
+                format!("part-{}.md", i)
+            } else {
+                format!("{}-{}.md", i, anchor)
+            }
+        })
+        .unwrap();
+    assert_eq!(
+        files,
+        vec![
+            (
+                "0-guide.md".to_string(),
+                "# Guide\nSee [usage](1-usage.md#usage), [more usage](2-usage.md#usage) \
+                 and [this](#guide)\n\n"
+                    .to_string()
+            ),
+            ("1-usage.md".to_string(), "## Usage\n".to_string()),
+            (
+                "2-usage.md".to_string(),
+                "## Usage\nBack to [guide](0-guide.md#guide)\n\n".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn anchor_namespace() {
+    let mut namespace = AnchorNamespace::new();
+    namespace.add("a.md", &"Intro".heading(1));
+    namespace.add("b.md", &"Intro".heading(1));
+    assert_eq!(namespace.resolve("#intro", "b.md"), "a.md#intro");
+    assert_eq!(namespace.resolve("#intro-1", "b.md"), "#intro");
+    assert_eq!(namespace.resolve("#intro-1", "a.md"), "b.md#intro");
+    assert_eq!(namespace.resolve("#missing", "a.md"), "#missing");
+    namespace.add("c.md", &Heading::new(2).append("Setup").id("intro-2"));
+    namespace.add("d.md", &"Intro".heading(1));
+    assert_eq!(namespace.resolve("#intro-2", "a.md"), "c.md#intro-2");
+    assert_eq!(namespace.resolve("#setup", "a.md"), "c.md#setup");
+    assert_eq!(
+        namespace.resolve("https://x.org/#intro", "a.md"),
+        "https://x.org/#intro"
+    );
+}
+//endregion
+
+//region DocumentDiff
+#[test]
+fn document_diff() {
+    let mut old = Document::new();
+    old.push("Usage".heading(2));
+    old.push("Run it.".paragraph());
+    old.push("Legacy".heading(2));
+    old.push("Deprecated.".paragraph());
+    old.push("Changes".heading(2));
+    old.push("None.".paragraph());
+
+    let mut new = Document::new();
+    new.push("Usage".heading(2));
+    new.push("Run it.".paragraph());
+    new.push("Changes".heading(2));
+    new.push("Fixed `a`.".paragraph());
+    new.push(CodeBlock::new("# not a heading"));
+    new.push("FAQ".heading(2));
+
+    let diff = DocumentDiff::between(&old, &new).unwrap();
+    assert_eq!(
+        diff.changes()[0],
+        SectionChange::Changed {
+            title: Some("Changes".to_string()),
+            old: "None\\.".to_string(),
+            new: "Fixed \\`a\\`\\.\n\n```\n# not a heading\n```".to_string(),
+        }
+    );
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(&diff).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "**Changed:** Changes\n\n\
+         > Fixed \\`a\\`\\.\n>\n> ```\n> # not a heading\n> ```\n\n\
+         **Added:** FAQ\n\n\
+         **Removed:** Legacy\n\n> Deprecated\\.\n\n"
+    );
+    assert!(DocumentDiff::between(&new, &new).unwrap().is_empty());
+    new.push(Failing);
+    assert!(DocumentDiff::between(&old, &new).is_err());
+}
+//endregion
+
+//region Serializer
+#[cfg(feature = "serde")]
+#[test]
+fn serializer() {
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Job {
+        name: &'static str,
+        passed: bool,
+    }
+
+    #[derive(Serialize)]
+    enum Trigger {
+        Push,
+        Schedule { cron: &'static str },
+    }
+
+    #[derive(Serialize)]
+    struct Report {
+        version: (u8, u8),
+        trigger: Trigger,
+        tags: Vec<Trigger>,
+        jobs: Vec<Job>,
+    }
+
+    let jobs = vec![
+        Job {
+            name: "build",
+            passed: true,
+        },
+        Job {
+            name: "lint",
+            passed: false,
+        },
+    ];
+    assert_eq!(
+        crate::ser::to_string(&jobs).unwrap(),
+        "| name | passed |\n| :--- | :--- |\n| build | true |\n| lint | false |\n\n"
+    );
+
+    let report = Report {
+        version: (1, 2),
+        trigger: Trigger::Schedule { cron: "0 * * * *" },
+        tags: vec![Trigger::Push],
+        jobs,
+    };
+    assert_eq!(
+        crate::ser::to_string(&report).unwrap(),
+        "\n   * **version**:\
+         \n      * 1\
+         \n      * 2\
+         \n   * **trigger**:\
+         \n      * **Schedule**:\
+         \n         * **cron**: 0 \\* \\* \\* \\*\
+         \n   * **tags**:\
+         \n      * Push\
+         \n   * **jobs**:\
+         \n      * \
+         \n         * **name**: build\
+         \n         * **passed**: true\
+         \n      * \
+         \n         * **name**: lint\
+         \n         * **passed**: false\n\n"
+    );
+
+    let mut ports = std::collections::BTreeMap::new();
+    ports.insert(443, "https");
+    ports.insert(80, "http");
+    let mut flags = std::collections::BTreeMap::new();
+    flags.insert(true, "enabled");
+    assert_eq!(
+        crate::ser::to_string(&(ports, flags)).unwrap(),
+        "\n   * \
+         \n      * **80**: http\
+         \n      * **443**: https\
+         \n   * \
+         \n      * **true**: enabled\n\n"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn table_from_serialize() {
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Benchmark {
+        name: &'static str,
+        time: f64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        note: Option<&'static str>,
+        targets: Vec<&'static str>,
+    }
+
+    let benchmarks = vec![
+        Benchmark {
+            name: "parse",
+            time: 1.5,
+            note: None,
+            targets: vec!["x86", "arm"],
+        },
+        Benchmark {
+            name: "render",
+            time: 0.25,
+            note: Some("cached"),
+            targets: vec![],
+        },
+    ];
+    let mut md = Markdown::new(Vec::new());
+    md.write(Table::from_serialize(&benchmarks).unwrap())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| name | time | targets | note |\n| :--- | ---: | :--- | :--- |\n\
+         | parse | 1\\.5 | x86, arm |  |\n\
+         | render | 0\\.25 |  | cached |\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Table::from_serialize(&benchmarks)
+            .unwrap()
+            .column_alignment(1, Alignment::Center),
+    )
+    .unwrap();
+    assert!(String::from_utf8(md.into_inner())
+        .unwrap()
+        .contains("| :--- | :---: | :--- | :--- |"));
+
+    let nested = vec![BTreeMap::from([("a", vec![vec![1]])])];
+    assert!(Table::from_serialize(nested).is_err());
+    assert!(Table::from_serialize(vec![1, 2]).is_err());
+}
+//endregion
+
+//region FmtWriter
+#[test]
+fn fmt_writer() {
+    struct Report(&'static str);
+
+    impl std::fmt::Display for Report {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let mut md = Markdown::new(FmtWriter::new(f));
+            md.write(self.0.heading(1)).map_err(|_| std::fmt::Error)
+        }
+    }
+
+    assert_eq!(Report("Über").to_string(), "# Über\n");
+
+    let mut writer = FmtWriter::new(String::new());
+    writer.write_all(&"č".as_bytes()[..1]).unwrap();
+    writer.write_all(&"č".as_bytes()[1..]).unwrap();
+    assert!(writer.write_all(&[0xff]).is_err());
+    assert_eq!(writer.into_inner(), "č");
+}
+
+#[test]
+fn display() {
+    assert_eq!("Intro".heading(2).to_string(), "## Intro");
+    assert_eq!(format!("{}", "a*b".bold()), "**a\\*b**");
+    assert_eq!(
+        Paragraph::new()
+            .append("See ")
+            .append(Link::new("https://example.com").append("docs"))
+            .to_string(),
+        "See [docs](https://example.com)"
+    );
+    assert_eq!(
+        List::new(false).item("one").item("two").to_string(),
+        "   * one\n   * two"
+    );
+    assert_eq!(
+        Table::new()
+            .header(vec!["a"])
+            .rows(vec![vec!["1"]])
+            .to_string(),
+        "| a |\n| --- |\n| 1 |"
+    );
+}
+//endregion
+
+//region AsyncMarkdown
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_markdown() {
+    use crate::markdown::tokio::AsyncMarkdown;
+
+    let mut md = AsyncMarkdown::new(Vec::new());
+    md.write("Report".heading(1)).await.unwrap();
+    let note = md.footnote("Streamed.").unwrap();
+    md.write("Done".paragraph().append(note)).await.unwrap();
+    md.write_footnotes().await.unwrap();
+    md.flush().await.unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "# Report\nDone[^1]\n\n[^1]: Streamed\\.\n\n"
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_markdown_spawn() {
+    use crate::markdown::tokio::AsyncMarkdown;
+
+    assert_send::<AsyncMarkdown<Vec<u8>>>();
+    let output = ::tokio::spawn(async {
+        let mut md = AsyncMarkdown::new(Vec::new()).with_link_rewriter(|address| address.into());
+        md.write("Report".heading(1)).await.unwrap();
+        md.write("Done".paragraph().append(Link::new("log.txt")))
+            .await
+            .unwrap();
+        md.into_inner()
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "# Report\nDone[log\\.txt](log.txt)\n\n"
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn async_markdown_failed_write() {
+    use crate::markdown::tokio::AsyncMarkdown;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+
+    // Accepts 4 bytes at a time and fails once after the first write
+    struct Flaky {
+        written: Vec<u8>,
+        failed: bool,
+    }
+
+    impl ::tokio::io::AsyncWrite for Flaky {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Error>> {
+            if !self.written.is_empty() && !self.failed {
+                self.failed = true;
+                return Poll::Ready(Err(Error::new(ErrorKind::BrokenPipe, "flaky writer")));
+            }
+            let len = buf.len().min(4);
+            self.written.extend_from_slice(&buf[..len]);
+            Poll::Ready(Ok(len))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    let mut md = AsyncMarkdown::new(Flaky {
+        written: Vec::new(),
+        failed: false,
+    });
+    assert!(md.write("Report".heading(1)).await.is_err());
+    md.write("Done".paragraph()).await.unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner().written).unwrap(),
+        "# Report\nDone\n\n"
+    );
+}
+//endregion
+
+//region Send
+fn assert_send<T: Send>() {}
+
+#[test]
+fn markdown_is_send() {
+    assert_send::<Markdown<Vec<u8>>>();
+    assert_send::<Options>();
+    assert_send::<HeadingPath>();
+}
+//endregion
+
+//region Reference links
+#[test]
+fn reference_links() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        "See "
+            .paragraph()
+            .append("Rust docs".link_to("https://doc.rust-lang.org").reference())
+            .append(", ")
+            .append("Rust docs".link_to("https://docs.rs").reference())
+            .append(" and ")
+            .append("rust docs".link_to("https://doc.rust-lang.org").reference()),
+    )
+    .unwrap();
+    md.write_link_definitions().unwrap();
+    md.write_link_definitions().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "See [Rust docs][rust-docs], [Rust docs][rust-docs-2] and [rust docs][rust-docs]\n\n\
+         [rust-docs]: https://doc.rust-lang.org\n\
+         [rust-docs-2]: https://docs.rs\n\n"
+    );
+
+    let mut doc = Document::new();
+    doc.push("Home".link_to("index.html").reference().paragraph());
+    let output = doc.render(Vec::new()).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "[Home][home]\n\n[home]: index.html\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Discord);
+    md.write("Home".link_to("index.html").reference().paragraph())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "[Home](index.html)\n\n"
+    );
+}
+//endregion
+
+//region Escaping policy
+#[test]
+fn escaping_policy() {
+    assert!(needs_escape('*', EscapeContext::Text(Flavor::CommonMark)));
+    assert!(!needs_escape('~', EscapeContext::Text(Flavor::CommonMark)));
+    assert!(needs_escape('~', EscapeContext::Text(Flavor::Gfm)));
+    assert!(needs_escape('=', EscapeContext::Text(Flavor::Telegram)));
+    assert!(!needs_escape('.', EscapeContext::Address(Flavor::Telegram)));
+    assert!(!needs_escape(
+        '`',
+        EscapeContext::InlineCode(Flavor::CommonMark)
+    ));
+    assert!(needs_escape(
+        '`',
+        EscapeContext::InlineCode(Flavor::Telegram)
+    ));
+    assert!(needs_escape('"', EscapeContext::Quoted));
+    assert!(!needs_escape('é', EscapeContext::Text(Flavor::CommonMark)));
+
+    // Policy matches the written output
+    let text = "a*b~c=d.e(f)";
+    for flavor in [
+        Flavor::CommonMark,
+        Flavor::Gfm,
+        Flavor::Telegram,
+        Flavor::Discord,
+    ] {
+        let mut expected = String::new();
+        for c in text.chars() {
+            if needs_escape(c, EscapeContext::Text(flavor)) {
+                expected.push('\\');
+            }
+            expected.push(c);
+        }
+        let mut md = Markdown::new(Vec::new()).with_flavor(flavor);
+        md.write(text.paragraph()).unwrap();
+        assert_eq!(
+            String::from_utf8(md.into_inner()).unwrap(),
+            expected + "\n\n"
+        );
+    }
+}
+//endregion
+
+//region Smart escaping
+#[test]
+fn smart_escaping() {
+    let mut md = Markdown::new(Vec::new()).with_smart_escaping(true);
+    md.write("See test.url, v1.2-rc and snake_case (maybe) 5 + 3 = 8!".paragraph())
+        .unwrap();
+    md.write("# not a heading\n1. not a list\n- nor this\n-5 degrees\n---".paragraph())
+        .unwrap();
+    md.write("> quote with *stars*, _under_ and [brackets]".paragraph())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "See test.url, v1.2-rc and snake_case (maybe) 5 + 3 = 8!\n\n\
+         \\# not a heading\n1\\. not a list\n\\- nor this\n-5 degrees\n\\---\n\n\
+         \\> quote with \\*stars\\*, \\_under\\_ and \\[brackets\\]\n\n"
+    );
+}
+
+#[test]
+fn smart_escaping_chunks() {
+    let mut md = Markdown::new(Vec::new()).with_smart_escaping(true);
+    md.write("1".paragraph().append(". item").append(" a").append("_b"))
+        .unwrap();
+    md.write("x".paragraph().append("1").append(". no list"))
+        .unwrap();
+    md.write(Blockquote::new().append("a\n ".paragraph().append("# b")))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "1\\. item a_b\n\nx1. no list\n\n> a\n>  \\# b\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new())
+        .with_smart_escaping(true)
+        .with_line_width(8, LongLinks::OwnLine);
+    md.write("aaaa 1. bb - cc #".paragraph()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "aaaa 1.\nbb - cc #\n\n"
+    );
+}
+
+#[test]
+fn smart_escaping_flavors() {
+    let text = "a~b|c.d";
+    let expected = [
+        (Flavor::CommonMark, "a~b|c.d\n\n"),
+        (Flavor::Gfm, "a\\~b|c.d\n\n"),
+        (Flavor::Discord, "a\\~b\\|c.d\n\n"),
+        (Flavor::Telegram, "a\\~b\\|c\\.d\n\n"),
+    ];
+    for (flavor, expected) in expected {
+        let mut md = Markdown::new(Vec::new())
+            .with_flavor(flavor)
+            .with_smart_escaping(true);
+        md.write(text.paragraph()).unwrap();
+        assert_eq!(String::from_utf8(md.into_inner()).unwrap(), expected);
+    }
+}
+//endregion
+
+//region Raw
+#[test]
+fn raw() {
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Paragraph::new()
+            .append("Press ")
+            .append(Raw::new("<kbd>Ctrl</kbd>"))
+            .append(" then *"),
+    )
+    .unwrap();
+    md.write(Raw::new("| a | b |\n|---|---|")).unwrap();
+    md.write(Blockquote::new().append(Raw::new("1. *done*\n2. todo")))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Press <kbd>Ctrl</kbd> then \\*\n\n\
+         | a | b |\n|---|---|\n\n\
+         > 1. *done*\n> 2. todo\n\n"
+    );
+}
+//endregion
+
+//region Wrapping
+#[test]
+fn line_width() {
+    let paragraph = Paragraph::new()
+        .append("Read the ")
+        .append("installation guide".link_to("https://example.com/docs/install/linux"))
+        .append(" before running ")
+        .append("cargo build --release".code())
+        .append(" or see the notes below");
+
+    let mut md = Markdown::new(Vec::new()).with_line_width(30, LongLinks::OwnLine);
+    md.write(&paragraph).unwrap();
+    md.write(Blockquote::new().append("one two three four five six seven".paragraph()))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Read the\n\
+         [installation guide](https://example.com/docs/install/linux)\n\
+         before running\n\
+         ` cargo build --release ` or\n\
+         see the notes below\n\n\
+         > one two three four five six\n> seven\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_line_width(30, LongLinks::Reference);
+    md.write(&paragraph).unwrap();
+    md.write_link_definitions().unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "Read the [installation\n\
+         guide][installation-guide]\n\
+         before running\n\
+         ` cargo build --release ` or\n\
+         see the notes below\n\n\
+         [installation-guide]: https://example.com/docs/install/linux\n\n"
+    );
+}
+
+#[test]
+fn wrap_block_starts() {
+    for (flavor, token, expected) in [
+        (Flavor::Gfm, ">", "aaaaaaaaaaa >\nbb\n\n"),
+        (Flavor::Gfm, "=", "aaaaaaaaaaa =\nbb\n\n"),
+        (Flavor::Gfm, "-", "aaaaaaaaaaa\n\\- bb\n\n"),
+        (Flavor::CommonMark, "~~~", "aaaaaaaaaaa ~~~\nbb\n\n"),
+    ] {
+        let mut md = Markdown::new(Vec::new())
+            .with_flavor(flavor)
+            .with_line_width(11, LongLinks::OwnLine);
+        md.write(format!("aaaaaaaaaaa {} bb", token).paragraph())
+            .unwrap();
+        assert_eq!(String::from_utf8(md.into_inner()).unwrap(), expected);
+    }
+
+    let mut md = Markdown::new(Vec::new()).with_line_width(11, LongLinks::OwnLine);
+    md.write(
+        "aaaaaaaaaaa "
+            .paragraph()
+            .append(Raw::new("<div>"))
+            .append(" bb"),
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "aaaaaaaaaaa <div>\nbb\n\n"
+    );
+}
+//endregion
+
+//region Faq
+#[test]
+fn faq() {
+    let faq = Faq::new()
+        .entry("How to <install>?", "Run cargo add.".paragraph())
+        .entry("Is it free?", "Yes".paragraph());
+    let mut md = Markdown::new(Vec::new());
+    md.write(&faq).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "<details>\n<summary>How to &lt;install&gt;?</summary>\n\n\
+         Run cargo add\\.\n\n</details>\n\n\
+         <details>\n<summary>Is it free?</summary>\n\n\
+         Yes\n\n</details>\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Discord);
+    md.write(&faq).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "### How to <install\\>?\nRun cargo add.\n\n### Is it free?\nYes\n\n"
+    );
+    assert_eq!(faq.headings(&Options::new()), vec![]);
+}
+//endregion
+
+//region Html
+#[test]
+fn footer() {
+    let footer = || {
+        Footer::new("report_gen")
+            .version("2.0.0-beta.1+build--7")
+            .timestamp("2024-05-01 12:00")
+    };
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(footer()).unwrap();
+    md.write(Footer::new("report_gen")).unwrap();
+    md.write(footer().comment()).unwrap();
+    md.write(footer().template("Erzeugt von {tool} am {timestamp}"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "*Generated by report\\_gen 2\\.0\\.0\\-beta\\.1\\+build\\-\\-7 on 2024\\-05\\-01 12:00*\n\n\
+         *Generated by report\\_gen*\n\n\
+         <!-- Generated by report_gen 2.0.0-beta.1+build- -7 on 2024-05-01 12:00 -->\n\n\
+         *Erzeugt von report\\_gen am 2024\\-05\\-01 12:00*\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_flavor(Flavor::Telegram);
+    md.write(Footer::new("bot").version("1.0").comment())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "_Generated by bot 1\\.0_\n\n"
+    );
+}
+
+#[test]
+fn html() {
+    let mut md = Markdown::new(Vec::new());
+    md.write("Intro".heading(2)).unwrap();
+    md.write(Html::new(
+        "    <table>\n  <tr><td rowspan=\"2\">a</td></tr>\n\n  <tr></tr>\n</table>\n",
+    ))
+    .unwrap();
+    md.write(Html::new("<pre>\nline\n\nline\n</pre>")).unwrap();
+    md.write(
+        Html::element("<div align=\"center\">", "</div>")
+            .append("Centered *text*".paragraph())
+            .append("Title".heading(3)),
+    )
+    .unwrap();
+    md.write("After".paragraph()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "## Intro\n\
+         <table>\n  <tr><td rowspan=\"2\">a</td></tr>\n  <tr></tr>\n</table>\n\n\
+         <pre>\nline\n\nline\n</pre>\n\n\
+         <div align=\"center\">\n\nCentered \\*text\\*\n\n### Title\n\n</div>\n\n\
+         After\n\n"
+    );
+}
+//endregion
+
+//region Emphasis style
+#[test]
+fn emphasis_style() {
+    let write = |flavor: Flavor| {
+        let mut md = Markdown::new(Vec::new())
+            .with_flavor(flavor)
+            .with_emphasis_style(EmphasisStyle::Underscore);
+        md.write(
+            Paragraph::new()
+                .append("bold".bold())
+                .append(" ")
+                .append("italic".italic())
+                .append(" ")
+                .append("both".bold().italic()),
+        )
+        .unwrap();
+        md.write("Deep".heading(6)).unwrap();
+        String::from_utf8(md.into_inner()).unwrap()
+    };
+    assert_eq!(
+        write(Flavor::CommonMark),
+        "__bold__ _italic_ ___both___\n\n###### Deep\n"
+    );
+    assert_eq!(
+        write(Flavor::Discord),
+        "**bold** _italic_ **_both_**\n\n**Deep**\n\n"
+    );
+}
+
+#[test]
+fn emphasis_mapping() {
+    let write = |flavor: Flavor, italic: Emphasis, bold: Emphasis| {
+        let mut md = Markdown::new(Vec::new())
+            .with_flavor(flavor)
+            .with_emphasis_mapping(italic, bold);
+        md.write(
+            Paragraph::new()
+                .append("bold".bold())
+                .append(" ")
+                .append("italic".italic())
+                .append(" ")
+                .append("both".bold().italic()),
+        )
+        .unwrap();
+        md.write("Deep".heading(4)).unwrap();
+        String::from_utf8(md.into_inner()).unwrap()
+    };
+    assert_eq!(
+        write(Flavor::Discord, Emphasis::Bold, Emphasis::Bold),
+        "**bold** **italic** **both**\n\n**Deep**\n\n"
+    );
+    assert_eq!(
+        write(Flavor::Discord, Emphasis::Plain, Emphasis::Italic),
+        "*bold* italic *both*\n\n*Deep*\n\n"
+    );
+    assert_eq!(
+        write(Flavor::Telegram, Emphasis::Italic, Emphasis::Plain),
+        "bold _italic_ _both_\n\nDeep\n\n"
+    );
+
+    let mut options = Options::new();
+    options.set_flavor(Flavor::Telegram);
+    assert_eq!(
+        options.emphasis_mapping(),
+        Flavor::Telegram.emphasis_mapping()
+    );
+    options.set_emphasis_mapping(Emphasis::Bold, Emphasis::Bold);
+    options.set_flavor(Flavor::Discord);
+    assert_eq!(options.emphasis_mapping(), (Emphasis::Bold, Emphasis::Bold));
+}
+//endregion
+
+//region Empty elements
+#[test]
+fn empty_elements() {
+    let mut md = Markdown::new(Vec::new());
+    md.write("").unwrap();
+    md.write(
+        Paragraph::new()
+            .append("a")
+            .append("".bold())
+            .append("".code())
+            .append("".italic())
+            .append(" ")
+            .append(Link::new("https://example.com")),
+    )
+    .unwrap();
+    md.write(List::new(false).item("first").item("").item("last"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "a [https://example\\.com](https://example.com)\n\n\
+         \n   * first\n   *\n   * last"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_strict(true);
+    for result in [
+        md.write("".bold()),
+        md.write(Link::new("https://example.com")),
+        md.write(List::new(true).item("")),
+    ] {
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+    md.write("text".bold()).unwrap();
+
+    let mut md = Markdown::new(Vec::new())
+        .with_strict(true)
+        .with_source_map();
+    assert!(md.write(List::new(false).item("first").item("")).is_err());
+    md.write("text".paragraph()).unwrap();
+    let range = md.source_map()[0].range.clone();
+    let output = String::from_utf8(md.into_inner()).unwrap();
+    assert!(range.start > 0);
+    assert_eq!(&output[range], "text\n\n");
+
+    let mut md = Markdown::new(Vec::new())
+        .with_strict(true)
+        .with_buffered_writes()
+        .with_source_map();
+    assert!(md.write(List::new(false).item("first").item("")).is_err());
+    md.write("text".paragraph()).unwrap();
+    assert_eq!(md.source_map()[0].range, 0..6);
+    assert_eq!(String::from_utf8(md.into_inner()).unwrap(), "text\n\n");
+}
+//endregion
+
+//region Whitespace policy
+#[test]
+fn whitespace_policy() {
+    let mut md = Markdown::new(Vec::new()).with_whitespace_policy(WhitespacePolicy::Preserve);
+    md.write(
+        Paragraph::new()
+            .append("  indented  twice")
+            .append(" and ")
+            .append(" bold ".bold())
+            .append(" code ".code())
+            .append("end \n  next "),
+    )
+    .unwrap();
+    md.write("plain text".paragraph()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "&nbsp;&nbsp;indented &nbsp;twice and **&nbsp;bold&nbsp;**`  code  `\
+         end&nbsp;\n&nbsp;&nbsp;next&nbsp;\n\n\
+         plain text\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_whitespace_policy(WhitespacePolicy::Reject);
+    md.write("single spaces only".paragraph()).unwrap();
+    assert_eq!(
+        md.write("two  spaces".paragraph()).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert!(md.write(" padded ".italic()).is_err());
+}
+//endregion
+
+//region Tab policy
+#[test]
+fn tab_policy() {
+    let log = "12:00\tINFO\tstarted\n\tdone";
+    let mut md = Markdown::new(Vec::new()).with_tab_policy(TabPolicy::Expand(4));
+    md.write(log.paragraph()).unwrap();
+    md.write("a\tb".code()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "12:00   INFO    started\ndone\n\n` a   b `\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_tab_policy(TabPolicy::Expand(4));
+    md.write("\tindented".paragraph()).unwrap();
+    md.write(Paragraph::new().append("a").append("\tb"))
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "indented\n\na   b\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new()).with_tab_policy(TabPolicy::Reject);
+    assert_eq!(
+        md.write(log.paragraph()).unwrap_err().kind(),
+        ErrorKind::InvalidInput
+    );
+    assert!(md.write("a\tb".code()).is_err());
+}
+//endregion
+
+//region Control characters
+#[test]
+fn control_chars() {
+    let log = "\u{1b}[1;31merror\u{1b}[0m: \u{1b}]8;;https://x.org\u{7}link\u{1b}]8;;\u{1b}\\ failed\r\n\tbell\u{7}";
+    let write = |control: ControlChars| {
+        let mut md = Markdown::new(Vec::new()).with_control_chars(control);
+        md.write(CodeBlock::new(log)).unwrap();
+        md.write("a\u{0}b".code()).unwrap();
+        String::from_utf8(md.into_inner()).unwrap()
+    };
+    assert_eq!(
+        write(ControlChars::Strip),
+        "```\nerror: link failed\n\tbell\n```\n\n` ab `\n\n"
+    );
+    assert_eq!(
+        write(ControlChars::Escape),
+        "```\n␛[1;31merror␛[0m: ␛]8;;https://x.org␇link␛]8;;␛\\ failed␍\n\tbell␇\n```\n\n` a␀b `\n\n"
+    );
+}
+//endregion
+
+//region ANSI output
+#[cfg(feature = "ansi")]
+#[test]
+fn ansi_output() {
+    use crate::markdown::ansi::AnsiOutput;
+
+    let log =
+        "\u{1b}[1;31merror\u{1b}[0m: expected `<T>`\n\u{1b}[38;5;82mok\u{1b}[39m \u{1b}[2Kdone";
+    let mut md = Markdown::new(Vec::new());
+    md.write(AnsiOutput::new(log)).unwrap();
+    md.write(AnsiOutput::new(log).html()).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "```\nerror: expected `<T>`\nok done\n```\n\n\
+         <pre><span style=\"color:#cd0000;font-weight:bold\">error</span>: expected `&lt;T&gt;`\n\
+         <span style=\"color:#5fff00\">ok</span> done</pre>\n\n"
+    );
+}
+//endregion
+
+//region Key order
+#[test]
+fn key_order() {
+    let mut front_matter = FrontMatter::new();
+    front_matter.set("title", "Report");
+    front_matter.set("date", "2021-05-01");
+    let fields = FieldList::table()
+        .field("version", "1.2")
+        .field("author", "me");
+    let write = |order: KeyOrder| {
+        let mut md = Markdown::new(Vec::new()).with_key_order(order);
+        md.write(&front_matter).unwrap();
+        md.write(&fields).unwrap();
+        String::from_utf8(md.into_inner()).unwrap()
+    };
+    assert_eq!(
+        write(KeyOrder::Insertion),
+        "---\ntitle: Report\ndate: 2021-05-01\n---\n\
+         | Field | Value |\n| --- | --- |\n| version | 1\\.2 |\n| author | me |\n\n"
+    );
+    assert_eq!(
+        write(KeyOrder::Sorted),
+        "---\ndate: 2021-05-01\ntitle: Report\n---\n\
+         | Field | Value |\n| --- | --- |\n| author | me |\n| version | 1\\.2 |\n\n"
+    );
+
+    #[cfg(feature = "serde")]
+    {
+        use serde::Serialize;
+        use std::collections::HashMap;
+
+        let rows: Vec<HashMap<&str, u32>> = (0..2)
+            .map(|i| {
+                vec![("c", i), ("a", i + 1), ("b", i + 2)]
+                    .into_iter()
+                    .collect()
+            })
+            .collect();
+        let mut md = Markdown::new(Vec::new()).with_key_order(KeyOrder::Sorted);
+        md.write(rows.serialize(crate::ser::Serializer).unwrap())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(md.into_inner()).unwrap(),
+            "| a | b | c |\n| ---: | ---: | ---: |\n| 1 | 2 | 0 |\n| 2 | 3 | 1 |\n\n"
+        );
+    }
+}
+//endregion
+
+//region Null placeholder
+#[test]
+fn null_placeholder() {
+    let table = || {
+        Table::new()
+            .header(vec!["Job", "Duration"])
+            .row(vec![Some("build"), Some("5 s")])
+            .row(vec![Some("lint"), None])
+    };
+    let mut md = Markdown::new(Vec::new());
+    md.write(table()).unwrap();
+    let mut md = Markdown::new(md.into_inner()).with_null_placeholder("n/a");
+    md.write(table()).unwrap();
+    md.write(None::<&str>).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "| Job | Duration |\n| --- | --- |\n| build | 5 s |\n| lint |  |\n\n\
+         | Job | Duration |\n| --- | --- |\n| build | 5 s |\n| lint | n/a |\n\n\
+         n/a\n\n"
+    );
+
+    #[cfg(feature = "serde")]
+    {
+        use serde::Serialize;
+
+        #[derive(Serialize)]
+        struct Job {
+            name: &'static str,
+            exit_code: Option<i32>,
+        }
+
+        let jobs = vec![
+            Job {
+                name: "build",
+                exit_code: Some(0),
+            },
+            Job {
+                name: "deploy",
+                exit_code: None,
+            },
+        ];
+        let mut md = Markdown::new(Vec::new()).with_null_placeholder("—");
+        md.write(Table::from_serialize(&jobs).unwrap()).unwrap();
+        md.write(jobs.serialize(crate::ser::Serializer).unwrap())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(md.into_inner()).unwrap(),
+            "| name | exit\\_code |\n| :--- | ---: |\n| build | 0 |\n| deploy | — |\n\n\
+             | name | exit\\_code |\n| :--- | ---: |\n| build | 0 |\n| deploy | — |\n\n"
+        );
+    }
+}
+//endregion
+
+//region Math
+#[test]
+fn math() {
+    let write = |flavor: Flavor| {
+        let mut md = Markdown::new(Vec::new()).with_flavor(flavor);
+        md.write(
+            Paragraph::new()
+                .append("Energy ")
+                .append(MathInline::new(" E = mc^2 "))
+                .append(" costs ")
+                .append(MathInline::new(r"\$5 \cdot x_1")),
+        )
+        .unwrap();
+        md.write(MathBlock::new("\\sum_{i=1}^n i = \\frac{n(n+1)}{2}\n"))
+            .unwrap();
+        String::from_utf8(md.into_inner()).unwrap()
+    };
+    assert_eq!(
+        write(Flavor::Gfm),
+        "Energy $E = mc^2$ costs $\\$5 \\cdot x_1$\n\n\
+         $$\n\\sum_{i=1}^n i = \\frac{n(n+1)}{2}\n$$\n\n"
+    );
+    assert_eq!(
+        write(Flavor::Discord),
+        "Energy ` E = mc^2 ` costs ` \\$5 \\cdot x_1 `\n\n\
+         ```latex\n\\sum_{i=1}^n i = \\frac{n(n+1)}{2}\n```\n\n"
+    );
+
+    let mut md = Markdown::new(Vec::new());
+    md.write(
+        Paragraph::new()
+            .append("a")
+            .append(MathInline::new(" "))
+            .append(MathInline::new("x$y \\\\$z")),
+    )
+    .unwrap();
+    md.write(MathBlock::new("\n")).unwrap();
+    md.write(MathBlock::new("a\n$$\nb")).unwrap();
+    assert_eq!(
+        String::from_utf8(md.into_inner()).unwrap(),
+        "a$x\\$y \\\\\\$z$\n\n```math\na\n$$\nb\n```\n\n"
+    );
+}
+//endregion
@@ -0,0 +1,292 @@
+//! Writing markdown to a `tokio::io::AsyncWrite`
+//!
+//! Available with the `tokio` feature.
+
+use super::{
+    Bullet, ControlChars, Emphasis, EmphasisStyle, Flavor, FootnoteRef, HeadingPath, InlineElement,
+    KeyOrder, LineBreakStyle, LongLinks, Markdown, MarkdownWritable, TabPolicy, TextRole, Warning,
+    WhitespacePolicy,
+};
+use ::tokio::io::{AsyncWrite, AsyncWriteExt};
+use std::future::Future;
+use std::io;
+
+/// Struct for generating Markdown into an asynchronous writer
+///
+/// Every element is rendered into a buffer and then written to `writer`,
+/// so the output is the same as of [Markdown](../struct.Markdown.html).
+pub struct AsyncMarkdown<W: AsyncWrite + Unpin> {
+    writer: W,
+    markdown: Markdown<Vec<u8>>,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncMarkdown<W> {
+    /// Creates a new [AsyncMarkdown](struct.AsyncMarkdown.html) struct
+    ///
+    /// # Arguments
+    /// * `writer` - Destination for Markdown data
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            markdown: Markdown::new(Vec::new()),
+        }
+    }
+
+    /// Sets a transform applied to text with `role`,
+    /// see [Markdown::with_transform](../struct.Markdown.html#method.with_transform)
+    pub fn with_transform<F: 'static + Fn(&str) -> String + Send + Sync>(
+        mut self,
+        role: TextRole,
+        transform: F,
+    ) -> Self {
+        self.markdown = self.markdown.with_transform(role, transform);
+        self
+    }
+
+    /// Sets the target platform, see [Markdown::with_flavor](../struct.Markdown.html#method.with_flavor)
+    pub fn with_flavor(mut self, flavor: Flavor) -> Self {
+        self.markdown = self.markdown.with_flavor(flavor);
+        self
+    }
+
+    /// Escapes characters in text only where they would be parsed as markdown,
+    /// see [Markdown::with_smart_escaping](../struct.Markdown.html#method.with_smart_escaping)
+    pub fn with_smart_escaping(mut self, smart: bool) -> Self {
+        self.markdown = self.markdown.with_smart_escaping(smart);
+        self
+    }
+
+    /// Wraps paragraphs to lines of at most `width` characters,
+    /// see [Markdown::with_line_width](../struct.Markdown.html#method.with_line_width)
+    pub fn with_line_width(mut self, width: usize, long_links: LongLinks) -> Self {
+        self.markdown = self.markdown.with_line_width(width, long_links);
+        self
+    }
+
+    /// Sets the delimiters of italic and bold text,
+    /// see [Markdown::with_emphasis_style](../struct.Markdown.html#method.with_emphasis_style)
+    pub fn with_emphasis_style(mut self, style: EmphasisStyle) -> Self {
+        self.markdown = self.markdown.with_emphasis_style(style);
+        self
+    }
+
+    /// Sets the form of hard line breaks,
+    /// see [Markdown::with_line_break_style](../struct.Markdown.html#method.with_line_break_style)
+    pub fn with_line_break_style(mut self, style: LineBreakStyle) -> Self {
+        self.markdown = self.markdown.with_line_break_style(style);
+        self
+    }
+
+    /// Sets the forms italic and bold text are written in,
+    /// see [Markdown::with_emphasis_mapping](../struct.Markdown.html#method.with_emphasis_mapping)
+    pub fn with_emphasis_mapping(mut self, italic: Emphasis, bold: Emphasis) -> Self {
+        self.markdown = self.markdown.with_emphasis_mapping(italic, bold);
+        self
+    }
+
+    /// Makes writing empty elements fail,
+    /// see [Markdown::with_strict](../struct.Markdown.html#method.with_strict)
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.markdown = self.markdown.with_strict(strict);
+        self
+    }
+
+    /// Sets the handling of spaces which markdown would drop or collapse,
+    /// see [Markdown::with_whitespace_policy](../struct.Markdown.html#method.with_whitespace_policy)
+    pub fn with_whitespace_policy(mut self, policy: WhitespacePolicy) -> Self {
+        self.markdown = self.markdown.with_whitespace_policy(policy);
+        self
+    }
+
+    /// Sets the handling of tabs in text and inline code,
+    /// see [Markdown::with_tab_policy](../struct.Markdown.html#method.with_tab_policy)
+    pub fn with_tab_policy(mut self, policy: TabPolicy) -> Self {
+        self.markdown = self.markdown.with_tab_policy(policy);
+        self
+    }
+
+    /// Sets the marker of bulleted list items and the indentation of nested lists,
+    /// see [Markdown::with_list_style](../struct.Markdown.html#method.with_list_style)
+    pub fn with_list_style(mut self, bullet: Bullet, indent: usize) -> Self {
+        self.markdown = self.markdown.with_list_style(bullet, indent);
+        self
+    }
+
+    /// Sets the handling of control characters,
+    /// see [Markdown::with_control_chars](../struct.Markdown.html#method.with_control_chars)
+    pub fn with_control_chars(mut self, control: ControlChars) -> Self {
+        self.markdown = self.markdown.with_control_chars(control);
+        self
+    }
+
+    /// Sets the order of keys of elements built from maps,
+    /// see [Markdown::with_key_order](../struct.Markdown.html#method.with_key_order)
+    pub fn with_key_order(mut self, order: KeyOrder) -> Self {
+        self.markdown = self.markdown.with_key_order(order);
+        self
+    }
+
+    /// Sets the text written for `None` values,
+    /// see [Markdown::with_null_placeholder](../struct.Markdown.html#method.with_null_placeholder)
+    pub fn with_null_placeholder<T: Into<String>>(mut self, placeholder: T) -> Self {
+        self.markdown = self.markdown.with_null_placeholder(placeholder);
+        self
+    }
+
+    /// Sets a callback rewriting every link and image address,
+    /// see [Markdown::with_link_rewriter](../struct.Markdown.html#method.with_link_rewriter)
+    pub fn with_link_rewriter<F: 'static + Fn(&str) -> String + Send + Sync>(
+        mut self,
+        rewriter: F,
+    ) -> Self {
+        self.markdown = self.markdown.with_link_rewriter(rewriter);
+        self
+    }
+
+    /// Sets a callback receiving non-fatal issues,
+    /// see [Markdown::with_warning_handler](../struct.Markdown.html#method.with_warning_handler)
+    pub fn with_warning_handler<F: 'static + Fn(&Warning) + Send + Sync>(
+        mut self,
+        handler: F,
+    ) -> Self {
+        self.markdown = self.markdown.with_warning_handler(handler);
+        self
+    }
+
+    /// Numbers headings automatically,
+    /// see [Markdown::with_heading_numbers](../struct.Markdown.html#method.with_heading_numbers)
+    pub fn with_heading_numbers(mut self) -> Self {
+        self.markdown = self.markdown.with_heading_numbers();
+        self
+    }
+
+    /// Restarts numbering of headings at `1.`, e.g. before appendices
+    pub fn reset_heading_numbers(&mut self) {
+        self.markdown.reset_heading_numbers();
+    }
+
+    /// Returns the underlying `writer`
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes a [MarkdownWritable](../trait.MarkdownWritable.html) to the document
+    ///
+    /// The element is rendered before the returned future is polled, so the future is `Send`
+    /// even if the element is not.
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn write<T: MarkdownWritable>(
+        &mut self,
+        element: T,
+    ) -> impl Future<Output = Result<(), io::Error>> + '_ {
+        let written = self.markdown.write(element);
+        self.send_written(written)
+    }
+
+    /// Writes `element` with all headings shifted down by `level_offset` levels,
+    /// see [Markdown::write_nested](../struct.Markdown.html#method.write_nested)
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn write_nested<T: MarkdownWritable>(
+        &mut self,
+        element: T,
+        level_offset: usize,
+    ) -> impl Future<Output = Result<(), io::Error>> + '_ {
+        let written = self.markdown.write_nested(element, level_offset);
+        self.send_written(written)
+    }
+
+    /// Writes a [Toc](../struct.Toc.html) of all headings written so far
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub async fn write_toc(&mut self) -> Result<(), io::Error> {
+        self.markdown.write_toc()?;
+        self.send().await
+    }
+
+    /// Writes `title` as a heading and enters the section until [end_section](#method.end_section) is called,
+    /// see [Markdown::section](../struct.Markdown.html#method.section)
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub fn begin_section<T: InlineElement>(
+        &mut self,
+        title: T,
+    ) -> impl Future<Output = Result<(), io::Error>> + '_ {
+        let written = self.markdown.begin_section(title);
+        self.send_written(written)
+    }
+
+    /// Leaves the innermost section started by [begin_section](#method.begin_section)
+    pub fn end_section(&mut self) {
+        self.markdown.end_section();
+    }
+
+    /// Returns the level of a heading of a section started now
+    pub fn section_level(&self) -> usize {
+        self.markdown.section_level()
+    }
+
+    /// Returns titles of the headings enclosing the next element,
+    /// see [Markdown::heading_path](../struct.Markdown.html#method.heading_path)
+    pub fn heading_path(&self) -> HeadingPath {
+        self.markdown.heading_path()
+    }
+
+    /// Adds a footnote, see [Markdown::footnote](../struct.Markdown.html#method.footnote)
+    pub fn footnote<T: MarkdownWritable>(&mut self, body: T) -> Result<FootnoteRef, io::Error> {
+        self.markdown.footnote(body)
+    }
+
+    /// Writes definitions of footnotes added since the last call
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub async fn write_footnotes(&mut self) -> Result<(), io::Error> {
+        self.markdown.write_footnotes()?;
+        self.send().await
+    }
+
+    /// Writes definitions of reference links written since the last call
+    ///
+    /// # Returns
+    /// `()` or `std::io::Error` if an error occurred during writing to the underlying writer
+    pub async fn write_link_definitions(&mut self) -> Result<(), io::Error> {
+        self.markdown.write_link_definitions()?;
+        self.send().await
+    }
+
+    /// Flushes the underlying writer
+    pub async fn flush(&mut self) -> Result<(), io::Error> {
+        self.writer.flush().await
+    }
+
+    /// Sends output of an element rendered by `written`
+    async fn send_written(&mut self, written: Result<(), io::Error>) -> Result<(), io::Error> {
+        written?;
+        self.send().await
+    }
+
+    /// Moves rendered markdown from the buffer to `writer`
+    ///
+    /// Bytes are removed from the buffer as soon as they are written,
+    /// so a failed write doesn't send them again.
+    async fn send(&mut self) -> Result<(), io::Error> {
+        let buffer = &mut self.markdown.writer;
+        while !buffer.is_empty() {
+            match self.writer.write(buffer).await {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    buffer.drain(..written);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,675 @@
+//! Serializer writing any `Serialize` type as markdown
+//!
+//! Available with the `serde` feature.
+//! Maps and structs are written as tables, or as lists when they contain nested values,
+//! sequences are written as bulleted lists, or as tables if all items are flat maps
+//! with the same keys. Keys are written in the serialization order unless
+//! [KeyOrder::Sorted](../markdown/enum.KeyOrder.html#variant.Sorted) is set.
+//! [Table::from_serialize](../markdown/struct.Table.html#method.from_serialize) creates a table
+//! from an iterator of structs. Columns of numbers are right-aligned, other columns left-aligned.
+
+use crate::markdown::{
+    display, Alignment, AsMarkdown, BlockElement, KeyOrder, List, Markdown, MarkdownWritable,
+    Paragraph, RenderContext, Table,
+};
+use serde::ser::{self, Serialize};
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+/// Error returned when a value cannot be serialized
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+enum Node {
+    Null,
+    Scalar(String),
+    Number(String),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    fn is_scalar(&self) -> bool {
+        matches!(self, Node::Null | Node::Scalar(_) | Node::Number(_))
+    }
+
+    /// Returns entries of a map in `order`
+    fn entries(&self, order: KeyOrder) -> Vec<(&str, &Node)> {
+        let mut entries: Vec<_> = match self {
+            Node::Map(entries) => entries
+                .iter()
+                .map(|(key, value)| (key.as_str(), value))
+                .collect(),
+            _ => Vec::new(),
+        };
+        if order == KeyOrder::Sorted {
+            entries.sort_by_key(|(key, _)| *key);
+        }
+        entries
+    }
+
+    /// Returns keys of a map with only scalar values in `order`
+    fn flat_keys(&self, order: KeyOrder) -> Option<Vec<&str>> {
+        match self {
+            Node::Map(entries) if entries.iter().all(|(_, value)| value.is_scalar()) => Some(
+                self.entries(order)
+                    .into_iter()
+                    .map(|(key, _)| key)
+                    .collect(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns text of a scalar, `None` for null values written as the null placeholder
+    fn scalar(&self) -> Option<&str> {
+        match self {
+            Node::Scalar(text) | Node::Number(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// Value converted by [Serializer](struct.Serializer.html), writable as markdown
+pub struct Serialized(Node);
+
+/// Serializer converting values to [Serialized](struct.Serialized.html) elements
+///
+/// `value.serialize(Serializer)` returns an element which can be passed to
+/// [Markdown::write](../markdown/struct.Markdown.html#method.write).
+pub struct Serializer;
+
+/// Serializes `value` and writes it as markdown to `writer`
+///
+/// # Returns
+/// `writer` or `std::io::Error` if the value cannot be serialized or an error occurred during writing
+pub fn to_writer<W: Write, T: Serialize + ?Sized>(writer: W, value: &T) -> Result<W, io::Error> {
+    let element = value
+        .serialize(Serializer)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let mut md = Markdown::new(writer);
+    md.write(element)?;
+    Ok(md.into_inner())
+}
+
+/// Serializes `value` to a markdown string
+///
+/// # Returns
+/// Markdown or [Error](struct.Error.html) if the value cannot be serialized or written
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let element = value.serialize(Serializer)?;
+    let mut md = Markdown::new(Vec::new());
+    md.write(element).map_err(ser::Error::custom)?;
+    Ok(String::from_utf8_lossy(&md.into_inner()).into_owned())
+}
+
+fn scalar<T: ToString>(value: T) -> Result<Serialized, Error> {
+    Ok(Serialized(Node::Scalar(value.to_string())))
+}
+
+fn number<T: ToString>(value: T) -> Result<Serialized, Error> {
+    Ok(Serialized(Node::Number(value.to_string())))
+}
+
+/// Right-aligns a column of numbers, other columns are left-aligned, null values are ignored
+fn infer_alignment<'n, I: IntoIterator<Item = &'n Node>>(column: I) -> Alignment {
+    let mut numbers = false;
+    for node in column {
+        match node {
+            Node::Null => {}
+            Node::Number(_) => numbers = true,
+            _ => return Alignment::Left,
+        }
+    }
+    if numbers {
+        Alignment::Right
+    } else {
+        Alignment::Left
+    }
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Serialized;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Serialized, Error> {
+        scalar(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Serialized, Error> {
+        number(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Serialized, Error> {
+        scalar(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Serialized, Error> {
+        scalar(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Serialized, Error> {
+        let bytes = v.iter().map(|b| Node::Number(b.to_string())).collect();
+        Ok(Serialized(Node::Seq(bytes)))
+    }
+
+    fn serialize_none(self) -> Result<Serialized, Error> {
+        Ok(Serialized(Node::Null))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Serialized, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Serialized, Error> {
+        scalar("")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Serialized, Error> {
+        scalar("")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Serialized, Error> {
+        scalar(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Serialized, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Serialized, Error> {
+        let value = value.serialize(self)?.0;
+        Ok(Serialized(Node::Map(vec![(variant.to_string(), value)])))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, Error> {
+        Ok(SeqSerializer {
+            variant: Some(variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer {
+            variant: None,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer, Error> {
+        Ok(MapSerializer {
+            variant: Some(variant),
+            entries: Vec::with_capacity(len),
+            key: None,
+        })
+    }
+}
+
+/// Wraps `node` in a single-entry map named after the enum variant
+fn wrap_variant(variant: Option<&'static str>, node: Node) -> Serialized {
+    match variant {
+        Some(variant) => Serialized(Node::Map(vec![(variant.to_string(), node)])),
+        None => Serialized(node),
+    }
+}
+
+#[doc(hidden)]
+pub struct SeqSerializer {
+    variant: Option<&'static str>,
+    items: Vec<Node>,
+}
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(Serializer)?.0);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Serialized, Error> {
+        Ok(wrap_variant(self.variant, Node::Seq(self.items)))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        self.finish()
+    }
+}
+
+#[doc(hidden)]
+pub struct MapSerializer {
+    variant: Option<&'static str>,
+    entries: Vec<(String, Node)>,
+    key: Option<String>,
+}
+
+impl MapSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), Error> {
+        self.entries.push((key, value.serialize(Serializer)?.0));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Serialized, Error> {
+        Ok(wrap_variant(self.variant, Node::Map(self.entries)))
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        match key.serialize(Serializer)?.0 {
+            Node::Scalar(key) | Node::Number(key) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(Error("map keys must be scalars".to_string())),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().unwrap_or_default();
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer {
+    type Ok = Serialized;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Serialized, Error> {
+        self.finish()
+    }
+}
+
+/// Writes maps as lists of `**key**: value` items and sequences as bulleted lists
+fn to_list(node: &Node, order: KeyOrder) -> List<'_> {
+    let mut list = List::new(false);
+    match node {
+        Node::Null | Node::Scalar(_) | Node::Number(_) => list = list.item(node.scalar()),
+        Node::Seq(items) => {
+            for item in items {
+                list = match item {
+                    Node::Null | Node::Scalar(_) | Node::Number(_) => list.item(item.scalar()),
+                    _ => list.item(to_list(item, order)),
+                };
+            }
+        }
+        Node::Map(_) => {
+            for (key, value) in node.entries(order) {
+                list = match value {
+                    Node::Null | Node::Scalar(_) | Node::Number(_) => list.item(
+                        Paragraph::new()
+                            .append(key.bold())
+                            .append(": ")
+                            .append(value.scalar()),
+                    ),
+                    _ => list.item(to_list(value, order).title(key.bold()).title(":")),
+                };
+            }
+        }
+    }
+    list
+}
+
+/// Creates a table if `node` is a flat map or a sequence of flat maps with the same keys
+fn table(node: &Node, order: KeyOrder) -> Option<Table<'_>> {
+    match node {
+        Node::Map(_) => {
+            node.flat_keys(order)?;
+            let entries = node.entries(order);
+            let mut table = Table::new().header(vec!["Key", "Value"]).alignment(vec![
+                Alignment::Left,
+                infer_alignment(entries.iter().map(|(_, value)| *value)),
+            ]);
+            for (key, value) in entries {
+                table = table.row(vec![Some(key), value.scalar()]);
+            }
+            Some(table)
+        }
+        Node::Seq(items) => {
+            let keys = items.first()?.flat_keys(order)?;
+            if items
+                .iter()
+                .any(|item| item.flat_keys(order).as_ref() != Some(&keys))
+            {
+                return None;
+            }
+            let alignment: Vec<Alignment> = (0..keys.len())
+                .map(|column| {
+                    infer_alignment(items.iter().map(|item| item.entries(order)[column].1))
+                })
+                .collect();
+            let mut table = Table::new().header(keys).alignment(alignment);
+            for item in items {
+                table = table.row(
+                    item.entries(order)
+                        .into_iter()
+                        .map(|(_, value)| value.scalar()),
+                );
+            }
+            Some(table)
+        }
+        Node::Null | Node::Scalar(_) | Node::Number(_) => None,
+    }
+}
+
+impl Table<'static> {
+    /// Creates a table with one row per serialized item, e.g. of a `Vec` of structs
+    ///
+    /// The header contains field names in the order they first appear,
+    /// fields missing in an item and null values are written as the
+    /// [null placeholder](struct.Markdown.html#method.with_null_placeholder).
+    /// Sequences of scalars are written as comma-separated values.
+    /// Columns of numbers are right-aligned and other columns left-aligned,
+    /// see [column_alignment](#method.column_alignment) to override it.
+    ///
+    /// # Returns
+    /// Table or `Error` if an item is not a struct or a map or a field contains nested structs
+    pub fn from_serialize<I, T>(items: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Serialize,
+    {
+        let mut keys: Vec<String> = Vec::new();
+        let mut rows = Vec::new();
+        for item in items {
+            let entries = match item.serialize(Serializer)?.0 {
+                Node::Map(entries) => entries,
+                _ => return Err(Error("table rows must be structs or maps".to_string())),
+            };
+            let mut row: Vec<Node> = (0..keys.len()).map(|_| Node::Null).collect();
+            for (key, value) in entries {
+                let index = match keys.iter().position(|k| *k == key) {
+                    Some(index) => index,
+                    None => {
+                        keys.push(key);
+                        row.push(Node::Null);
+                        keys.len() - 1
+                    }
+                };
+                row[index] = value;
+            }
+            rows.push(row);
+        }
+        for row in &mut rows {
+            row.resize_with(keys.len(), || Node::Null);
+        }
+        let alignment: Vec<Alignment> = (0..keys.len())
+            .map(|column| infer_alignment(rows.iter().map(|row| &row[column])))
+            .collect();
+        let mut table = Table::new().header(keys).alignment(alignment);
+        for row in rows {
+            table = table.row(row.into_iter().map(cell).collect::<Result<Vec<_>, _>>()?);
+        }
+        Ok(table)
+    }
+}
+
+/// Converts a field value to text of a table cell, `None` for null values
+fn cell(node: Node) -> Result<Option<String>, Error> {
+    match node {
+        Node::Null => Ok(None),
+        Node::Scalar(text) | Node::Number(text) => Ok(Some(text)),
+        Node::Seq(items) if items.iter().all(Node::is_scalar) => Ok(Some(
+            items
+                .iter()
+                .map(|item| item.scalar().unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )),
+        _ => Err(Error(
+            "nested values cannot be written in a table cell".to_string(),
+        )),
+    }
+}
+
+impl MarkdownWritable for &'_ Serialized {
+    fn write_to(
+        &self,
+        writer: &mut dyn Write,
+        context: &RenderContext<'_>,
+    ) -> Result<(), io::Error> {
+        let RenderContext { inner, options, .. } = *context;
+        match &self.0 {
+            node @ (Node::Null | Node::Scalar(_) | Node::Number(_)) if inner => {
+                node.scalar().write_to(writer, context)
+            }
+            node @ (Node::Null | Node::Scalar(_) | Node::Number(_)) => Paragraph::new()
+                .append(node.scalar())
+                .write_to(writer, context),
+            node => {
+                if !inner {
+                    if let Some(table) = table(node, options.key_order()) {
+                        return table.write_to(writer, context);
+                    }
+                }
+                to_list(node, options.key_order()).write_to(writer, context)?;
+                if !inner {
+                    writer.write_all(b"\n\n")?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn count_max_streak(&self, _char: u8, _carry: usize) -> (usize, usize) {
+        (0, 0)
+    }
+}
+
+impl MarkdownWritable for Serialized {
+    fn write_to(
+        &self,
+        writer: &mut dyn Write,
+        context: &RenderContext<'_>,
+    ) -> Result<(), io::Error> {
+        (&self).write_to(writer, context)
+    }
+
+    fn count_max_streak(&self, char: u8, carry: usize) -> (usize, usize) {
+        (&self).count_max_streak(char, carry)
+    }
+}
+
+impl fmt::Display for Serialized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        display(&self, f)
+    }
+}
+
+impl BlockElement for Serialized {}
+impl BlockElement for &'_ Serialized {}